@@ -74,10 +74,7 @@ enum State {
 
 impl State {
   pub fn finished(&self) -> bool {
-    match self {
-      State::Finished => true,
-      _ => false,
-    }
+    matches!(self, State::Finished)
   }
 }
 
@@ -508,7 +505,7 @@ mod tests {
         messages: Vec::new(),
         sysex: Vec::new(),
         num_events: 0,
-        max_events: usize::max_value(),
+        max_events: usize::MAX,
       }
     }
   }
@@ -522,11 +519,11 @@ mod tests {
       MockedCallbacks { state }
     }
 
-    pub fn state(&self) -> Ref<MockedCallbacksState> {
+    pub fn state(&self) -> Ref<'_, MockedCallbacksState> {
       (*self.state).borrow()
     }
 
-    pub fn state_mut(&self) -> RefMut<MockedCallbacksState> {
+    pub fn state_mut(&self) -> RefMut<'_, MockedCallbacksState> {
       (*self.state).borrow_mut()
     }
 
@@ -587,7 +584,7 @@ mod tests {
   #[test]
   fn reserved_status() {
     decoder_with_mocked_callbacks(&mut [0u8; 2], |mut callbacks, mut decoder| {
-      let data = vec![0b1111_0100, 0b1111_0101, 0b1111_1001, 0b1111_1101];
+      let data = [0b1111_0100, 0b1111_0101, 0b1111_1001, 0b1111_1101];
       let mut source = data.iter();
 
       assert_eq!(
@@ -612,7 +609,7 @@ mod tests {
   #[test]
   fn unexpected_status() {
     decoder_with_mocked_callbacks(&mut [0u8; 2], |mut callbacks, mut decoder| {
-      let data = vec![0b1000_0000, 64, 0b1000_0001, 0b1000_0010, 12];
+      let data = [0b1000_0000, 64, 0b1000_0001, 0b1000_0010, 12];
       let mut source = data.iter();
 
       assert_eq!(
@@ -769,7 +766,7 @@ mod tests {
   #[allow(clippy::inconsistent_digit_grouping)]
   fn decode_mtc_quarter_frame() {
     decodes_successfully(
-      vec![0b1111_0001u8, 0b0_101_1010],
+      vec![0b1111_0001u8, 0b0101_1010],
       vec![Message::MTCQuarterFrame {
         msg_type: 0b101,
         value: 0b1010,
@@ -838,7 +835,7 @@ mod tests {
   #[test]
   fn decode_reserved() {
     decoder_with_mocked_callbacks(&mut [], |mut callbacks, mut decoder| {
-      let data = vec![0b1111_0100u8, 0b1111_0101, 0b1111_1001, 0b1111_1101];
+      let data = [0b1111_0100u8, 0b1111_0101, 0b1111_1001, 0b1111_1101];
       let mut source = data.iter();
 
       assert_eq!(
@@ -880,7 +877,7 @@ mod tests {
   #[test]
   fn sysex_buffer_overflow() {
     decoder_with_mocked_callbacks(&mut [0u8; 2], |mut callbacks, mut decoder| {
-      let data = vec![0b1111_0000u8, 1, 2, 3, 4, 0b1111_0111];
+      let data = [0b1111_0000u8, 1, 2, 3, 4, 0b1111_0111];
       assert_eq!(
         decoder.decode(&mut data.iter(), &mut callbacks),
         Err(Error::DataBufferOverflow)
@@ -891,7 +888,7 @@ mod tests {
   #[test]
   fn decode_sysex_continuous() {
     decoder_with_mocked_callbacks(&mut [0u8; 4], |mut callbacks, mut decoder| {
-      let data = vec![0b1111_0000u8, 1, 2, 3, 4, 0b1111_0111];
+      let data = [0b1111_0000u8, 1, 2, 3, 4, 0b1111_0111];
 
       assert_eq!(decoder.decode(&mut data.iter(), &mut callbacks), Ok(()));
       assert_eq!(callbacks.state().sysex, vec![1u8, 2, 3, 4]);
@@ -953,7 +950,7 @@ mod tests {
         );
       });
 
-      let source = vec![0b1111_0111];
+      let source = [0b1111_0111];
       assert_eq!(decoder.decode(&mut source.iter(), &mut callbacks), Ok(()));
 
       assert_eq!(
@@ -972,7 +969,7 @@ mod tests {
   #[test]
   fn decode_stop() {
     decoder_with_mocked_callbacks(&mut [0u8; 2], |mut callbacks, mut decoder| {
-      let data = vec![0b1000_0000, 64, 0b1111_1000u8, 12, 0b1111_1010u8];
+      let data = [0b1000_0000, 64, 0b1111_1000u8, 12, 0b1111_1010u8];
       let mut source = data.iter();
       callbacks.state_mut().max_events = 1;
 
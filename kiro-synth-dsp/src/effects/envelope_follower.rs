@@ -0,0 +1,111 @@
+use crate::float::Float;
+use crate::funcs::denormals::flush_denormal;
+
+/// Tracks the level of a signal rectifying it and smoothing it with
+/// different time constants for when it rises (attack) and falls (release).
+#[derive(Debug, Clone)]
+pub struct EnvelopeFollower<F: Float> {
+  sample_rate: F,
+  attack_sec: F,
+  release_sec: F,
+  attack_coefficient: F,
+  release_coefficient: F,
+  level: F,
+}
+
+impl<F: Float> EnvelopeFollower<F> {
+  pub fn new(sample_rate: F, attack_sec: F, release_sec: F) -> Self {
+    let mut follower = EnvelopeFollower {
+      sample_rate,
+      attack_sec,
+      release_sec,
+      attack_coefficient: F::zero(),
+      release_coefficient: F::zero(),
+      level: F::zero(),
+    };
+    follower.set_attack_time_sec(attack_sec);
+    follower.set_release_time_sec(release_sec);
+    follower
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.sample_rate = sample_rate;
+    self.set_attack_time_sec(self.attack_sec);
+    self.set_release_time_sec(self.release_sec);
+  }
+
+  /// Time constant to follow a rising level
+  pub fn set_attack_time_sec(&mut self, attack_sec: F) {
+    self.attack_sec = attack_sec.max(F::zero());
+    self.attack_coefficient = self.coefficient(self.attack_sec);
+  }
+
+  /// Time constant to follow a falling level
+  pub fn set_release_time_sec(&mut self, release_sec: F) {
+    self.release_sec = release_sec.max(F::zero());
+    self.release_coefficient = self.coefficient(self.release_sec);
+  }
+
+  fn coefficient(&self, time_sec: F) -> F {
+    let samples = time_sec * self.sample_rate;
+    if samples > F::zero() {
+      (F::one().neg() / samples).exp()
+    } else {
+      F::zero()
+    }
+  }
+
+  /// Level after the last processed sample
+  pub fn get_level(&self) -> F {
+    self.level
+  }
+
+  pub fn reset(&mut self) {
+    self.level = F::zero();
+  }
+
+  pub fn process(&mut self, input: F) -> F {
+    let input = input.abs();
+    let coefficient = if input > self.level {
+      self.attack_coefficient
+    } else {
+      self.release_coefficient
+    };
+    self.level = flush_denormal(input + (self.level - input) * coefficient);
+    self.level
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::effects::envelope_follower::EnvelopeFollower;
+
+  #[test]
+  fn follows_a_step_with_the_time_constants() {
+    // attack of 10 samples and release of 100 samples
+    let mut follower = EnvelopeFollower::new(1000.0, 0.01f64, 0.1);
+
+    for _ in 0..10 {
+      follower.process(-1.0);
+    }
+    // after one time constant it covers 1 - 1/e of the step
+    assert!((follower.get_level() - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+
+    for _ in 0..1000 {
+      follower.process(1.0);
+    }
+    assert!((follower.get_level() - 1.0).abs() < 1e-9);
+
+    for _ in 0..100 {
+      follower.process(0.0);
+    }
+    assert!((follower.get_level() - (-1.0f64).exp()).abs() < 1e-9);
+  }
+
+  #[test]
+  fn zero_times_follow_instantly() {
+    let mut follower = EnvelopeFollower::new(1000.0, 0.0f64, 0.0);
+    assert!((follower.process(0.5) - 0.5).abs() < 1e-12);
+    assert!((follower.process(-0.25) - 0.25).abs() < 1e-12);
+  }
+}
@@ -0,0 +1,131 @@
+use crate::float::Float;
+use crate::funcs::denormals::flush_denormal;
+
+/// Stereo linked brickwall limiter without look-ahead.
+/// The gain drops instantly to keep the peaks at the threshold and recovers smoothly in the release time.
+#[derive(Debug, Clone)]
+pub struct Limiter<F: Float> {
+  sample_rate: F,
+  threshold: F,
+  release_sec: F,
+  release_coefficient: F,
+  gain: F,
+}
+
+impl<F: Float> Limiter<F> {
+  const MIN_THRESHOLD: f64 = 1e-3;
+
+  pub fn new(sample_rate: F, threshold: F, release_sec: F) -> Self {
+    let mut limiter = Limiter {
+      sample_rate,
+      threshold: F::one(),
+      release_sec,
+      release_coefficient: F::zero(),
+      gain: F::one(),
+    };
+    limiter.set_threshold(threshold);
+    limiter.set_release_time_sec(release_sec);
+    limiter
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.sample_rate = sample_rate;
+    self.set_release_time_sec(self.release_sec);
+  }
+
+  /// Maximum absolute value of the output
+  pub fn set_threshold(&mut self, threshold: F) {
+    self.threshold = threshold.max(F::val(Self::MIN_THRESHOLD));
+  }
+
+  pub fn get_threshold(&self) -> F {
+    self.threshold
+  }
+
+  /// Time constant for the gain to go back to unity
+  pub fn set_release_time_sec(&mut self, release_sec: F) {
+    self.release_sec = release_sec.max(F::zero());
+    let samples = self.release_sec * self.sample_rate;
+    self.release_coefficient = if samples > F::zero() {
+      (F::one().neg() / samples).exp()
+    } else {
+      F::zero()
+    };
+  }
+
+  pub fn get_release_time_sec(&self) -> F {
+    self.release_sec
+  }
+
+  /// Gain applied to the last processed sample
+  pub fn get_gain(&self) -> F {
+    self.gain
+  }
+
+  pub fn reset(&mut self) {
+    self.gain = F::one();
+  }
+
+  pub fn process(&mut self, left: F, right: F) -> (F, F) {
+    let peak = left.abs().max(right.abs());
+    let target = if peak > self.threshold {
+      self.threshold / peak
+    } else {
+      F::one()
+    };
+
+    self.gain = if target < self.gain {
+      target
+    } else {
+      flush_denormal(target + (self.gain - target) * self.release_coefficient)
+    };
+
+    (left * self.gain, right * self.gain)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::effects::limiter::Limiter;
+  use crate::float::Float;
+
+  #[test]
+  fn keeps_the_output_under_the_threshold() {
+    let sample_rate = 44100.0;
+    let mut limiter = Limiter::new(sample_rate, 1.0f64, 0.05);
+    // +6dB sine at 440Hz
+    let sine = |index: usize, amplitude: f64| {
+      amplitude * (2.0 * f64::PI * 440.0 * index as f64 / sample_rate).sin()
+    };
+
+    for index in 0..44100 {
+      let input = sine(index, 2.0);
+      let (left, right) = limiter.process(input, -input);
+      assert!(left.abs() <= 1.0 + 1e-9 && right.abs() <= 1.0 + 1e-9);
+    }
+
+    let mut peak = 0.0f64;
+    for index in 0..4410 {
+      peak = peak.max(limiter.process(sine(index, 2.0), 0.0).0.abs());
+    }
+    assert!((peak - 1.0).abs() < 1e-3);
+  }
+
+  #[test]
+  fn releases_back_to_unity() {
+    let mut limiter = Limiter::new(1000.0, 0.5f64, 0.01);
+    limiter.process(1.0, 0.0);
+    assert!((limiter.get_gain() - 0.5).abs() < 1e-9);
+
+    let mut previous = limiter.get_gain();
+    for _ in 0..100 {
+      limiter.process(0.1, 0.1);
+      assert!(limiter.get_gain() >= previous);
+      previous = limiter.get_gain();
+    }
+    assert!((limiter.get_gain() - 1.0).abs() < 1e-3);
+
+    let (left, right) = limiter.process(0.25, -0.25);
+    assert!((left - 0.25).abs() < 1e-3 && (right + 0.25).abs() < 1e-3);
+  }
+}
@@ -1 +1,4 @@
 pub mod delay;
+pub mod envelope_follower;
+pub mod limiter;
+pub mod shaper;
@@ -0,0 +1,170 @@
+use crate::float::Float;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shape {
+  /// Smooth saturation using tanh
+  SoftClip,
+  /// Hard limit at ±1
+  HardClip,
+  /// Exponential curve clipping the negative half earlier, it introduces DC
+  Diode,
+  /// Values over ±1 are reflected back into the range
+  Foldback,
+}
+
+/// Waveshaper with drive compensation so a full scale input keeps a full scale output
+#[derive(Debug, Clone)]
+pub struct Shaper<F: Float> {
+  shape: Shape,
+  drive: F,
+  compensation: F,
+  oversampling: bool,
+  last_input: F,
+}
+
+impl<F: Float> Shaper<F> {
+  const MIN_DRIVE: f64 = 0.01;
+
+  pub fn new(shape: Shape) -> Self {
+    let mut shaper = Shaper {
+      shape,
+      drive: F::one(),
+      compensation: F::one(),
+      oversampling: false,
+      last_input: F::zero(),
+    };
+    shaper.update_compensation();
+    shaper
+  }
+
+  pub fn set_shape(&mut self, shape: Shape) {
+    self.shape = shape;
+    self.update_compensation();
+  }
+
+  pub fn get_shape(&self) -> Shape {
+    self.shape
+  }
+
+  /// Gain applied to the input before shaping
+  pub fn set_drive(&mut self, drive: F) {
+    self.drive = drive.max(F::val(Self::MIN_DRIVE));
+    self.update_compensation();
+  }
+
+  pub fn get_drive(&self) -> F {
+    self.drive
+  }
+
+  /// Shape twice per sample and average them to reduce aliasing
+  pub fn set_oversampling(&mut self, oversampling: bool) {
+    self.oversampling = oversampling;
+  }
+
+  pub fn reset(&mut self) {
+    self.last_input = F::zero();
+  }
+
+  pub fn process(&mut self, input: F) -> F {
+    let output = if self.oversampling {
+      let middle = (self.last_input + input) * F::val(0.5);
+      (self.shape_sample(middle) + self.shape_sample(input)) * F::val(0.5)
+    } else {
+      self.shape_sample(input)
+    };
+    self.last_input = input;
+    output
+  }
+
+  fn shape_sample(&self, input: F) -> F {
+    Self::transfer(self.shape, input * self.drive) * self.compensation
+  }
+
+  fn update_compensation(&mut self) {
+    self.compensation = match self.shape {
+      Shape::Foldback => F::one(),
+      shape => Self::transfer(shape, self.drive).recip(),
+    };
+  }
+
+  fn transfer(shape: Shape, x: F) -> F {
+    match shape {
+      Shape::SoftClip => x.tanh(),
+      Shape::HardClip => num_traits::clamp(x, F::one().neg(), F::one()),
+      Shape::Diode => {
+        if x >= F::zero() {
+          F::one() - x.neg().exp()
+        } else {
+          (F::one() - x.exp()) * F::val(-0.5)
+        }
+      }
+      Shape::Foldback => {
+        let position = (x + F::one()) * F::val(0.25);
+        let fraction = position - position.floor();
+        if fraction < F::val(0.5) {
+          F::val(4.0) * fraction - F::one()
+        } else {
+          F::val(3.0) - F::val(4.0) * fraction
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use assert_approx_eq::assert_approx_eq;
+
+  use crate::effects::shaper::{Shape, Shaper};
+
+  fn sweep() -> impl Iterator<Item = f64> {
+    (-400..=400).map(|index| f64::from(index) / 100.0)
+  }
+
+  #[test]
+  fn soft_clip_is_monotonic_and_bounded() {
+    for drive in &[0.5, 1.0, 10.0] {
+      let mut shaper = Shaper::new(Shape::SoftClip);
+      shaper.set_drive(*drive);
+      let mut previous = -1.0;
+      for input in sweep() {
+        let output = shaper.process(num_traits::clamp(input, -1.0, 1.0));
+        assert!(output >= previous && output.abs() <= 1.0 + 1e-9);
+        previous = output;
+      }
+    }
+  }
+
+  #[test]
+  fn hard_clip_limits() {
+    let mut shaper = Shaper::<f64>::new(Shape::HardClip);
+    shaper.set_drive(1.0);
+    for input in sweep() {
+      let output = shaper.process(input);
+      assert!(output.abs() <= 1.0);
+    }
+    assert_approx_eq!(shaper.process(3.0), 1.0);
+    assert_approx_eq!(shaper.process(-3.0), -1.0);
+    assert_approx_eq!(shaper.process(0.5), 0.5);
+  }
+
+  #[test]
+  fn drive_keeps_full_scale() {
+    for shape in &[Shape::SoftClip, Shape::HardClip, Shape::Diode] {
+      for drive in &[0.5, 2.0, 20.0] {
+        let mut shaper = Shaper::<f64>::new(*shape);
+        shaper.set_drive(*drive);
+        assert_approx_eq!(shaper.process(1.0), 1.0);
+      }
+    }
+  }
+
+  #[test]
+  fn foldback_reflects() {
+    let mut shaper = Shaper::<f64>::new(Shape::Foldback);
+    assert_approx_eq!(shaper.process(0.5), 0.5);
+    assert_approx_eq!(shaper.process(1.5), 0.5);
+    assert_approx_eq!(shaper.process(-1.5), -0.5);
+  }
+}
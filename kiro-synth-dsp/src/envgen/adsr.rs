@@ -4,6 +4,7 @@ use crate::float::Float;
 pub enum Mode {
   Analog,
   Digital,
+  Linear,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -17,9 +18,9 @@ enum State {
 }
 
 #[derive(Debug, Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
 struct ADR<F: Float> {
   time_sec: F,
-  time_constant_overshoot: F,
   coefficient: F,
   offset: F,
 }
@@ -32,6 +33,7 @@ impl<F: Float> ADR<F> {
     let time_constant_overshoot = match mode {
       Mode::Analog => F::val(-1.5).exp(),
       Mode::Digital => F::val(0.99999).exp(),
+      Mode::Linear => return Self::linear(sample_rate, time_sec, F::one()),
     };
 
     let samples = Self::samples(sample_rate, time_sec);
@@ -41,7 +43,6 @@ impl<F: Float> ADR<F> {
 
     ADR {
       time_sec,
-      time_constant_overshoot,
       coefficient,
       offset,
     }
@@ -51,6 +52,7 @@ impl<F: Float> ADR<F> {
     let time_constant_overshoot = match mode {
       Mode::Analog => F::val(Self::ANALOG_DECAY_EXPONENT).exp(),
       Mode::Digital => F::val(Self::DIGITAL_DECAY_EXPONENT).exp(),
+      Mode::Linear => return Self::linear(sample_rate, time_sec, sustain_level - F::one()),
     };
 
     let samples = Self::samples(sample_rate, time_sec);
@@ -60,7 +62,6 @@ impl<F: Float> ADR<F> {
 
     ADR {
       time_sec,
-      time_constant_overshoot,
       coefficient,
       offset,
     }
@@ -70,6 +71,7 @@ impl<F: Float> ADR<F> {
     let time_constant_overshoot = match mode {
       Mode::Analog => F::val(Self::ANALOG_DECAY_EXPONENT).exp(),
       Mode::Digital => F::val(Self::DIGITAL_DECAY_EXPONENT).exp(),
+      Mode::Linear => return Self::linear(sample_rate, time_sec, F::one().neg()),
     };
 
     let samples = Self::samples(sample_rate, time_sec);
@@ -79,12 +81,21 @@ impl<F: Float> ADR<F> {
 
     ADR {
       time_sec,
-      time_constant_overshoot,
       coefficient,
       offset,
     }
   }
 
+  /// Constant slope that covers the distance in the given time
+  fn linear(sample_rate: F, time_sec: F, distance: F) -> ADR<F> {
+    let samples = Self::samples(sample_rate, time_sec).max(F::one());
+    ADR {
+      time_sec,
+      coefficient: F::one(),
+      offset: distance / samples,
+    }
+  }
+
   fn samples(sample_rate: F, time_sec: F) -> F {
     sample_rate * time_sec
   }
@@ -128,6 +139,11 @@ impl<F: Float> EnvGen<F> {
     }
   }
 
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.sample_rate = sample_rate;
+    self.set_mode(self.mode);
+  }
+
   pub fn set_mode(&mut self, mode: Mode) {
     self.mode = mode;
     self.attack = ADR::attack(self.sample_rate, mode, self.attack.time_sec);
@@ -190,17 +206,11 @@ impl<F: Float> EnvGen<F> {
   //  }
 
   pub fn is_active(&self) -> bool {
-    match self.state {
-      State::Off | State::Release => false,
-      _ => true,
-    }
+    !matches!(self.state, State::Off | State::Release)
   }
 
   pub fn is_off(&self) -> bool {
-    match self.state {
-      State::Off => true,
-      _ => false,
-    }
+    matches!(self.state, State::Off)
   }
 
   pub fn note_off(&mut self) {
@@ -283,3 +293,91 @@ impl<F: Float> EnvGen<F> {
     F::val(0.01)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use assert_approx_eq::assert_approx_eq;
+
+  use crate::envgen::adsr::{EnvGen, Mode};
+
+  fn envgen(mode: Mode) -> EnvGen<f64> {
+    let mut envgen = EnvGen::new(1000.0);
+    envgen.set_mode(mode);
+    envgen.set_attack_time_sec(0.01);
+    envgen.set_decay_time_sec(0.01);
+    envgen.set_sustain_level(0.5);
+    envgen.set_release_time_sec(0.01);
+    envgen
+  }
+
+  /// Generates values until the output reaches the level, returning them
+  fn generate_until(envgen: &mut EnvGen<f64>, level: f64) -> Vec<f64> {
+    let mut values = vec![envgen.generate()];
+    while (values.last().unwrap() - level).abs() > 1e-9 {
+      values.push(envgen.generate());
+    }
+    values
+  }
+
+  fn assert_slope(values: &[f64], slope: f64) {
+    assert!(values.len() >= 10 && values.len() <= 11, "{:?}", values);
+    for pair in values[..9].windows(2) {
+      assert_approx_eq!(pair[1] - pair[0], slope);
+    }
+  }
+
+  #[test]
+  fn linear_shape() {
+    let mut envgen = envgen(Mode::Linear);
+    envgen.start();
+
+    let attack = generate_until(&mut envgen, 1.0);
+    assert_approx_eq!(attack[0], 0.1);
+    assert_slope(&attack, 0.1);
+
+    let decay = generate_until(&mut envgen, 0.5);
+    assert_slope(&decay, -0.05);
+
+    for _ in 0..100 {
+      assert_approx_eq!(envgen.generate(), 0.5);
+    }
+
+    envgen.note_off();
+    let mut release = Vec::new();
+    while !envgen.is_off() {
+      release.push(envgen.generate());
+    }
+    assert!(release.len() >= 5 && release.len() <= 6, "{:?}", release);
+    assert_approx_eq!(release[0], 0.4);
+    assert_approx_eq!(*release.last().unwrap(), 0.0);
+  }
+
+  #[test]
+  fn exponential_shape() {
+    for mode in &[Mode::Analog, Mode::Digital] {
+      let mut envgen = envgen(*mode);
+      envgen.start();
+
+      let mut previous = 0.0;
+      while envgen.is_active() && previous < 1.0 {
+        let value = envgen.generate();
+        assert!(value > previous);
+        previous = value;
+      }
+      assert_approx_eq!(previous, 1.0);
+
+      for _ in 0..100 {
+        envgen.generate();
+      }
+      assert_approx_eq!(envgen.generate(), 0.5);
+
+      envgen.note_off();
+      let mut samples = 0;
+      while !envgen.is_off() {
+        envgen.generate();
+        samples += 1;
+      }
+      assert!(samples > 1 && samples <= 10, "{:?} {}", mode, samples);
+    }
+  }
+}
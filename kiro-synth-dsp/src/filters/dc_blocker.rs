@@ -0,0 +1,67 @@
+use crate::float::Float;
+use crate::funcs::denormals::flush_denormal;
+
+/// One pole high-pass removing the DC offset: y[n] = x[n] - x[n-1] + R * y[n-1]
+#[derive(Debug, Clone)]
+pub struct DcBlocker<F: Float> {
+  pole: F,
+  x1: F,
+  y1: F,
+}
+
+impl<F: Float> DcBlocker<F> {
+  /// Cutoff frequency in Hz, low enough to leave the audible range untouched
+  const CUTOFF: f64 = 10.0;
+
+  pub fn new(sample_rate: F) -> Self {
+    DcBlocker {
+      pole: Self::pole(sample_rate),
+      x1: F::zero(),
+      y1: F::zero(),
+    }
+  }
+
+  fn pole(sample_rate: F) -> F {
+    F::one() - F::val(2.0) * F::PI * F::val(Self::CUTOFF) / sample_rate
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.pole = Self::pole(sample_rate);
+  }
+
+  pub fn reset(&mut self) {
+    self.x1 = F::zero();
+    self.y1 = F::zero();
+  }
+
+  pub fn process(&mut self, input: F) -> F {
+    let output = flush_denormal(input - self.x1 + self.pole * self.y1);
+    self.x1 = input;
+    self.y1 = output;
+    output
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::filters::dc_blocker::DcBlocker;
+
+  #[test]
+  fn removes_dc_and_keeps_ac() {
+    let mut blocker = DcBlocker::new(44100.0f64);
+    // 441Hz square wave between -0.5 and 1.5
+    let square = |index: usize| if index % 100 < 50 { 1.5 } else { -0.5 };
+    for index in 0..44100 {
+      blocker.process(square(index));
+    }
+
+    let output: Vec<f64> = (44100..44100 + 4410)
+      .map(|index| blocker.process(square(index)))
+      .collect();
+    let mean = output.iter().sum::<f64>() / output.len() as f64;
+    let max = output.iter().cloned().fold(f64::MIN, f64::max);
+    let min = output.iter().cloned().fold(f64::MAX, f64::min);
+    assert!(mean.abs() < 0.01, "{}", mean);
+    assert!((max - min - 2.0).abs() < 0.1, "{} {}", max, min);
+  }
+}
@@ -31,6 +31,10 @@ impl<F: Float> FreqControl<F> {
     };
   }
 
+  pub fn invalidate(&mut self) {
+    self.invalidated = true;
+  }
+
   pub fn is_invalidated(&self) -> bool {
     self.invalidated
   }
@@ -1,5 +1,7 @@
+pub mod dc_blocker;
 pub mod freq_control;
 pub mod oberheim_sem;
 pub mod q_control;
 pub mod saturation;
+pub mod svf;
 pub mod va_one_pole;
@@ -3,7 +3,8 @@ use crate::filters::q_control::QControl;
 use crate::filters::saturation::Saturation;
 use crate::float::Float;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
   LowPass,
   HighPass,
@@ -45,6 +46,12 @@ impl<F: Float> OberheimSEM<F> {
     }
   }
 
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.sample_rate = sample_rate;
+    self.inv_sample_rate = F::one() / sample_rate;
+    self.freq.invalidate();
+  }
+
   pub fn set_mode(&mut self, mode: Mode) {
     self.mode = mode;
   }
@@ -0,0 +1,146 @@
+use crate::float::Float;
+
+/// Simultaneous outputs of the state variable filter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SvfOutput<F: Float> {
+  pub lowpass: F,
+  pub bandpass: F,
+  pub highpass: F,
+}
+
+/// Zero delay feedback state variable filter (Andrew Simper's TPT topology).
+/// It stays stable with the cutoff modulated at audio rate up to Nyquist.
+#[derive(Debug, Clone)]
+pub struct StateVariableFilter<F: Float> {
+  inv_sample_rate: F,
+  cutoff: F,
+  resonance: F,
+  k: F,
+  a1: F,
+  a2: F,
+  a3: F,
+  ic1eq: F,
+  ic2eq: F,
+  invalidated: bool,
+}
+
+impl<F: Float> StateVariableFilter<F> {
+  /// Upper limit for the cutoff relative to the sample rate, it keeps the prewarping finite
+  const MAX_CUTOFF_RATIO: f64 = 0.49;
+
+  pub fn new(sample_rate: F, cutoff: F) -> Self {
+    StateVariableFilter {
+      inv_sample_rate: sample_rate.recip(),
+      cutoff,
+      resonance: F::zero(),
+      k: F::val(2.0),
+      a1: F::zero(),
+      a2: F::zero(),
+      a3: F::zero(),
+      ic1eq: F::zero(),
+      ic2eq: F::zero(),
+      invalidated: true,
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.inv_sample_rate = sample_rate.recip();
+    self.invalidated = true;
+  }
+
+  /// Set the cutoff frequency in Hz
+  pub fn set_cutoff(&mut self, cutoff: F) {
+    self.cutoff = cutoff;
+    self.invalidated = true;
+  }
+
+  /// Set the resonance, from 0 (Q = 0.5) to 1 (self oscillation)
+  pub fn set_resonance(&mut self, resonance: F) {
+    self.resonance = resonance.max(F::zero()).min(F::one());
+    self.invalidated = true;
+  }
+
+  pub fn reset(&mut self) {
+    self.ic1eq = F::zero();
+    self.ic2eq = F::zero();
+  }
+
+  fn update(&mut self) {
+    if self.invalidated {
+      self.invalidated = false;
+      let ratio = (self.cutoff * self.inv_sample_rate)
+        .max(F::zero())
+        .min(F::val(Self::MAX_CUTOFF_RATIO));
+      let g = (F::PI * ratio).tan();
+      self.k = F::val(2.0) * (F::one() - self.resonance);
+      self.a1 = (F::one() + g * (g + self.k)).recip();
+      self.a2 = g * self.a1;
+      self.a3 = g * self.a2;
+    }
+  }
+
+  pub fn process(&mut self, input: F) -> SvfOutput<F> {
+    self.update();
+
+    let two = F::val(2.0);
+    let v3 = input - self.ic2eq;
+    let v1 = self.a1 * self.ic1eq + self.a2 * v3;
+    let v2 = self.ic2eq + self.a2 * self.ic1eq + self.a3 * v3;
+    self.ic1eq = two * v1 - self.ic1eq;
+    self.ic2eq = two * v2 - self.ic2eq;
+
+    SvfOutput {
+      lowpass: v2,
+      bandpass: v1,
+      highpass: input - self.k * v1 - v2,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::filters::svf::{StateVariableFilter, SvfOutput};
+
+  const SAMPLE_RATE: f64 = 44100.0;
+
+  /// Peak amplitude of the outputs for a sine input, after the filter settles
+  fn peaks(filter: &mut StateVariableFilter<f64>, freq: f64) -> SvfOutput<f64> {
+    let mut peaks = SvfOutput::<f64>::default();
+    let step = 2.0 * std::f64::consts::PI * freq / SAMPLE_RATE;
+    for index in 0..44100 {
+      let output = filter.process((index as f64 * step).sin());
+      if index > 22050 {
+        peaks.lowpass = peaks.lowpass.max(output.lowpass.abs());
+        peaks.bandpass = peaks.bandpass.max(output.bandpass.abs());
+        peaks.highpass = peaks.highpass.max(output.highpass.abs());
+      }
+    }
+    peaks
+  }
+
+  #[test]
+  fn lowpass_sweep() {
+    for cutoff in &[100.0, 500.0, 1000.0, 2000.0] {
+      let mut filter = StateVariableFilter::new(SAMPLE_RATE, *cutoff);
+      let low = peaks(&mut filter, cutoff / 10.0);
+      filter.reset();
+      let high = peaks(&mut filter, 15000.0);
+
+      assert!(low.lowpass > 0.95, "cutoff {}: {:?}", cutoff, low);
+      assert!(high.lowpass < 0.05, "cutoff {}: {:?}", cutoff, high);
+      assert!(high.highpass > 0.95, "cutoff {}: {:?}", cutoff, high);
+    }
+  }
+
+  #[test]
+  fn stable_up_to_nyquist() {
+    let mut filter = StateVariableFilter::new(SAMPLE_RATE, 100.0);
+    filter.set_resonance(1.0);
+    for index in 0..44100 {
+      filter.set_cutoff(100.0 + (index % 100) as f64 * 300.0);
+      let output = filter.process(if index % 2 == 0 { 1.0 } else { -1.0 });
+      assert!(output.lowpass.is_finite() && output.lowpass.abs() < 1e3);
+      assert!(output.highpass.is_finite() && output.bandpass.is_finite());
+    }
+  }
+}
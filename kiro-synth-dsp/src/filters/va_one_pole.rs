@@ -1,7 +1,8 @@
 use crate::filters::freq_control::FreqControl;
 use crate::float::Float;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
   LowPass,
   HighPass,
@@ -41,6 +42,12 @@ impl<F: Float> VAOnePoleFilter<F> {
     }
   }
 
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.sample_rate = sample_rate;
+    self.inv_sample_rate = F::one() / sample_rate;
+    self.freq.invalidate();
+  }
+
   pub fn set_mode(&mut self, mode: Mode) {
     self.mode = mode;
   }
@@ -0,0 +1,679 @@
+mod tables;
+
+use core::num::FpCategory;
+use core::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+use num_traits::{Float as _, Num, One, ToPrimitive, Zero};
+
+use crate::float::Float;
+use crate::funcs::parabolic_sine::ParabolicSine;
+
+use tables::{EXP2_FRACTION, LOG2_MANTISSA, SINE_QUARTER};
+
+const FRACTION_BITS: u32 = 32;
+const ONE: i64 = 1 << FRACTION_BITS;
+const HALF: i64 = ONE >> 1;
+const FRACTION_MASK: i64 = ONE - 1;
+
+const FRAC_1_2PI: Fixed = Fixed(683_565_276);
+const LOG2_E: Fixed = Fixed(6_196_328_019);
+const LN_2: Fixed = Fixed(2_977_044_472);
+const LOG10_2: Fixed = Fixed(1_292_913_986);
+
+/// Signed fixed point number with 32 bits for the integer part and 32 for the fraction (Q32.32),
+/// to run the DSP with integer arithmetic in processors without a floating point unit.
+///
+/// Q16.16 can not hold the sample rate (over 32767) that the processors keep in the float type,
+/// and 1 / 44100 would be a single unit (a 33% error in the phase increments), so it uses 64 bits.
+/// The price measured in a thumbv6m (Cortex-M0) release build is that a multiplication takes
+/// ~110 instructions and four `__aeabi_lmul` calls (a Q16.16 one would be one call),
+/// which is why it avoids `i128` (six calls), and a division ~450, so the audio path should multiply.
+/// There is neither NaN nor infinity, the operations saturate to the range instead.
+/// The trigonometric, exponential and logarithmic functions use tables with linear interpolation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fixed(i64);
+
+/// Error parsing a [`Fixed`] from a string
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseFixedError;
+
+impl Fixed {
+  pub const fn from_bits(bits: i64) -> Self {
+    Fixed(bits)
+  }
+
+  pub const fn to_bits(self) -> i64 {
+    self.0
+  }
+
+  /// Convert from a float saturating to the range of the fixed point
+  pub fn from_f64(value: f64) -> Self {
+    if value.is_nan() {
+      Fixed(0)
+    } else {
+      Self::saturate((value * ONE as f64) as i128)
+    }
+  }
+
+  fn checked_from_f64(value: f64) -> Option<Self> {
+    let bits = value * ONE as f64;
+    if bits.is_finite() && bits >= i64::MIN as f64 && bits <= i64::MAX as f64 {
+      Some(Fixed(libm::round(bits) as i64))
+    } else {
+      None
+    }
+  }
+
+  fn saturate(bits: i128) -> Self {
+    if bits > i64::MAX as i128 {
+      Fixed(i64::MAX)
+    } else if bits < i64::MIN as i128 {
+      Fixed(i64::MIN)
+    } else {
+      Fixed(bits as i64)
+    }
+  }
+
+  /// Linearly interpolated value from a table of 257 points, where position is in [0, 2^32]
+  fn lookup(table: &[i64; 257], position: u64) -> i64 {
+    let index = (position >> 24) as usize;
+    if index >= 256 {
+      return table[256];
+    }
+    let fraction = (position & 0x00ff_ffff) as i64;
+    let current = table[index];
+    current + (((table[index + 1] - current) * fraction) >> 24)
+  }
+
+  /// Sine of the phase as a fraction of the cycle in 32 bits
+  fn sine_of_phase(phase: u32) -> Self {
+    let position = u64::from(phase << 2);
+    let quarter = |position| Self::lookup(&SINE_QUARTER, position);
+    match phase >> 30 {
+      0 => Fixed(quarter(position)),
+      1 => Fixed(quarter(ONE as u64 - position)),
+      2 => Fixed(-quarter(position)),
+      _ => Fixed(-quarter(ONE as u64 - position)),
+    }
+  }
+
+  /// Phase of the angle as a fraction of the cycle in 32 bits
+  fn phase(self) -> u32 {
+    (self * FRAC_1_2PI).0 as u32
+  }
+
+  fn isqrt(value: u128) -> u128 {
+    let mut remainder = value;
+    let mut result = 0u128;
+    let mut bit = 1u128 << 126;
+    while bit > value {
+      bit >>= 2;
+    }
+    while bit != 0 {
+      if remainder >= result + bit {
+        remainder -= result + bit;
+        result = (result >> 1) + bit;
+      } else {
+        result >>= 1;
+      }
+      bit >>= 2;
+    }
+    result
+  }
+
+  /// Not needed in the audio path, so computed with floating point
+  fn via_f64<A: Fn(f64) -> f64>(self, function: A) -> Self {
+    Self::from_f64(function(self.0 as f64 / ONE as f64))
+  }
+}
+
+impl Add for Fixed {
+  type Output = Fixed;
+
+  fn add(self, rhs: Self) -> Self::Output {
+    Fixed(self.0.saturating_add(rhs.0))
+  }
+}
+
+impl Sub for Fixed {
+  type Output = Fixed;
+
+  fn sub(self, rhs: Self) -> Self::Output {
+    Fixed(self.0.saturating_sub(rhs.0))
+  }
+}
+
+impl Mul for Fixed {
+  type Output = Fixed;
+
+  /// The product of the magnitudes in 32 bits halves, as a Cortex-M0 has no 64 bits multiplication
+  fn mul(self, rhs: Self) -> Self::Output {
+    let negative = (self.0 < 0) != (rhs.0 < 0);
+    let (lhs, rhs) = (self.0.unsigned_abs(), rhs.0.unsigned_abs());
+    let (lhs_high, lhs_low) = (lhs >> 32, lhs & 0xffff_ffff);
+    let (rhs_high, rhs_low) = (rhs >> 32, rhs & 0xffff_ffff);
+    let high = lhs_high * rhs_high;
+    let magnitude = if high >> 31 == 0 {
+      (lhs_high * rhs_low)
+        .checked_add(lhs_low * rhs_high)
+        .and_then(|middle| middle.checked_add((lhs_low * rhs_low) >> 32))
+        .and_then(|middle| middle.checked_add(high << 32))
+    } else {
+      None
+    };
+    match magnitude {
+      Some(magnitude) if negative && magnitude <= 1 << 63 => {
+        Fixed((magnitude as i64).wrapping_neg())
+      }
+      Some(magnitude) if !negative && magnitude < 1 << 63 => Fixed(magnitude as i64),
+      _ if negative => Fixed(i64::MIN),
+      _ => Fixed(i64::MAX),
+    }
+  }
+}
+
+impl Div for Fixed {
+  type Output = Fixed;
+
+  fn div(self, rhs: Self) -> Self::Output {
+    match rhs.0 {
+      0 if self.0 < 0 => Fixed(i64::MIN),
+      0 => Fixed(i64::MAX),
+      _ => Self::saturate((i128::from(self.0) << FRACTION_BITS) / i128::from(rhs.0)),
+    }
+  }
+}
+
+impl Rem for Fixed {
+  type Output = Fixed;
+
+  /// Zero when dividing by zero, and for the minimum by minus one that can not overflow
+  fn rem(self, rhs: Self) -> Self::Output {
+    Fixed(self.0.checked_rem(rhs.0).unwrap_or(0))
+  }
+}
+
+impl Neg for Fixed {
+  type Output = Fixed;
+
+  fn neg(self) -> Self::Output {
+    Fixed(0i64.saturating_sub(self.0))
+  }
+}
+
+impl Zero for Fixed {
+  fn zero() -> Self {
+    Fixed(0)
+  }
+
+  fn is_zero(&self) -> bool {
+    self.0 == 0
+  }
+}
+
+impl One for Fixed {
+  fn one() -> Self {
+    Fixed(ONE)
+  }
+}
+
+impl Num for Fixed {
+  type FromStrRadixErr = ParseFixedError;
+
+  fn from_str_radix(text: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+    if radix == 10 {
+      text
+        .parse::<f64>()
+        .ok()
+        .and_then(Self::checked_from_f64)
+        .ok_or(ParseFixedError)
+    } else {
+      i64::from_str_radix(text, radix)
+        .ok()
+        .and_then(<Self as num_traits::NumCast>::from)
+        .ok_or(ParseFixedError)
+    }
+  }
+}
+
+impl ToPrimitive for Fixed {
+  fn to_i64(&self) -> Option<i64> {
+    Some(self.trunc().0 >> FRACTION_BITS)
+  }
+
+  fn to_u64(&self) -> Option<u64> {
+    self
+      .to_i64()
+      .and_then(|value| if value >= 0 { Some(value as u64) } else { None })
+  }
+
+  fn to_f32(&self) -> Option<f32> {
+    self.to_f64().map(|value| value as f32)
+  }
+
+  fn to_f64(&self) -> Option<f64> {
+    Some(self.0 as f64 / ONE as f64)
+  }
+}
+
+impl num_traits::NumCast for Fixed {
+  fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+    n.to_f64().and_then(Self::checked_from_f64)
+  }
+}
+
+impl num_traits::Float for Fixed {
+  /// Saturates to the maximum
+  fn infinity() -> Self {
+    Self::max_value()
+  }
+
+  /// Saturates to the minimum
+  fn neg_infinity() -> Self {
+    Self::min_value()
+  }
+
+  /// There is no NaN, the invalid operations give zero
+  fn nan() -> Self {
+    Fixed(0)
+  }
+
+  fn neg_zero() -> Self {
+    Fixed(0)
+  }
+
+  fn min_value() -> Self {
+    Fixed(i64::MIN)
+  }
+
+  fn min_positive_value() -> Self {
+    Fixed(1)
+  }
+
+  fn epsilon() -> Self {
+    Fixed(1)
+  }
+
+  fn max_value() -> Self {
+    Fixed(i64::MAX)
+  }
+
+  fn is_nan(self) -> bool {
+    false
+  }
+
+  fn is_infinite(self) -> bool {
+    false
+  }
+
+  fn is_finite(self) -> bool {
+    true
+  }
+
+  fn is_normal(self) -> bool {
+    self.0 != 0
+  }
+
+  fn classify(self) -> FpCategory {
+    if self.0 == 0 {
+      FpCategory::Zero
+    } else {
+      FpCategory::Normal
+    }
+  }
+
+  fn floor(self) -> Self {
+    Fixed(self.0 & !FRACTION_MASK)
+  }
+
+  fn ceil(self) -> Self {
+    Fixed(self.0.saturating_add(FRACTION_MASK) & !FRACTION_MASK)
+  }
+
+  fn round(self) -> Self {
+    if self.0 >= 0 {
+      Fixed(self.0.saturating_add(HALF) & !FRACTION_MASK)
+    } else {
+      -(-self).round()
+    }
+  }
+
+  fn trunc(self) -> Self {
+    if self.0 >= 0 {
+      self.floor()
+    } else {
+      -(-self).floor()
+    }
+  }
+
+  fn fract(self) -> Self {
+    self - self.trunc()
+  }
+
+  fn abs(self) -> Self {
+    if self.0 < 0 {
+      -self
+    } else {
+      self
+    }
+  }
+
+  fn signum(self) -> Self {
+    if self.0 < 0 {
+      -Self::one()
+    } else {
+      Self::one()
+    }
+  }
+
+  fn is_sign_positive(self) -> bool {
+    self.0 >= 0
+  }
+
+  fn is_sign_negative(self) -> bool {
+    self.0 < 0
+  }
+
+  fn mul_add(self, a: Self, b: Self) -> Self {
+    self * a + b
+  }
+
+  fn recip(self) -> Self {
+    Self::one() / self
+  }
+
+  fn powi(self, n: i32) -> Self {
+    let mut base = if n < 0 { self.recip() } else { self };
+    let mut exponent = i64::from(n).abs();
+    let mut result = Self::one();
+    while exponent > 0 {
+      if exponent & 1 == 1 {
+        result = result * base;
+      }
+      base = base * base;
+      exponent >>= 1;
+    }
+    result
+  }
+
+  /// Zero for a base that is not positive
+  fn powf(self, n: Self) -> Self {
+    if self.0 <= 0 {
+      Fixed(0)
+    } else {
+      (n * self.log2()).exp2()
+    }
+  }
+
+  /// Zero for negative values
+  fn sqrt(self) -> Self {
+    if self.0 <= 0 {
+      Fixed(0)
+    } else {
+      Fixed(Self::isqrt((self.0 as u128) << FRACTION_BITS) as i64)
+    }
+  }
+
+  fn exp(self) -> Self {
+    (self * LOG2_E).exp2()
+  }
+
+  fn exp2(self) -> Self {
+    let integer = self.0 >> FRACTION_BITS;
+    let mantissa = Self::lookup(&EXP2_FRACTION, (self.0 & FRACTION_MASK) as u64);
+    if integer >= 31 {
+      Self::max_value()
+    } else if integer >= 0 {
+      Fixed(mantissa << integer)
+    } else if integer > -64 {
+      Fixed(mantissa >> -integer)
+    } else {
+      Fixed(0)
+    }
+  }
+
+  fn ln(self) -> Self {
+    self.log2() * LN_2
+  }
+
+  fn log(self, base: Self) -> Self {
+    self.log2() / base.log2()
+  }
+
+  /// The minimum for values that are not positive
+  fn log2(self) -> Self {
+    if self.0 <= 0 {
+      return Self::min_value();
+    }
+    let exponent = 63 - i64::from(self.0.leading_zeros()) - i64::from(FRACTION_BITS);
+    let normalized = if exponent >= 0 {
+      self.0 >> exponent
+    } else {
+      self.0 << -exponent
+    };
+    let mantissa = Self::lookup(&LOG2_MANTISSA, (normalized - ONE) as u64);
+    Fixed((exponent << FRACTION_BITS) + mantissa)
+  }
+
+  fn log10(self) -> Self {
+    self.log2() * LOG10_2
+  }
+
+  fn max(self, other: Self) -> Self {
+    if self.0 >= other.0 {
+      self
+    } else {
+      other
+    }
+  }
+
+  fn min(self, other: Self) -> Self {
+    if self.0 <= other.0 {
+      self
+    } else {
+      other
+    }
+  }
+
+  fn abs_sub(self, other: Self) -> Self {
+    (self - other).max(Fixed(0))
+  }
+
+  fn cbrt(self) -> Self {
+    if self.0 == 0 {
+      self
+    } else {
+      (self.abs().log2() / Fixed(3 * ONE)).exp2() * self.signum()
+    }
+  }
+
+  fn hypot(self, other: Self) -> Self {
+    (self * self + other * other).sqrt()
+  }
+
+  fn sin(self) -> Self {
+    Self::sine_of_phase(self.phase())
+  }
+
+  fn cos(self) -> Self {
+    Self::sine_of_phase(self.phase().wrapping_add(1 << 30))
+  }
+
+  fn tan(self) -> Self {
+    let (sin, cos) = self.sin_cos();
+    sin / cos
+  }
+
+  fn asin(self) -> Self {
+    self.via_f64(libm::asin)
+  }
+
+  fn acos(self) -> Self {
+    self.via_f64(libm::acos)
+  }
+
+  fn atan(self) -> Self {
+    self.via_f64(libm::atan)
+  }
+
+  fn atan2(self, other: Self) -> Self {
+    let other = other.0 as f64 / ONE as f64;
+    self.via_f64(|value| libm::atan2(value, other))
+  }
+
+  fn sin_cos(self) -> (Self, Self) {
+    (self.sin(), self.cos())
+  }
+
+  fn exp_m1(self) -> Self {
+    self.exp() - Self::one()
+  }
+
+  fn ln_1p(self) -> Self {
+    (self + Self::one()).ln()
+  }
+
+  fn sinh(self) -> Self {
+    (self.exp() - (-self).exp()) / Fixed(2 * ONE)
+  }
+
+  fn cosh(self) -> Self {
+    (self.exp() + (-self).exp()) / Fixed(2 * ONE)
+  }
+
+  fn tanh(self) -> Self {
+    if self.abs().0 >= 20 * ONE {
+      self.signum()
+    } else {
+      let exp = (self + self).exp();
+      (exp - Self::one()) / (exp + Self::one())
+    }
+  }
+
+  fn asinh(self) -> Self {
+    (self + (self * self + Self::one()).sqrt()).ln()
+  }
+
+  fn acosh(self) -> Self {
+    (self + (self * self - Self::one()).sqrt()).ln()
+  }
+
+  fn atanh(self) -> Self {
+    ((Self::one() + self) / (Self::one() - self)).ln() / Fixed(2 * ONE)
+  }
+
+  fn integer_decode(self) -> (u64, i16, i8) {
+    let sign = if self.0 < 0 { -1 } else { 1 };
+    let mantissa = self.0.unsigned_abs();
+    (mantissa, -(FRACTION_BITS as i16), sign)
+  }
+}
+
+impl ParabolicSine for Fixed {
+  const B: Fixed = Fixed(5_468_522_205);
+  const C: Fixed = Fixed(-1_740_684_681);
+  const P: Fixed = Fixed(966_367_642);
+
+  fn parabolic_sine(&self) -> Self {
+    let y = Self::B * *self + Self::C * *self * self.abs();
+    Self::P * (y * y.abs() - y) + y
+  }
+}
+
+impl Float for Fixed {
+  const PI: Fixed = Fixed(13_493_037_705);
+}
+
+#[cfg(test)]
+mod tests {
+  use num_traits::{Float as _, ToPrimitive};
+
+  use crate::fixed::Fixed;
+  use crate::float::Float;
+  use crate::oscillators::osc_waveform::OscWaveform;
+  use crate::oscillators::pitched_oscillator::PitchedOscillator;
+  use crate::waveforms::saw_blep::SawBlep;
+  use crate::waveforms::sine_parabolic::SineParabolic;
+
+  fn fixed(value: f64) -> Fixed {
+    Fixed::from_f64(value)
+  }
+
+  fn float(value: Fixed) -> f64 {
+    value.to_f64().unwrap()
+  }
+
+  #[test]
+  fn arithmetic() {
+    assert!((float(fixed(1.5) * fixed(-2.25)) + 3.375).abs() < 1e-9);
+    assert!((float(fixed(1.0) / fixed(3.0)) - 1.0 / 3.0).abs() < 1e-9);
+    assert!((float(fixed(44100.0).recip()) - 1.0 / 44100.0).abs() < 1e-9);
+    assert!((float(fixed(2.0).sqrt()) - 2f64.sqrt()).abs() < 1e-9);
+    assert!((float(fixed(1.5).powi(-3)) - 1.5f64.powi(-3)).abs() < 1e-9);
+    assert!((float(fixed(-2.5).floor()) + 3.0).abs() < 1e-9);
+    assert!((float(fixed(-2.5).round()) + 3.0).abs() < 1e-9);
+    assert!((float(fixed(-2.5).trunc()) + 2.0).abs() < 1e-9);
+    assert!((float(fixed(-2.25).fract()) + 0.25).abs() < 1e-9);
+    assert_eq!(fixed(1.0) / fixed(0.0), Fixed::max_value());
+    assert_eq!(Fixed::max_value() + fixed(1.0), Fixed::max_value());
+    assert_eq!(Fixed::max_value() * fixed(2.0), Fixed::max_value());
+    assert_eq!(Fixed::max_value() * fixed(-2.0), Fixed::min_value());
+    assert_eq!(Fixed::min_value() * fixed(1.0), Fixed::min_value());
+    assert!((float(fixed(-3.5) * fixed(-0.5)) - 1.75).abs() < 1e-9);
+    assert!((float(fixed(-5.5) % fixed(2.0)) + 1.5).abs() < 1e-9);
+    assert_eq!(fixed(5.5) % fixed(0.0), fixed(0.0));
+    assert_eq!(Fixed::min_value() % Fixed::from_bits(-1), fixed(0.0));
+    assert_eq!(Fixed::val(0.75), fixed(0.75));
+    assert_eq!(Fixed::val(7.9).to_usize(), Some(7));
+  }
+
+  #[test]
+  fn transcendental_functions() {
+    for index in -100..100 {
+      let x = index as f64 * 0.07;
+      assert!((float(fixed(x).sin()) - x.sin()).abs() < 1e-5);
+      assert!((float(fixed(x).cos()) - x.cos()).abs() < 1e-5);
+      assert!((float(fixed(x).tanh()) - x.tanh()).abs() < 1e-5);
+      assert!((float(fixed(x).exp()) / x.exp() - 1.0).abs() < 1e-5);
+      assert!((float(fixed(2.0).powf(fixed(x / 12.0))) - 2f64.powf(x / 12.0)).abs() < 1e-5);
+      let positive = x.abs() * 100.0 + 0.01;
+      assert!((float(fixed(positive).ln()) - positive.ln()).abs() < 1e-5);
+      assert!((float(fixed(positive).log10()) - positive.log10()).abs() < 1e-5);
+    }
+    assert!((float(fixed(0.3).tan()) - 0.3f64.tan()).abs() < 1e-5);
+  }
+
+  #[test]
+  fn oscillators_follow_f32() {
+    fn compare(
+      fixed_waveform: OscWaveform<Fixed>,
+      float_waveform: OscWaveform<f32>,
+      tolerance: f32,
+    ) {
+      let mut fixed_osc =
+        PitchedOscillator::new(Fixed::val(44100), fixed_waveform, Fixed::val(440));
+      let mut float_osc = PitchedOscillator::new(44100.0f32, float_waveform, 440.0);
+      fixed_osc.set_semitones(Fixed::val(7));
+      float_osc.set_semitones(7.0);
+      for _ in 0..4410 {
+        let fixed_value = fixed_osc.generate().to_f32().unwrap();
+        let float_value = float_osc.generate();
+        assert!((fixed_value - float_value).abs() < tolerance);
+      }
+    }
+
+    // 1 / 44100 has a relative error of ~5e-6 in Q32.32, so the phases drift apart very slowly,
+    // and the BLEP correction amplifies that drift in the samples next to the discontinuity
+    compare(
+      OscWaveform::SineParabolic(SineParabolic),
+      OscWaveform::SineParabolic(SineParabolic),
+      5e-3,
+    );
+    compare(
+      OscWaveform::SawBlep(SawBlep::default()),
+      OscWaveform::SawBlep(SawBlep::default()),
+      5e-2,
+    );
+  }
+}
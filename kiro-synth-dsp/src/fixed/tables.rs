@@ -0,0 +1,110 @@
+#![allow(clippy::all)]
+
+/// sin(x) for x in [0, pi/2] in 256 steps, as Q32.32
+pub const SINE_QUARTER: [i64; 257] = [
+  0, 26353424, 52705856, 79056303, 105403774, 131747276, 158085819, 184418409, 210744057,
+  237061769, 263370557, 289669429, 315957395, 342233465, 368496651, 394745962, 420980412,
+  447199012, 473400776, 499584716, 525749847, 551895183, 578019742, 604122538, 630202589,
+  656258914, 682290530, 708296459, 734275721, 760227338, 786150333, 812043729, 837906553,
+  863737830, 889536587, 915301854, 941032661, 966728038, 992387019, 1018008636, 1043591926,
+  1069135926, 1094639673, 1120102207, 1145522571, 1170899806, 1196232957, 1221521071, 1246763195,
+  1271958380, 1297105676, 1322204136, 1347252816, 1372250773, 1397197066, 1422090755, 1446930903,
+  1471716574, 1496446837, 1521120759, 1545737412, 1570295869, 1594795204, 1619234497, 1643612827,
+  1667929275, 1692182927, 1716372869, 1740498191, 1764557983, 1788551342, 1812477362, 1836335144,
+  1860123788, 1883842400, 1907490086, 1931065957, 1954569124, 1977998702, 2001353810, 2024633568,
+  2047837100, 2070963532, 2094011993, 2116981616, 2139871536, 2162680890, 2185408821, 2208054473,
+  2230616993, 2253095531, 2275489241, 2297797281, 2320018810, 2342152991, 2364198992, 2386155981,
+  2408023134, 2429799626, 2451484637, 2473077351, 2494576955, 2515982640, 2537293599, 2558509031,
+  2579628136, 2600650120, 2621574191, 2642399561, 2663125446, 2683751066, 2704275644, 2724698408,
+  2745018589, 2765235421, 2785348143, 2805355999, 2825258235, 2845054101, 2864742853, 2884323748,
+  2903796051, 2923159027, 2942411948, 2961554089, 2980584729, 2999503152, 3018308645, 3037000500,
+  3055578014, 3074040487, 3092387225, 3110617535, 3128730733, 3146726136, 3164603066, 3182360851,
+  3199998822, 3217516315, 3234912670, 3252187232, 3269339351, 3286368382, 3303273682, 3320054617,
+  3336710553, 3353240863, 3369644927, 3385922125, 3402071844, 3418093478, 3433986423, 3449750080,
+  3465383855, 3480887161, 3496259414, 3511500034, 3526608449, 3541584088, 3556426389, 3571134792,
+  3585708745, 3600147697, 3614451106, 3628618433, 3642649144, 3656542712, 3670298613, 3683916329,
+  3697395348, 3710735162, 3723935269, 3736995171, 3749914379, 3762692404, 3775328765, 3787822988,
+  3800174601, 3812383140, 3824448145, 3836369162, 3848145741, 3859777440, 3871263820, 3882604450,
+  3893798902, 3904846754, 3915747591, 3926501002, 3937106583, 3947563934, 3957872662, 3968032378,
+  3978042699, 3987903250, 3997613658, 4007173558, 4016582591, 4025840401, 4034946641, 4043900968,
+  4052703044, 4061352537, 4069849124, 4078192482, 4086382299, 4094418266, 4102300081, 4110027446,
+  4117600071, 4125017671, 4132279966, 4139386683, 4146337555, 4153132319, 4159770720, 4166252509,
+  4172577440, 4178745276, 4184755784, 4190608739, 4196303920, 4201841112, 4207220108, 4212440704,
+  4217502704, 4222405917, 4227150159, 4231735252, 4236161021, 4240427302, 4244533933, 4248480760,
+  4252267634, 4255894413, 4259360959, 4262667143, 4265812840, 4268797931, 4271622305, 4274285855,
+  4276788480, 4279130086, 4281310585, 4283329896, 4285187942, 4286884652, 4288419964, 4289793820,
+  4291006167, 4292056960, 4292946160, 4293673732, 4294239650, 4294643893, 4294886444, 4294967296,
+];
+
+/// 2^x for x in [0, 1] in 256 steps, as Q32.32
+pub const EXP2_FRACTION: [i64; 257] = [
+  4294967296, 4306612134, 4318288544, 4329996612, 4341736423, 4353508065, 4365311623, 4377147183,
+  4389014833, 4400914660, 4412846750, 4424811191, 4436808071, 4448837478, 4460899500, 4472994226,
+  4485121744, 4497282142, 4509475511, 4521701940, 4533961517, 4546254334, 4558580480, 4570940045,
+  4583333121, 4595759798, 4608220167, 4620714319, 4633242347, 4645804341, 4658400394, 4671030599,
+  4683695048, 4696393833, 4709127049, 4721894787, 4734697143, 4747534209, 4760406080, 4773312851,
+  4786254615, 4799231467, 4812243504, 4825290820, 4838373510, 4851491672, 4864645400, 4877834792,
+  4891059943, 4904320952, 4917617915, 4930950930, 4944320094, 4957725506, 4971167263, 4984645465,
+  4998160210, 5011711597, 5025299726, 5038924695, 5052586606, 5066285558, 5080021652, 5093794988,
+  5107605667, 5121453791, 5135339461, 5149262779, 5163223846, 5177222766, 5191259641, 5205334574,
+  5219447668, 5233599026, 5247788752, 5262016951, 5276283726, 5290589183, 5304933425, 5319316559,
+  5333738689, 5348199922, 5362700363, 5377240118, 5391819295, 5406438001, 5421096341, 5435794424,
+  5450532358, 5465310250, 5480128210, 5494986345, 5509884764, 5524823577, 5539802893, 5554822823,
+  5569883475, 5584984961, 5600127392, 5615310878, 5630535530, 5645801460, 5661108781, 5676457604,
+  5691848042, 5707280207, 5722754214, 5738270175, 5753828203, 5769428414, 5785070921, 5800755840,
+  5816483285, 5832253371, 5848066214, 5863921930, 5879820635, 5895762446, 5911747479, 5927775853,
+  5943847684, 5959963090, 5976122189, 5992325100, 6008571941, 6024862833, 6041197893, 6057577242,
+  6074001000, 6090469287, 6106982225, 6123539933, 6140142534, 6156790150, 6173482901, 6190220911,
+  6207004303, 6223833199, 6240707722, 6257627997, 6274594148, 6291606299, 6308664574, 6325769099,
+  6342919999, 6360117399, 6377361427, 6394652208, 6411989869, 6429374537, 6446806340, 6464285405,
+  6481811861, 6499385836, 6517007458, 6534676858, 6552394164, 6570159507, 6587973017, 6605834824,
+  6623745059, 6641703853, 6659711339, 6677767649, 6695872913, 6714027267, 6732230841, 6750483771,
+  6768786189, 6787138230, 6805540029, 6823991719, 6842493438, 6861045320, 6879647501, 6898300117,
+  6917003306, 6935757205, 6954561950, 6973417680, 6992324534, 7011282649, 7030292165, 7049353220,
+  7068465956, 7087630511, 7106847027, 7126115644, 7145436504, 7164809747, 7184235517, 7203713956,
+  7223245206, 7242829410, 7262466713, 7282157258, 7301901189, 7321698651, 7341549790, 7361454751,
+  7381413680, 7401426722, 7421494026, 7441615738, 7461792005, 7482022975, 7502308797, 7522649620,
+  7543045592, 7563496864, 7584003584, 7604565904, 7625183973, 7645857945, 7666587968, 7687374197,
+  7708216783, 7729115879, 7750071638, 7771084214, 7792153760, 7813280433, 7834464385, 7855705773,
+  7877004752, 7898361478, 7919776109, 7941248800, 7962779710, 7984368996, 8006016816, 8027723330,
+  8049488696, 8071313074, 8093196623, 8115139505, 8137141881, 8159203910, 8181325756, 8203507581,
+  8225749546, 8248051816, 8270414553, 8292837922, 8315322086, 8337867211, 8360473463, 8383141006,
+  8405870007, 8428660633, 8451513050, 8474427426, 8497403930, 8520442729, 8543543993, 8566707891,
+  8589934592,
+];
+
+/// log2(x) for x in [1, 2] in 256 steps, as Q32.32
+pub const LOG2_MANTISSA: [i64; 257] = [
+  0, 24157255, 48220695, 72191046, 96069025, 119855343, 143550699, 167155786, 190671291, 214097890,
+  237436253, 260687042, 283850912, 306928510, 329920477, 352827446, 375650043, 398388887,
+  421044590, 443617759, 466108993, 488518883, 510848017, 533096975, 555266330, 577356649,
+  599368495, 621302422, 643158981, 664938715, 686642163, 708269857, 729822324, 751300086,
+  772703658, 794033552, 815290272, 836474320, 857586191, 878626374, 899595355, 920493615,
+  941321628, 962079865, 982768792, 1003388871, 1023940559, 1044424306, 1064840562, 1085189769,
+  1105472367, 1125688789, 1145839467, 1165924827, 1185945290, 1205901275, 1225793196, 1245621463,
+  1265386481, 1285088654, 1304728379, 1324306051, 1343822060, 1363276795, 1382670639, 1402003972,
+  1421277169, 1440490605, 1459644648, 1478739665, 1497776018, 1516754066, 1535674166, 1554536671,
+  1573341930, 1592090289, 1610782092, 1629417679, 1647997388, 1666521551, 1684990500, 1703404565,
+  1721764068, 1740069334, 1758320682, 1776518428, 1794662886, 1812754368, 1830793181, 1848779632,
+  1866714024, 1884596657, 1902427829, 1920207835, 1937936969, 1955615520, 1973243777, 1990822024,
+  2008350545, 2025829620, 2043259528, 2060640543, 2077972941, 2095256991, 2112492963, 2129681124,
+  2146821738, 2163915068, 2180961373, 2197960912, 2214913940, 2231820712, 2248681479, 2265496490,
+  2282265995, 2298990237, 2315669461, 2332303909, 2348893820, 2365439432, 2381940981, 2398398701,
+  2414812824, 2431183582, 2447511201, 2463795910, 2480037932, 2496237492, 2512394810, 2528510107,
+  2544583599, 2560615505, 2576606038, 2592555411, 2608463835, 2624331521, 2640158677, 2655945509,
+  2671692221, 2687399018, 2703066101, 2718693670, 2734281925, 2749831063, 2765341278, 2780812767,
+  2796245722, 2811640333, 2826996792, 2842315287, 2857596005, 2872839132, 2888044853, 2903213350,
+  2918344806, 2933439400, 2948497313, 2963518722, 2978503803, 2993452732, 3008365682, 3023242827,
+  3038084339, 3052890387, 3067661140, 3082396766, 3097097433, 3111763305, 3126394546, 3140991321,
+  3155553791, 3170082117, 3184576458, 3199036973, 3213463820, 3227857155, 3242217134, 3256543910,
+  3270837638, 3285098468, 3299326552, 3313522041, 3327685082, 3341815825, 3355914416, 3369981001,
+  3384015725, 3398018732, 3411990165, 3425930167, 3439838878, 3453716438, 3467562987, 3481378662,
+  3495163602, 3508917943, 3522641820, 3536335369, 3549998721, 3563632012, 3577235372, 3590808933,
+  3604352825, 3617867177, 3631352118, 3644807776, 3658234277, 3671631748, 3685000315, 3698340100,
+  3711651229, 3724933824, 3738188006, 3751413898, 3764611620, 3777781291, 3790923031, 3804036958,
+  3817123189, 3830181840, 3843213029, 3856216870, 3869193478, 3882142967, 3895065449, 3907961038,
+  3920829844, 3933671979, 3946487554, 3959276677, 3972039458, 3984776005, 3997486426, 4010170828,
+  4022829316, 4035461997, 4048068976, 4060650357, 4073206244, 4085736740, 4098241947, 4110721967,
+  4123176902, 4135606852, 4148011918, 4160392197, 4172747791, 4185078796, 4197385310, 4209667431,
+  4221925255, 4234158878, 4246368396, 4258553902, 4270715492, 4282853259, 4294967296,
+];
@@ -0,0 +1,14 @@
+use crate::float::Float;
+
+/// Values below this magnitude are flushed to zero, it is well above the denormal range of f32
+const THRESHOLD: f64 = 1e-30;
+
+/// Flush to zero the values small enough to become denormals, which are very slow on some CPUs
+#[inline]
+pub fn flush_denormal<F: Float>(value: F) -> F {
+  if value.abs() < F::val(THRESHOLD) {
+    F::zero()
+  } else {
+    value
+  }
+}
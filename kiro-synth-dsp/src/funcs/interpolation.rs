@@ -14,3 +14,32 @@ pub fn linear_interpolation<F: Float>(x1: F, x2: F, y1: F, y2: F, x: F) -> F {
     dx * y2 + (F::one() - dx) * y1
   }
 }
+
+/// Catmull-Rom interpolation at `x` between [0.0, 1.0) from `y1` to `y2`,
+/// with `y0` and `y3` the points before and after them
+pub fn cubic_interpolation<F: Float>(y0: F, y1: F, y2: F, y3: F, x: F) -> F {
+  let half = F::val(0.5);
+  let c1 = half * (y2 - y0);
+  let c2 = y0 - F::val(2.5) * y1 + F::val(2.0) * y2 - half * y3;
+  let c3 = half * (y3 - y0) + F::val(1.5) * (y1 - y2);
+  ((c3 * x + c2) * x + c1) * x + y1
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::funcs::interpolation::cubic_interpolation;
+
+  #[test]
+  fn cubic_goes_through_the_points() {
+    assert!((cubic_interpolation(3.0, 1.0, 2.0, 5.0, 0.0f64) - 1.0).abs() < 1e-12);
+    assert!((cubic_interpolation(3.0, 1.0, 2.0, 5.0, 1.0f64) - 2.0).abs() < 1e-12);
+  }
+
+  #[test]
+  fn cubic_is_exact_for_lines() {
+    for step in 0..10 {
+      let x = step as f64 / 10.0;
+      assert!((cubic_interpolation(-1.0, 0.0, 1.0, 2.0, x) - x).abs() < 1e-12);
+    }
+  }
+}
@@ -1,5 +1,8 @@
 pub mod concave_transforms;
 pub mod decibels;
+pub mod denormals;
 pub mod interpolation;
+pub mod pan;
 pub mod parabolic_sine;
+pub mod random;
 pub mod signal_polarity;
@@ -0,0 +1,10 @@
+use crate::float::Float;
+
+/// Left and right gains for a pan between [-1.0, 1.0] keeping a constant power (sin/cos law)
+pub fn constant_power_pan<F: Float>(pan: F) -> (F, F) {
+  let pan = pan.max(F::one().neg()).min(F::one());
+  let angle = F::PI / F::val(4.0) * (pan + F::one());
+  let left = angle.cos().max(F::zero()).min(F::one());
+  let right = angle.sin().max(F::zero()).min(F::one());
+  (left, right)
+}
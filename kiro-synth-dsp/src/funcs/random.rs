@@ -0,0 +1,63 @@
+use crate::float::Float;
+
+/// Xorshift pseudo-random generator, cheap and allocation free
+#[derive(Debug, Clone, Copy)]
+pub struct Random {
+  state: u32,
+}
+
+impl Default for Random {
+  fn default() -> Self {
+    Random::new(0x2545_f491)
+  }
+}
+
+impl Random {
+  pub fn new(seed: u32) -> Self {
+    // zero is a fixed point for xorshift
+    let state = if seed == 0 { 0x2545_f491 } else { seed };
+    Random { state }
+  }
+
+  pub fn next_u32(&mut self) -> u32 {
+    let mut x = self.state;
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    self.state = x;
+    x
+  }
+
+  /// Next value between [0.0, 1.0)
+  pub fn next_unipolar<F: Float>(&mut self) -> F {
+    F::val(self.next_u32() >> 8) / F::val(1u32 << 24)
+  }
+
+  /// Next value between [-1.0, 1.0)
+  pub fn next_bipolar<F: Float>(&mut self) -> F {
+    F::val(2.0) * self.next_unipolar::<F>() - F::one()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::funcs::random::Random;
+
+  #[test]
+  fn values_within_range() {
+    let mut random = Random::default();
+    for _ in 0..10000 {
+      let value: f32 = random.next_bipolar();
+      assert!((-1.0..1.0).contains(&value));
+    }
+  }
+
+  #[test]
+  fn same_seed_same_sequence() {
+    let mut a = Random::new(7);
+    let mut b = Random::new(7);
+    for _ in 0..100 {
+      assert_eq!(a.next_u32(), b.next_u32());
+    }
+  }
+}
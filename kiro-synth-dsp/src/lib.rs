@@ -1,10 +1,11 @@
-//#![no_std]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 pub mod blep;
 pub mod dca;
 pub mod effects;
 pub mod envgen;
 pub mod filters;
+pub mod fixed;
 pub mod float;
 pub mod funcs;
 pub mod meters;
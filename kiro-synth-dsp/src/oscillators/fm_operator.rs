@@ -0,0 +1,136 @@
+use crate::float::Float;
+use crate::oscillators::clamp_modulo;
+
+/// Sine operator for FM synthesis, where the modulation is applied to the phase (DX style).
+/// Several operators can be chained feeding the output of one as the modulation of another.
+#[derive(Debug, Clone)]
+pub struct FmOperator<F: Float> {
+  inv_sample_rate: F,
+  phase_inc: F,
+  freq: F,
+  feedback: F,
+  level: F,
+
+  modulo: F,
+  /// The last two values of the sine, before applying the level
+  y1: F,
+  y2: F,
+}
+
+impl<F: Float> FmOperator<F> {
+  pub fn new(sample_rate: F) -> Self {
+    FmOperator {
+      inv_sample_rate: sample_rate.recip(),
+      phase_inc: F::zero(),
+      freq: F::zero(),
+      feedback: F::zero(),
+      level: F::one(),
+      modulo: F::zero(),
+      y1: F::zero(),
+      y2: F::zero(),
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.inv_sample_rate = sample_rate.recip();
+    self.set_frequency(self.freq);
+  }
+
+  /// Frequency in Hz
+  pub fn set_frequency(&mut self, freq: F) {
+    self.freq = freq;
+    self.phase_inc = freq * self.inv_sample_rate;
+  }
+
+  /// Amount in radians of the self modulation, from the average of the last two values for stability
+  pub fn set_feedback(&mut self, feedback: F) {
+    self.feedback = feedback;
+  }
+
+  /// Gain of the output, for a modulator it is the modulation index in radians
+  pub fn set_level(&mut self, level: F) {
+    self.level = level;
+  }
+
+  pub fn reset(&mut self) {
+    self.modulo = F::zero();
+    self.y1 = F::zero();
+    self.y2 = F::zero();
+  }
+
+  /// Generate the next value with the phase modulated by the input in radians
+  pub fn generate(&mut self, modulation: F) -> F {
+    let self_modulation = self.feedback * (self.y1 + self.y2) * F::val(0.5);
+    let angle = self.modulo * F::val(2.0) * F::PI + modulation + self_modulation;
+    let value = angle.sin();
+
+    self.y2 = self.y1;
+    self.y1 = value;
+    self.modulo = clamp_modulo(self.modulo + self.phase_inc);
+
+    value * self.level
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::float::Float;
+  use crate::oscillators::fm_operator::FmOperator;
+
+  const SAMPLE_RATE: f64 = 48000.0;
+
+  /// Amplitude of the frequency in the values using the Goertzel algorithm
+  fn amplitude(values: &[f64], freq: f64) -> f64 {
+    let coefficient = 2.0 * (2.0 * f64::PI * freq / SAMPLE_RATE).cos();
+    let (mut s1, mut s2) = (0.0, 0.0);
+    for value in values {
+      let s0 = value + coefficient * s1 - s2;
+      s2 = s1;
+      s1 = s0;
+    }
+    let power = s1 * s1 + s2 * s2 - coefficient * s1 * s2;
+    2.0 * power.sqrt() / values.len() as f64
+  }
+
+  fn render(carrier_freq: f64, modulator_freq: f64, index: f64, feedback: f64) -> Vec<f64> {
+    let mut modulator = FmOperator::new(SAMPLE_RATE);
+    modulator.set_frequency(modulator_freq);
+    modulator.set_level(index);
+    let mut carrier = FmOperator::new(SAMPLE_RATE);
+    carrier.set_frequency(carrier_freq);
+    carrier.set_feedback(feedback);
+    (0..48000)
+      .map(|_| carrier.generate(modulator.generate(0.0)))
+      .collect()
+  }
+
+  #[test]
+  fn sidebands_are_spaced_by_the_modulator_frequency() {
+    // 1:5 ratio with an index of 1 radian, the sidebands follow the Bessel functions
+    let values = render(1000.0, 200.0, 1.0, 0.0);
+    let expected = [
+      (1000.0, 0.765),
+      (800.0, 0.440),
+      (1200.0, 0.440),
+      (600.0, 0.115),
+      (1400.0, 0.115),
+      (900.0, 0.0),
+      (1100.0, 0.0),
+      (1300.0, 0.0),
+    ];
+    for (freq, bessel) in expected.iter() {
+      let amplitude = amplitude(&values, *freq);
+      assert!((amplitude - bessel).abs() < 0.01, "{} {}", freq, amplitude);
+    }
+  }
+
+  #[test]
+  fn feedback_adds_harmonics() {
+    let pure = render(500.0, 0.0, 0.0, 0.0);
+    assert!(amplitude(&pure, 1000.0) < 1e-3);
+
+    let with_feedback = render(500.0, 0.0, 0.0, 1.0);
+    assert!(amplitude(&with_feedback, 1000.0) > 0.1);
+    assert!(with_feedback.iter().all(|value| value.abs() <= 1.0));
+  }
+}
@@ -9,6 +9,8 @@ use crate::oscillators::osc_waveform::OscWaveform;
 #[derive(Debug, Clone)]
 pub struct Lfo<F: Float> {
   waveform: OscWaveform<F>,
+  /// Seed for the random waveforms
+  seed: Option<u32>,
   rate: F,
   phase: F,
   depth: F,
@@ -25,6 +27,7 @@ impl<F: Float> Lfo<F> {
     let modulo = waveform.initial_modulo();
     Lfo {
       waveform,
+      seed: None,
       rate: F::one(),
       phase: F::zero(),
       depth: F::one(),
@@ -39,10 +42,19 @@ impl<F: Float> Lfo<F> {
   /// Set the waveform
   pub fn set_waveform(&mut self, waveform: OscWaveform<F>) {
     self.waveform = waveform;
+    if let Some(seed) = self.seed {
+      self.waveform.set_seed(seed);
+    }
     self.reset_modulo();
     // FIXME figure out how to avoid clips after changing the waveform and the module
   }
 
+  /// Set the seed for the random waveforms, kept when the waveform changes
+  pub fn set_seed(&mut self, seed: u32) {
+    self.seed = Some(seed);
+    self.waveform.set_seed(seed);
+  }
+
   /// Set the rate
   pub fn set_rate(&mut self, rate: F) {
     self.rate = rate;
@@ -67,6 +79,7 @@ impl<F: Float> Lfo<F> {
 
   /// Reset the LFO
   pub fn reset(&mut self) {
+    self.waveform.reset();
     self.reset_modulo();
   }
 
@@ -74,6 +87,7 @@ impl<F: Float> Lfo<F> {
   pub fn generate(&mut self) -> F {
     if self.phase_inc_invalidated {
       self.phase_inc = self.rate * self.inv_sample_rate;
+      self.phase_inc_invalidated = false;
     }
 
     let signal = self.waveform.generate(self.modulo, self.phase_inc);
@@ -85,3 +99,50 @@ impl<F: Float> Lfo<F> {
     self.modulo = clamp_modulo(self.waveform.initial_modulo() + self.phase);
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use assert_approx_eq::assert_approx_eq;
+
+  use crate::oscillators::lfo::Lfo;
+  use crate::oscillators::osc_waveform::OscWaveform;
+  use crate::waveforms::random_sample_hold::RandomSampleHold;
+  use crate::waveforms::saw_trivial::SawTrivial;
+  use crate::waveforms::square_trivial::SquareTrivial;
+
+  #[test]
+  fn square_waveform() {
+    let mut lfo = Lfo::new(8.0f64);
+    lfo.set_waveform(OscWaveform::SquareTrivial(SquareTrivial::default()));
+    let values: Vec<f64> = (0..16).map(|_| lfo.generate()).collect();
+    let period = [1.0, 1.0, 1.0, 1.0, 1.0, -1.0, -1.0, -1.0];
+    for (value, expected) in values.iter().zip(period.iter().cycle()) {
+      assert_approx_eq!(value, expected);
+    }
+  }
+
+  #[test]
+  fn saw_waveform_with_depth() {
+    let mut lfo = Lfo::new(4.0f64);
+    lfo.set_waveform(OscWaveform::SawTrivial(SawTrivial::default()));
+    lfo.set_depth(0.5);
+    let values: Vec<f64> = (0..8).map(|_| lfo.generate()).collect();
+    let period = [0.0, 0.25, -0.5, -0.25];
+    for (value, expected) in values.iter().zip(period.iter().cycle()) {
+      assert_approx_eq!(value, expected);
+    }
+  }
+
+  #[test]
+  fn seed_is_kept_when_the_waveform_changes() {
+    fn render(seed: u32) -> Vec<f64> {
+      let mut lfo = Lfo::new(8.0f64);
+      lfo.set_seed(seed);
+      lfo.set_waveform(OscWaveform::RandomSampleHold(RandomSampleHold::default()));
+      (0..32).map(|_| lfo.generate()).collect()
+    }
+
+    assert_eq!(render(1), render(1));
+    assert_ne!(render(1), render(2));
+  }
+}
@@ -1,6 +1,8 @@
 use crate::float::Float;
 
+pub mod fm_operator;
 pub mod lfo;
+pub mod noise;
 pub mod osc_freq_linear_mod;
 pub mod osc_pitch_shift;
 pub mod osc_waveform;
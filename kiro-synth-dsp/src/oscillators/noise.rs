@@ -0,0 +1,169 @@
+use crate::float::Float;
+use crate::funcs::random::Random;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseKind {
+  /// Flat spectrum
+  White,
+  /// -3dB per octave, using the Paul Kellet filter approximation
+  Pink,
+  /// -6dB per octave, integrating white noise with a leak to avoid DC drift
+  Brown,
+}
+
+#[derive(Debug, Clone)]
+pub struct Noise<F: Float> {
+  kind: NoiseKind,
+  seed: u32,
+  random: Random,
+  pink: [F; 7],
+  brown: F,
+}
+
+impl<F: Float> Noise<F> {
+  const PINK_POLES: [f64; 6] = [0.99886, 0.99332, 0.96900, 0.86650, 0.55000, -0.7616];
+  const PINK_GAINS: [f64; 6] = [
+    0.0555179, 0.0750759, 0.1538520, 0.3104856, 0.5329522, -0.0168980,
+  ];
+  const PINK_DIRECT_GAIN: f64 = 0.5362;
+  const PINK_DELAYED_GAIN: f64 = 0.115926;
+  const PINK_SCALE: f64 = 0.11;
+  const BROWN_LEAK: f64 = 1.02;
+  const BROWN_STEP: f64 = 0.02;
+  const BROWN_SCALE: f64 = 3.5;
+
+  pub fn new(seed: u32) -> Self {
+    Noise {
+      kind: NoiseKind::White,
+      seed,
+      random: Random::new(seed),
+      pink: [F::zero(); 7],
+      brown: F::zero(),
+    }
+  }
+
+  pub fn set_kind(&mut self, kind: NoiseKind) {
+    self.kind = kind;
+  }
+
+  pub fn get_kind(&self) -> NoiseKind {
+    self.kind
+  }
+
+  /// Set the seed and restart the sequence from it
+  pub fn set_seed(&mut self, seed: u32) {
+    self.seed = seed;
+    self.reset();
+  }
+
+  /// Restart the same sequence from the seed
+  pub fn reset(&mut self) {
+    self.random = Random::new(self.seed);
+    self.pink = [F::zero(); 7];
+    self.brown = F::zero();
+  }
+
+  pub fn generate(&mut self) -> F {
+    let white = self.random.next_bipolar::<F>();
+    match self.kind {
+      NoiseKind::White => white,
+      NoiseKind::Pink => {
+        let mut pink = F::zero();
+        for (index, state) in self.pink.iter_mut().take(6).enumerate() {
+          *state =
+            F::val(Self::PINK_POLES[index]) * *state + F::val(Self::PINK_GAINS[index]) * white;
+          pink = pink + *state;
+        }
+        pink = pink + self.pink[6] + white * F::val(Self::PINK_DIRECT_GAIN);
+        self.pink[6] = white * F::val(Self::PINK_DELAYED_GAIN);
+        pink * F::val(Self::PINK_SCALE)
+      }
+      NoiseKind::Brown => {
+        self.brown = (self.brown + white * F::val(Self::BROWN_STEP)) / F::val(Self::BROWN_LEAK);
+        self.brown * F::val(Self::BROWN_SCALE)
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::oscillators::noise::{Noise, NoiseKind};
+
+  const BLOCK_SIZE: usize = 1024;
+  const NUM_BLOCKS: usize = 64;
+
+  /// Average power per bin for each octave band starting at bin 16
+  fn octave_powers(kind: NoiseKind) -> Vec<f64> {
+    let mut noise = Noise::<f64>::new(1);
+    noise.set_kind(kind);
+    let mut powers = vec![0.0; 4];
+    for _ in 0..NUM_BLOCKS {
+      let block: Vec<f64> = (0..BLOCK_SIZE).map(|_| noise.generate()).collect();
+      for (octave, power) in powers.iter_mut().enumerate() {
+        let first_bin = 16 << octave;
+        for bin in first_bin..first_bin * 2 {
+          *power += goertzel(&block, bin) / first_bin as f64;
+        }
+      }
+    }
+    powers
+  }
+
+  fn goertzel(samples: &[f64], bin: usize) -> f64 {
+    let omega = 2.0 * std::f64::consts::PI * bin as f64 / samples.len() as f64;
+    let coefficient = 2.0 * omega.cos();
+    let (mut s1, mut s2) = (0.0, 0.0);
+    for sample in samples {
+      let s0 = sample + coefficient * s1 - s2;
+      s2 = s1;
+      s1 = s0;
+    }
+    s1 * s1 + s2 * s2 - coefficient * s1 * s2
+  }
+
+  fn slopes_db(powers: &[f64]) -> Vec<f64> {
+    powers
+      .windows(2)
+      .map(|pair| 10.0 * (pair[1] / pair[0]).log10())
+      .collect()
+  }
+
+  #[test]
+  fn white_is_flat() {
+    for slope in slopes_db(&octave_powers(NoiseKind::White)) {
+      assert!(slope.abs() < 1.0, "{}", slope);
+    }
+  }
+
+  #[test]
+  fn pink_rolls_off_3db_per_octave() {
+    for slope in slopes_db(&octave_powers(NoiseKind::Pink)) {
+      assert!((slope + 3.0).abs() < 1.0, "{}", slope);
+    }
+  }
+
+  #[test]
+  fn brown_rolls_off_6db_per_octave() {
+    let slopes = slopes_db(&octave_powers(NoiseKind::Brown));
+    // the leak flattens the lowest octave
+    for slope in &slopes[1..] {
+      assert!((slope + 6.0).abs() < 1.5, "{}", slope);
+    }
+  }
+
+  #[test]
+  fn reset_reproduces_the_sequence() {
+    for kind in &[NoiseKind::White, NoiseKind::Pink, NoiseKind::Brown] {
+      let mut noise = Noise::<f32>::new(42);
+      noise.set_kind(*kind);
+      let first: Vec<f32> = (0..64).map(|_| noise.generate()).collect();
+      noise.reset();
+      let second: Vec<f32> = (0..64).map(|_| noise.generate()).collect();
+      assert!(first
+        .iter()
+        .zip(second.iter())
+        .all(|(a, b)| (a - b).abs() < 1e-9));
+    }
+  }
+}
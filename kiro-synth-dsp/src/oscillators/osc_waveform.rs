@@ -1,7 +1,11 @@
 use crate::float::Float;
+use crate::oscillators::clamp_modulo;
+use crate::waveforms::random_sample_hold::RandomSampleHold;
 use crate::waveforms::saw_blep::SawBlep;
 use crate::waveforms::saw_trivial::SawTrivial;
 use crate::waveforms::sine_parabolic::SineParabolic;
+use crate::waveforms::square_blep::SquareBlep;
+use crate::waveforms::square_trivial::SquareTrivial;
 use crate::waveforms::triangle_dpw2x::TriangleDpw2x;
 use crate::waveforms::triangle_trivial::TriangleTrivial;
 use crate::waveforms::Waveform;
@@ -13,6 +17,9 @@ pub enum OscWaveform<F: Float> {
   SawBlep(SawBlep<F>),
   TriangleTrivial(TriangleTrivial),
   TriangleDpw2x(TriangleDpw2x<F>),
+  SquareTrivial(SquareTrivial<F>),
+  SquareBlep(SquareBlep<F>),
+  RandomSampleHold(RandomSampleHold<F>),
 }
 
 impl<F: Float> Default for OscWaveform<F> {
@@ -29,9 +36,63 @@ impl<F: Float> OscWaveform<F> {
       OscWaveform::SawBlep(wf) => wf.initial_modulo(),
       OscWaveform::TriangleTrivial(wf) => wf.initial_modulo(),
       OscWaveform::TriangleDpw2x(wf) => wf.initial_modulo(),
+      OscWaveform::SquareTrivial(wf) => wf.initial_modulo(),
+      OscWaveform::SquareBlep(wf) => wf.initial_modulo(),
+      OscWaveform::RandomSampleHold(wf) => wf.initial_modulo(),
     }
   }
 
+  pub fn reset(&mut self) {
+    match self {
+      OscWaveform::SineParabolic(wf) => Waveform::<F>::reset(wf),
+      OscWaveform::SawTrivial(wf) => Waveform::<F>::reset(wf),
+      OscWaveform::SawBlep(wf) => wf.reset(),
+      OscWaveform::TriangleTrivial(wf) => Waveform::<F>::reset(wf),
+      OscWaveform::TriangleDpw2x(wf) => wf.reset(),
+      OscWaveform::SquareTrivial(wf) => wf.reset(),
+      OscWaveform::SquareBlep(wf) => wf.reset(),
+      OscWaveform::RandomSampleHold(wf) => wf.reset(),
+    }
+  }
+
+  /// Seed for the waveforms generating random values, the rest ignore it
+  pub fn set_seed(&mut self, seed: u32) {
+    if let OscWaveform::RandomSampleHold(wf) = self {
+      wf.set_seed(seed)
+    }
+  }
+
+  /// Pulse width for the pulse waveforms, the rest ignore it
+  pub fn set_pulse_width(&mut self, pulse_width: F) {
+    if let OscWaveform::SquareBlep(wf) = self {
+      wf.set_pulse_width(pulse_width)
+    }
+  }
+
+  /// Value at the modulo without any band limiting correction
+  pub fn naive(&self, modulo: F, phase_inc: F) -> F {
+    match self {
+      OscWaveform::SineParabolic(wf) => wf.clone().generate(modulo, phase_inc),
+      OscWaveform::SawTrivial(wf) => wf.clone().generate(modulo, phase_inc),
+      OscWaveform::SawBlep(wf) => wf.naive(modulo),
+      OscWaveform::TriangleTrivial(wf) => wf.clone().generate(modulo, phase_inc),
+      OscWaveform::TriangleDpw2x(wf) => {
+        // the differentiator needs the previous step to settle
+        let mut wf = wf.clone();
+        wf.generate(clamp_modulo(modulo - phase_inc), phase_inc);
+        wf.generate(modulo, phase_inc)
+      }
+      OscWaveform::SquareTrivial(wf) => wf.clone().generate(modulo, phase_inc),
+      OscWaveform::SquareBlep(wf) => wf.naive(modulo),
+      OscWaveform::RandomSampleHold(wf) => wf.value(),
+    }
+  }
+
+  /// Whether the waveform already corrects the discontinuity when the modulo wraps
+  pub fn corrects_wrap(&self) -> bool {
+    matches!(self, OscWaveform::SawBlep(_) | OscWaveform::SquareBlep(_))
+  }
+
   pub fn generate(&mut self, modulo: F, phase_inc: F) -> F {
     match self {
       OscWaveform::SineParabolic(wf) => wf.generate(modulo, phase_inc),
@@ -39,6 +100,9 @@ impl<F: Float> OscWaveform<F> {
       OscWaveform::SawBlep(wf) => wf.generate(modulo, phase_inc),
       OscWaveform::TriangleTrivial(wf) => wf.generate(modulo, phase_inc),
       OscWaveform::TriangleDpw2x(wf) => wf.generate(modulo, phase_inc),
+      OscWaveform::SquareTrivial(wf) => wf.generate(modulo, phase_inc),
+      OscWaveform::SquareBlep(wf) => wf.generate(modulo, phase_inc),
+      OscWaveform::RandomSampleHold(wf) => wf.generate(modulo, phase_inc),
     }
   }
 }
@@ -10,11 +10,16 @@ pub struct PitchedOscillator<F: Float> {
   pitch_shift: OscPitchShift<F>,
   amplitude: F,
   amp_mod: F,
+  pulse_width: F,
 
   modulo: F,
   phase_inc: F,
   phase_inc_invalidated: bool,
   inv_sample_rate: F,
+
+  sync_position: Option<F>,
+  sync_residual: F,
+  restart_position: F,
 }
 
 impl<F: Float> PitchedOscillator<F> {
@@ -29,17 +34,23 @@ impl<F: Float> PitchedOscillator<F> {
       pitch_shift,
       amplitude: F::one(),
       amp_mod: F::zero(),
+      pulse_width: F::val(0.5),
 
       modulo,
       phase_inc: F::zero(),
       phase_inc_invalidated: true,
       inv_sample_rate: sample_rate.recip(),
+
+      sync_position: None,
+      sync_residual: F::zero(),
+      restart_position: F::zero(),
     }
   }
 
   /// Set the waveform
   pub fn set_waveform(&mut self, waveform: OscWaveform<F>) {
     self.waveform = waveform;
+    self.waveform.set_pulse_width(self.pulse_width);
     self.modulo = self.waveform.initial_modulo();
     // FIXME figure out how to avoid clips after changing the waveform and the module
     // self.phase_inc_invalidated = true; // TODO really necessary ???
@@ -91,6 +102,12 @@ impl<F: Float> PitchedOscillator<F> {
     self.amp_mod = amp_mod;
   }
 
+  /// Set the pulse width for the pulse waveforms, between 0 and 1
+  pub fn set_pulse_width(&mut self, pulse_width: F) {
+    self.pulse_width = pulse_width;
+    self.waveform.set_pulse_width(pulse_width);
+  }
+
   /// Set the sample rate
   pub fn set_sample_rate(&mut self, sample_rate: F) {
     self.inv_sample_rate = sample_rate.recip();
@@ -100,6 +117,21 @@ impl<F: Float> PitchedOscillator<F> {
   // Reset the oscillator
   pub fn reset(&mut self) {
     self.modulo = self.waveform.initial_modulo();
+    self.sync_position = None;
+    self.sync_residual = F::zero();
+    self.restart_position = F::zero();
+  }
+
+  /// Restart the cycle from the beginning, as in hard sync, after the next generated value.
+  /// The position between that value (0) and the following one (1) is used to reduce the aliasing.
+  pub fn sync(&mut self, position: F) {
+    self.sync_position = Some(position.max(F::zero()).min(F::one()));
+  }
+
+  /// Position where the cycle restarts between the last generated value (0) and the next one (1),
+  /// or zero when it doesn't restart. It can drive the sync of other oscillators.
+  pub fn get_restart_position(&self) -> F {
+    self.restart_position
   }
 
   /// Generate the next value
@@ -108,13 +140,151 @@ impl<F: Float> PitchedOscillator<F> {
       self.update_phase_inc();
     }
 
-    let signal = self.waveform.generate(self.modulo, self.phase_inc);
-    self.modulo = clamp_modulo(self.modulo + self.phase_inc);
+    let mut signal = self.waveform.generate(self.modulo, self.phase_inc) + self.sync_residual;
+    self.sync_residual = F::zero();
+
+    match self.sync_position.take() {
+      Some(position) => {
+        signal = signal + self.sync_correction(position);
+        self.modulo = clamp_modulo((F::one() - position) * self.phase_inc);
+        self.restart_position = position;
+        // let the waveforms with state settle as if the new cycle had been running
+        self
+          .waveform
+          .generate(clamp_modulo(self.modulo - self.phase_inc), self.phase_inc);
+      }
+      None => {
+        let next_modulo = self.modulo + self.phase_inc;
+        self.modulo = clamp_modulo(next_modulo);
+        self.restart_position = if next_modulo >= F::one() && self.phase_inc > F::zero() {
+          F::one() - self.modulo / self.phase_inc
+        } else {
+          F::zero()
+        };
+      }
+    }
+
     signal * self.amplitude + self.amp_mod
   }
 
+  /// 2-point polyBLEP for the discontinuity of the sync, returns the residual for the current value
+  /// and keeps the one for the next value
+  fn sync_correction(&mut self, position: F) -> F {
+    let half = F::val(0.5);
+    let before = self.waveform.naive(
+      clamp_modulo(self.modulo + position * self.phase_inc),
+      self.phase_inc,
+    );
+    let height = self.waveform.naive(F::zero(), self.phase_inc) - before;
+
+    // the waveforms correcting their own wrap already add the residual of the jump from the end
+    // of the cycle to the beginning once the modulo restarts
+    let next_height = if self.waveform.corrects_wrap() {
+      self.waveform.naive(F::one(), self.phase_inc) - before
+    } else {
+      height
+    };
+    self.sync_residual = (next_height * position * position * half).neg();
+
+    let distance = F::one() - position;
+    height * distance * distance * half
+  }
+
   fn update_phase_inc(&mut self) {
     let freq = self.pitch_freq * self.pitch_shift.multiplier();
     self.phase_inc = freq * self.inv_sample_rate;
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::oscillators::osc_waveform::OscWaveform;
+  use crate::oscillators::pitched_oscillator::PitchedOscillator;
+  use crate::waveforms::saw_blep::SawBlep;
+
+  fn saw(sample_rate: f64, freq: f64) -> PitchedOscillator<f64> {
+    PitchedOscillator::new(sample_rate, OscWaveform::SawBlep(SawBlep::default()), freq)
+  }
+
+  #[derive(PartialEq)]
+  enum Sync {
+    Off,
+    Corrected,
+    /// Restart the modulo without correcting the discontinuity
+    Trivial,
+  }
+
+  /// Values of the slave oscillator driven by a master oscillator
+  fn render(master_freq: f64, slave_freq: f64, sync: Sync) -> Vec<f64> {
+    let mut master = saw(48000.0, master_freq);
+    let mut slave = saw(48000.0, slave_freq);
+    (0..4800)
+      .map(|_| {
+        master.generate();
+        if master.get_restart_position() > 0.0 {
+          match sync {
+            Sync::Off => {}
+            Sync::Corrected => slave.sync(master.get_restart_position()),
+            Sync::Trivial => slave.modulo = 0.0,
+          }
+        }
+        slave.generate()
+      })
+      .collect()
+  }
+
+  /// Energy of the second derivative, it grows with the high frequency content
+  fn roughness(values: &[f64]) -> f64 {
+    values[1000..]
+      .windows(3)
+      .map(|w| (w[2] - 2.0 * w[1] + w[0]).powi(2))
+      .sum()
+  }
+
+  fn is_periodic(values: &[f64], period: usize) -> bool {
+    values[1000..]
+      .iter()
+      .zip(values[1000 + period..].iter())
+      .all(|(a, b)| (a - b).abs() < 1e-6)
+  }
+
+  #[test]
+  fn restart_position() {
+    let mut osc = saw(100.0, 37.5);
+    let positions: Vec<f64> = (0..10)
+      .map(|_| {
+        osc.generate();
+        osc.get_restart_position()
+      })
+      .collect();
+    // it starts at the middle of the cycle and every step moves 3/8 of it
+    let expected = [
+      0.0,
+      1.0 / 3.0,
+      0.0,
+      1.0,
+      0.0,
+      0.0,
+      2.0 / 3.0,
+      0.0,
+      0.0,
+      1.0 / 3.0,
+    ];
+    for (position, expected) in positions.iter().zip(expected.iter()) {
+      assert!((position - expected).abs() < 1e-9, "{:?}", positions);
+    }
+  }
+
+  #[test]
+  fn hard_sync_follows_the_master_period() {
+    // the master period is 100 samples and the slave runs 3.3 times faster
+    assert!(!is_periodic(&render(480.0, 1584.0, Sync::Off), 100));
+    let synced = render(480.0, 1584.0, Sync::Corrected);
+    assert!(is_periodic(&synced, 100));
+    assert!(synced.iter().all(|value| value.abs() <= 1.5));
+
+    let trivial = render(480.0, 1584.0, Sync::Trivial);
+    assert!(is_periodic(&trivial, 100));
+    assert!(roughness(&synced) < roughness(&trivial) * 0.8);
+  }
+}
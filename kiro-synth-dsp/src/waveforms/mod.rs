@@ -1,9 +1,11 @@
 use crate::float::Float;
 
 pub mod exponential;
+pub mod random_sample_hold;
 pub mod saw_blep;
 pub mod saw_trivial;
 pub mod sine_parabolic;
+pub mod square_blep;
 pub mod square_trivial;
 pub mod triangle_dpw2x;
 pub mod triangle_trivial;
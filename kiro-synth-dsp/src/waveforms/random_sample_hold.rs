@@ -0,0 +1,80 @@
+use crate::float::Float;
+use crate::funcs::random::Random;
+use crate::waveforms::Waveform;
+
+/// Random value between [-1.0, 1.0) held during a whole period
+#[derive(Debug, Clone)]
+pub struct RandomSampleHold<F: Float> {
+  seed: u32,
+  random: Random,
+  value: F,
+}
+
+impl<F: Float> Default for RandomSampleHold<F> {
+  fn default() -> Self {
+    Self::new(Random::default().next_u32())
+  }
+}
+
+impl<F: Float> RandomSampleHold<F> {
+  pub fn new(seed: u32) -> Self {
+    RandomSampleHold {
+      seed,
+      random: Random::new(seed),
+      value: F::zero(),
+    }
+  }
+
+  /// Set the seed and restart the sequence from it
+  pub fn set_seed(&mut self, seed: u32) {
+    self.seed = seed;
+    self.random = Random::new(seed);
+  }
+
+  /// The value being held
+  pub fn value(&self) -> F {
+    self.value
+  }
+}
+
+impl<F: Float> Waveform<F> for RandomSampleHold<F> {
+  fn reset(&mut self) {
+    self.random = Random::new(self.seed);
+  }
+
+  fn generate(&mut self, modulo: F, phase_inc: F) -> F {
+    // the modulo only falls behind the increment at the start of a period
+    if modulo < phase_inc {
+      self.value = self.random.next_bipolar();
+    }
+    self.value
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::oscillators::clamp_modulo;
+  use crate::waveforms::random_sample_hold::RandomSampleHold;
+  use crate::waveforms::Waveform;
+
+  #[test]
+  fn holds_value_during_period() {
+    let mut waveform = RandomSampleHold::<f64>::default();
+    let phase_inc = 1.0 / 64.0;
+    let mut modulo = 0.0;
+    let mut previous: Option<f64> = None;
+    for _ in 0..10 {
+      let value = waveform.generate(modulo, phase_inc);
+      assert!((-1.0..1.0).contains(&value));
+      if let Some(previous) = previous {
+        assert!((value - previous).abs() > 0.0);
+      }
+      modulo = clamp_modulo(modulo + phase_inc);
+      for _ in 1..64 {
+        assert!((waveform.generate(modulo, phase_inc) - value).abs() < 1e-12);
+        modulo = clamp_modulo(modulo + phase_inc);
+      }
+      previous = Some(value);
+    }
+  }
+}
@@ -67,15 +67,10 @@ impl<F: Float> SawBlep<F> {
   pub fn with_saturation(self, saturation: F) -> Self {
     Self { saturation, ..self }
   }
-}
 
-impl<F: Float> Waveform<F> for SawBlep<F> {
-  fn initial_modulo(&self) -> F {
-    F::val(0.5)
-  }
-
-  fn generate(&mut self, modulo: F, phase_inc: F) -> F {
-    let signal = match self.mode {
+  /// The saw without the correction of the discontinuity
+  pub fn naive(&self, modulo: F) -> F {
+    match self.mode {
       Mode::Normal => unipolar_to_bipolar(modulo),
       Mode::Unipolar => {
         unipolar_to_bipolar((self.saturation * modulo).tanh() / self.saturation.tanh())
@@ -83,7 +78,17 @@ impl<F: Float> Waveform<F> for SawBlep<F> {
       Mode::Bipolar => {
         (self.saturation * unipolar_to_bipolar(modulo)).tanh() / self.saturation.tanh()
       }
-    };
+    }
+  }
+}
+
+impl<F: Float> Waveform<F> for SawBlep<F> {
+  fn initial_modulo(&self) -> F {
+    F::val(0.5)
+  }
+
+  fn generate(&mut self, modulo: F, phase_inc: F) -> F {
+    let signal = self.naive(modulo);
 
     let residual = match self.correction {
       Correction::TwoPointBlep => BLEP.residual(modulo, phase_inc.abs(), F::one(), false, 1, false),
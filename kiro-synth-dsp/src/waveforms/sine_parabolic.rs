@@ -12,7 +12,7 @@ impl Default for SineParabolic {
 
 impl SineParabolic {
   pub fn new() -> Self {
-    Self::default()
+    Self
   }
 }
 
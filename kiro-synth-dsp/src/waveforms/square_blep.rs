@@ -0,0 +1,130 @@
+use crate::blep::PolyBLEP;
+use crate::float::Float;
+use crate::oscillators::clamp_modulo;
+use crate::waveforms::Waveform;
+
+/// Pulse wave with its edges band limited using PolyBLEP
+#[derive(Debug, Clone)]
+pub struct SquareBlep<F: Float> {
+  /// pulse width between (0.0, 1.0)
+  pulse_width: F,
+}
+
+impl<F: Float> Default for SquareBlep<F> {
+  fn default() -> Self {
+    SquareBlep {
+      pulse_width: F::val(0.5),
+    }
+  }
+}
+
+impl<F: Float> SquareBlep<F> {
+  const MIN_PULSE_WIDTH: f32 = 0.01;
+  const MAX_PULSE_WIDTH: f32 = 0.99;
+
+  /// pulse width between (0.0, 1.0)
+  pub fn new(pulse_width: F) -> Self {
+    let mut square = SquareBlep::default();
+    square.set_pulse_width(pulse_width);
+    square
+  }
+
+  /// pulse width between (0.0, 1.0)
+  pub fn with_pulse_width(mut self, pulse_width: F) -> Self {
+    self.set_pulse_width(pulse_width);
+    self
+  }
+
+  /// pulse width between (0.0, 1.0), it is limited to keep both edges apart
+  pub fn set_pulse_width(&mut self, pulse_width: F) {
+    self.pulse_width = pulse_width
+      .max(F::val(Self::MIN_PULSE_WIDTH))
+      .min(F::val(Self::MAX_PULSE_WIDTH));
+  }
+
+  pub fn get_pulse_width(&self) -> F {
+    self.pulse_width
+  }
+
+  /// The pulse without the correction of the edges
+  pub fn naive(&self, modulo: F) -> F {
+    if modulo < self.pulse_width {
+      F::one()
+    } else {
+      F::one().neg()
+    }
+  }
+}
+
+impl<F: Float> Waveform<F> for SquareBlep<F> {
+  fn generate(&mut self, modulo: F, phase_inc: F) -> F {
+    let phase_inc = phase_inc.abs();
+    let rising = PolyBLEP::residual(modulo, phase_inc, F::one(), true);
+    let falling_modulo = clamp_modulo(modulo - self.pulse_width);
+    let falling = PolyBLEP::residual(falling_modulo, phase_inc, F::one(), false);
+    self.naive(modulo) + rising + falling
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::waveforms::square_blep::SquareBlep;
+  use crate::waveforms::square_trivial::SquareTrivial;
+  use crate::waveforms::Waveform;
+
+  const SAMPLE_RATE: f64 = 44100.0;
+
+  fn render<W: Waveform<f64>>(mut waveform: W, freq: f64, len: usize) -> Vec<f64> {
+    let phase_inc = freq / SAMPLE_RATE;
+    let mut modulo = 0.0;
+    (0..len)
+      .map(|_| {
+        let value = waveform.generate(modulo, phase_inc);
+        modulo += phase_inc;
+        if modulo >= 1.0 {
+          modulo -= 1.0;
+        }
+        value
+      })
+      .collect()
+  }
+
+  /// Power of the signal at the given frequency
+  fn power(samples: &[f64], freq: f64) -> f64 {
+    let omega = 2.0 * std::f64::consts::PI * freq / SAMPLE_RATE;
+    let (re, im) = samples
+      .iter()
+      .enumerate()
+      .fold((0.0, 0.0), |(re, im), (index, value)| {
+        let phase = omega * index as f64;
+        (re + value * phase.cos(), im - value * phase.sin())
+      });
+    (re * re + im * im) / (samples.len() * samples.len()) as f64
+  }
+
+  #[test]
+  fn high_notes_alias_much_less_than_the_trivial_square() {
+    // odd harmonics of 3 kHz above Nyquist fold back to these frequencies
+    let freq = 3000.0;
+    let aliases = [17100.0, 11100.0, 5100.0, 900.0];
+    let len = 44100;
+
+    let trivial = render(SquareTrivial::default(), freq, len);
+    let blep = render(SquareBlep::default(), freq, len);
+
+    let trivial_aliasing: f64 = aliases.iter().map(|alias| power(&trivial, *alias)).sum();
+    let blep_aliasing: f64 = aliases.iter().map(|alias| power(&blep, *alias)).sum();
+    assert!(blep_aliasing * 10.0 < trivial_aliasing);
+
+    // while the fundamental keeps most of its level
+    assert!(power(&blep, freq) > 0.8 * power(&trivial, freq));
+  }
+
+  #[test]
+  fn pulse_width_sets_the_duty_cycle() {
+    // ten whole cycles of a pulse with a duty cycle of 25% average to -0.5
+    let output = render(SquareBlep::new(0.25), 100.0, 4410);
+    let mean = output.iter().sum::<f64>() / output.len() as f64;
+    assert!((mean + 0.5).abs() < 1e-3);
+  }
+}
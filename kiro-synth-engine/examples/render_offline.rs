@@ -0,0 +1,112 @@
+//! Render two seconds of a simple patch into a WAV file
+//!
+//! cargo run --example render_offline -- output.wav
+
+use std::fs::File;
+use std::io::BufWriter;
+
+use kiro_synth_engine::event::{Event, Message};
+use kiro_synth_engine::globals::SynthGlobals;
+use kiro_synth_engine::offline::{write_wav, OfflineRenderer};
+use kiro_synth_engine::program::blocks::{envgen, osc};
+use kiro_synth_engine::program::{Block, ProgramBuilder};
+
+const SAMPLE_RATE: u32 = 44100;
+
+fn main() -> std::io::Result<()> {
+  let path = std::env::args()
+    .nth(1)
+    .unwrap_or_else(|| "render.wav".to_string());
+
+  let mut program = ProgramBuilder::new();
+  let voice = program.voice().clone();
+  let zero = program.const_zero();
+  let one = program.const_one();
+  let attack = program.const_value(0.05);
+  let decay = program.const_value(0.3);
+  let sustain = program.const_value(0.5);
+  let release = program.const_value(0.5);
+  let saw = program.const_value(2.0);
+
+  let envelope = program.signal();
+  let envelope_biased = program.signal();
+  program.block(Block::EG(envgen::Block {
+    inputs: envgen::Inputs {
+      attack,
+      decay,
+      sustain,
+      release,
+      mode: zero,
+      legato: zero,
+      reset_to_zero: zero,
+    },
+    outputs: envgen::Outputs {
+      normal: envelope,
+      biased: envelope_biased,
+      voice_off: voice.off,
+    },
+  }));
+
+  let oscillator = program.signal();
+  let oscillator_sync = program.signal();
+  program.block(Block::Osc(osc::Block {
+    inputs: osc::Inputs {
+      shape: saw,
+      amplitude: one,
+      amp_mod: zero,
+      octaves: zero,
+      semitones: zero,
+      cents: zero,
+      note_pitch: voice.note_pitch,
+      pitch_bend: zero,
+      freq_mod: zero,
+      pulse_width: zero,
+      sync: zero,
+    },
+    output: oscillator,
+    sync_output: oscillator_sync,
+  }));
+
+  let expr = program.expr(|expr| expr.mul_signals(oscillator, envelope));
+  let output = expr.output;
+  program.block(Block::Expr(expr));
+  program.out(output, output);
+
+  let events = [
+    Event::new(
+      0,
+      Message::NoteOn {
+        key: 48,
+        velocity: 0.8,
+      },
+    ),
+    Event::new(
+      22050,
+      Message::NoteOn {
+        key: 55,
+        velocity: 0.8,
+      },
+    ),
+    Event::new(
+      44100,
+      Message::NoteOff {
+        key: 48,
+        velocity: 0.0,
+      },
+    ),
+    Event::new(
+      44100,
+      Message::NoteOff {
+        key: 55,
+        velocity: 0.0,
+      },
+    ),
+  ];
+
+  let mut renderer = OfflineRenderer::new(SAMPLE_RATE as f32, program.build(), SynthGlobals::new());
+  let samples = renderer.render(&events, 2 * SAMPLE_RATE as usize);
+
+  write_wav(BufWriter::new(File::create(&path)?), SAMPLE_RATE, &samples)?;
+  println!("Rendered {} frames into {}", samples.len() / 2, path);
+  Ok(())
+}
@@ -0,0 +1,1555 @@
+use alloc::boxed::Box;
+
+use heapless::consts;
+use heapless::Vec;
+use typenum::marker_traits::Unsigned;
+
+use crate::float::Float;
+use crate::globals::SynthGlobals;
+use crate::program::{BlockRef, Program, SignalRef};
+use crate::tuning::{EqualTemperament, Tuning};
+use crate::velocity::VelocityCurve;
+use crate::voice::Voice;
+
+/// Number of frames rendered by every voice at once in process_block
+const BLOCK_FRAMES: usize = 64;
+
+/// How to choose the voice to steal when all of them are busy
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StealPolicy {
+  /// Steal the voice that started sounding first
+  #[default]
+  Oldest,
+  /// Steal the voice with the lowest output level, preferring the ones already released
+  Quietest,
+  /// Take the free voice that has been unused for longer, so repeated notes cycle through all
+  /// the voices and their differences (as the random seeds), and steal the oldest when all are busy
+  RoundRobin,
+}
+
+/// Which of the held keys sounds in monophonic mode
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NotePriority {
+  /// The last key pressed
+  #[default]
+  Last,
+  Highest,
+  Lowest,
+}
+
+impl NotePriority {
+  /// Selects the key to sound from the held keys, ordered from the first pressed to the last
+  fn select<F: Float>(self, held_keys: &[(u8, F)]) -> Option<(u8, F)> {
+    let held_keys = held_keys.iter().cloned();
+    match self {
+      NotePriority::Last => held_keys.last(),
+      NotePriority::Highest => held_keys.max_by_key(|(key, _)| *key),
+      NotePriority::Lowest => held_keys.min_by_key(|(key, _)| *key),
+    }
+  }
+}
+
+/// How the mix of the voices is scaled to keep its level as more of them sound
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Normalization {
+  /// Plain sum of the voices
+  #[default]
+  None,
+  /// Divide by the square root of the number of voices, keeping the level of uncorrelated ones
+  InverseSqrt,
+  /// Saturate the sum smoothly into [-1.0, 1.0]
+  SoftClip,
+}
+
+/// State of a voice that is sounding
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoiceInfo {
+  /// Which of the voices it is, it selects its random sequences
+  pub index: usize,
+  pub key: u8,
+  /// Whether the key is still held, otherwise it is releasing
+  pub gate: bool,
+  /// Whether it ended and will be freed by the next process
+  pub off: bool,
+}
+
+pub type MaxVoices = consts::U32;
+
+type MaxHeldKeys = consts::U128;
+
+/// When to glide the pitch from the previous note to the new one
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GlideMode {
+  #[default]
+  Off,
+  /// Glide only when there is another note held
+  Legato,
+  /// Glide on every note
+  Always,
+}
+
+pub struct VoiceAllocator<F: Float> {
+  sample_rate: F,
+  /// Kept in the heap as the voices are too big for the stack of most threads
+  voices: Box<[Voice<F>]>,
+  levels: Vec<F, MaxVoices>,
+  /// Stereo position of every voice, from -1 (left) to 1 (right)
+  pans: Vec<F, MaxVoices>,
+  /// Indices of the voices sounding, from the oldest to the newest
+  active_voices: Vec<usize, MaxVoices>,
+  free_voices: Vec<usize, MaxVoices>,
+  /// Voices whose key was released while the sustain pedal was down
+  sustained: Vec<bool, MaxVoices>,
+  sustain: bool,
+  steal_policy: StealPolicy,
+  /// Maximum number of voices sounding at once, up to MaxVoices
+  max_polyphony: usize,
+  normalization: Normalization,
+  glide_mode: GlideMode,
+  glide_time: F,
+  last_pitch: Option<F>,
+  /// Pitch bend between -1 and 1
+  pitch_bend: F,
+  /// Semitones for a full pitch bend
+  pitch_bend_range: F,
+  velocity_curve: VelocityCurve<F>,
+  legato: bool,
+  /// Note priority when playing monophonically
+  mono: Option<NotePriority>,
+  /// Keys held with their velocity, from the first pressed to the last
+  held_keys: Vec<(u8, F), MaxHeldKeys>,
+  unison_voices: usize,
+  unison_detune: F,
+  unison_spread: F,
+  tuning: Box<dyn Tuning<F> + Send>,
+}
+
+impl<F: Float> VoiceAllocator<F> {
+  /// Per sample decay of the level tracked for every voice
+  const LEVEL_DECAY: f64 = 0.999;
+
+  /// Semitones for a full pitch bend by default
+  const DEFAULT_PITCH_BEND_RANGE: f64 = 2.0;
+
+  pub fn new(sample_rate: F, program: &Program<F>) -> Self {
+    let mut voices = alloc::vec::Vec::with_capacity(MaxVoices::to_usize());
+    let mut levels: Vec<F, MaxVoices> = Vec::new();
+    let mut pans: Vec<F, MaxVoices> = Vec::new();
+    let mut free_voices: Vec<usize, MaxVoices> = Vec::new();
+    let mut sustained: Vec<bool, MaxVoices> = Vec::new();
+    for index in 0..MaxVoices::to_usize() {
+      let mut voice = Voice::new(sample_rate, program);
+      voice.set_index(index);
+      voices.push(voice);
+      levels.push(F::zero()).unwrap();
+      pans.push(F::zero()).unwrap();
+      sustained.push(false).unwrap();
+      free_voices.push(MaxVoices::to_usize() - index - 1).unwrap();
+    }
+
+    VoiceAllocator {
+      sample_rate,
+      voices: voices.into_boxed_slice(),
+      levels,
+      pans,
+      active_voices: Vec::new(),
+      free_voices,
+      sustained,
+      sustain: false,
+      steal_policy: StealPolicy::default(),
+      max_polyphony: MaxVoices::to_usize(),
+      normalization: Normalization::default(),
+      glide_mode: GlideMode::default(),
+      glide_time: F::zero(),
+      last_pitch: None,
+      pitch_bend: F::zero(),
+      pitch_bend_range: F::val(Self::DEFAULT_PITCH_BEND_RANGE),
+      velocity_curve: VelocityCurve::default(),
+      legato: false,
+      mono: None,
+      held_keys: Vec::new(),
+      unison_voices: 1,
+      unison_detune: F::zero(),
+      unison_spread: F::zero(),
+      tuning: Box::new(EqualTemperament),
+    }
+  }
+
+  pub fn with_steal_policy(self, steal_policy: StealPolicy) -> Self {
+    VoiceAllocator {
+      steal_policy,
+      ..self
+    }
+  }
+
+  pub fn set_steal_policy(&mut self, steal_policy: StealPolicy) {
+    self.steal_policy = steal_policy;
+  }
+
+  pub fn get_steal_policy(&self) -> StealPolicy {
+    self.steal_policy
+  }
+
+  /// Limit the number of voices sounding at once between 1 and MaxVoices,
+  /// stealing the ones above the new limit
+  pub fn set_max_polyphony(&mut self, program: &Program<F>, max_polyphony: usize) {
+    self.max_polyphony = max_polyphony.max(1).min(MaxVoices::to_usize());
+    while self.active_voices.len() > self.max_polyphony {
+      let position = self.voice_to_steal(program);
+      let voice_index = self.remove_active_voice(position);
+      self.free_voice(program, voice_index);
+    }
+  }
+
+  pub fn get_max_polyphony(&self) -> usize {
+    self.max_polyphony
+  }
+
+  pub fn set_normalization(&mut self, normalization: Normalization) {
+    self.normalization = normalization;
+  }
+
+  pub fn get_normalization(&self) -> Normalization {
+    self.normalization
+  }
+
+  pub fn set_velocity_curve(&mut self, velocity_curve: VelocityCurve<F>) {
+    self.velocity_curve = velocity_curve;
+  }
+
+  pub fn get_velocity_curve(&self) -> &VelocityCurve<F> {
+    &self.velocity_curve
+  }
+
+  /// In legato mode a note played while another is held takes over its voices
+  /// changing the pitch but without retriggering them
+  pub fn set_legato(&mut self, legato: bool) {
+    self.legato = legato;
+  }
+
+  pub fn get_legato(&self) -> bool {
+    self.legato
+  }
+
+  /// Play monophonically choosing the key to sound with the given priority,
+  /// or polyphonically when there is none
+  pub fn set_mono(&mut self, note_priority: Option<NotePriority>) {
+    self.mono = note_priority;
+    self.held_keys.clear();
+  }
+
+  pub fn get_mono(&self) -> Option<NotePriority> {
+    self.mono
+  }
+
+  /// Play every note with the given number of voices, with their pitch spread symmetrically
+  /// up to `detune_cents` and their stereo position spread up to `spread` (from 0 to 1).
+  /// The notes never take more voices than the max polyphony.
+  pub fn set_unison(&mut self, voices: usize, detune_cents: F, spread: F) {
+    self.unison_voices = voices.max(1).min(MaxVoices::to_usize());
+    self.unison_detune = detune_cents;
+    self.unison_spread = spread.max(F::zero()).min(F::one());
+  }
+
+  pub fn get_unison_voices(&self) -> usize {
+    self.unison_voices
+  }
+
+  /// Set the tuning used to get the pitch for the keys of the new notes
+  pub fn set_tuning<T: Tuning<F> + Send + 'static>(&mut self, tuning: T) {
+    self.tuning = Box::new(tuning);
+  }
+
+  /// Set the glide mode and the time in seconds to reach the pitch of a new note
+  pub fn set_glide(&mut self, glide_mode: GlideMode, glide_time: F) {
+    self.glide_mode = glide_mode;
+    self.glide_time = glide_time.max(F::zero());
+  }
+
+  pub fn get_glide_mode(&self) -> GlideMode {
+    self.glide_mode
+  }
+
+  pub fn get_glide_time(&self) -> F {
+    self.glide_time
+  }
+
+  /// Bend the pitch of all the voices, from -1 to 1 for the full bend range
+  pub fn set_pitch_bend(&mut self, program: &Program<F>, pitch_bend: F) {
+    self.pitch_bend = pitch_bend.max(-F::one()).min(F::one());
+    self.update_pitch_bend(program);
+  }
+
+  pub fn get_pitch_bend(&self) -> F {
+    self.pitch_bend
+  }
+
+  /// Set the number of semitones for a full pitch bend
+  pub fn set_pitch_bend_range(&mut self, program: &Program<F>, semitones: F) {
+    self.pitch_bend_range = semitones.abs();
+    self.update_pitch_bend(program);
+  }
+
+  pub fn get_pitch_bend_range(&self) -> F {
+    self.pitch_bend_range
+  }
+
+  fn update_pitch_bend(&mut self, program: &Program<F>) {
+    let semitones = self.pitch_bend * self.pitch_bend_range;
+    for voice in self.voices.iter_mut() {
+      voice.set_pitch_bend(program, semitones);
+    }
+  }
+
+  pub fn get_last_voice(&self) -> Option<&Voice<F>> {
+    self.active_voices.last().map(|index| &self.voices[*index])
+  }
+
+  /// State of the voices sounding, including the ones releasing, from the oldest to the newest
+  pub fn active_voices(&self, program: &Program<F>) -> Vec<VoiceInfo, MaxVoices> {
+    let mut voices = Vec::new();
+    for index in self.active_voices.iter() {
+      let voice = &self.voices[*index];
+      let info = VoiceInfo {
+        index: *index,
+        key: voice.get_key(program),
+        gate: voice.is_gate_on(program),
+        off: voice.is_off(program),
+      };
+      voices.push(info).unwrap();
+    }
+    voices
+  }
+
+  /// Reset the state of the processor of the block in all the voices
+  pub fn reset_block(&mut self, program: &Program<F>, block_ref: BlockRef) {
+    for voice in self.voices.iter_mut() {
+      voice.reset_block(program, block_ref);
+    }
+  }
+
+  /// Move the samples recorded by the probe on the signal of the last voice into the buffer
+  pub fn drain_probe(&mut self, signal: SignalRef, buffer: &mut [F]) -> usize {
+    match self.active_voices.last() {
+      Some(index) => self.voices[*index].drain_probe(signal, buffer),
+      None => 0,
+    }
+  }
+
+  pub fn get_num_active_voices(&self) -> usize {
+    self.active_voices.len()
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.sample_rate = sample_rate;
+    for voice in self.voices.iter_mut() {
+      voice.set_sample_rate(sample_rate);
+    }
+  }
+
+  pub fn note_on(&mut self, program: &Program<F>, key: u8, velocity: F) {
+    match self.mono {
+      None => self.poly_note_on(program, key, velocity),
+      Some(note_priority) => {
+        self.release_held_key(key);
+        self.held_keys.push((key, velocity)).ok();
+        if let Some((key, velocity)) = note_priority.select(self.held_keys.as_ref()) {
+          self.mono_note_on(program, key, velocity);
+        }
+      }
+    }
+  }
+
+  fn poly_note_on(&mut self, program: &Program<F>, key: u8, velocity: F) {
+    let glide_from = self.glide_from(program);
+
+    if self.legato && self.legato_note_on(program, key, glide_from) {
+      return;
+    }
+
+    self.start_note(program, key, velocity, glide_from, None);
+  }
+
+  /// Makes the single note sounding to play the key, reusing its voices
+  fn mono_note_on(&mut self, program: &Program<F>, key: u8, velocity: F) {
+    let sounding_key = self
+      .get_last_voice()
+      .filter(|voice| voice.is_gate_on(program))
+      .map(|voice| voice.get_key(program));
+
+    if sounding_key == Some(key) {
+      return;
+    }
+
+    let glide_from = self.glide_from(program);
+    if self.legato && self.legato_note_on(program, key, glide_from) {
+      return;
+    }
+
+    let reuse_key = self.get_last_voice().map(|voice| voice.get_key(program));
+    self.start_note(program, key, velocity, glide_from, reuse_key);
+  }
+
+  fn release_held_key(&mut self, key: u8) {
+    if let Some(position) = self.held_keys.iter().position(|(held, _)| *held == key) {
+      self.held_keys[position..].rotate_left(1);
+      self.held_keys.pop();
+    }
+  }
+
+  /// Starts a new note taking the voices sustained for the same key,
+  /// the ones playing `reuse_key` or allocating new ones otherwise.
+  fn start_note(
+    &mut self,
+    program: &Program<F>,
+    key: u8,
+    velocity: F,
+    glide_from: Option<F>,
+    reuse_key: Option<u8>,
+  ) {
+    // All the voices are taken before activating any of them so they don't steal each other
+    let unison_voices = self.unison_voices.min(self.max_polyphony);
+    let mut indices: Vec<usize, MaxVoices> = Vec::new();
+    for _ in 0..unison_voices {
+      let reused = reuse_key.and_then(|reuse_key| self.active_voice_playing(program, reuse_key));
+      let index = match self.sustained_voice(program, key).or(reused) {
+        Some(position) => self.remove_active_voice(position),
+        None => self.allocate_voice(program, indices.len()),
+      };
+      indices.push(index).unwrap();
+    }
+
+    let pitch = self.tuning.frequency(key);
+    let velocity = self.velocity_curve.map(velocity);
+    let glide_samples = (self.glide_time * self.sample_rate).round();
+    let glide_samples = glide_samples.to_usize().unwrap_or(0);
+
+    for (unison_index, index) in indices.into_iter().enumerate() {
+      self.active_voices.push(index).unwrap();
+      self.levels[index] = F::zero();
+      self.sustained[index] = false;
+      let unison_offset = Self::unison_offset(unison_index, unison_voices);
+      self.pans[index] = unison_offset * self.unison_spread;
+
+      let voice = &mut self.voices[index];
+      voice.note_on(program, key, pitch, velocity);
+      voice.set_detune(program, unison_offset * self.unison_detune);
+      if let Some(from_pitch) = glide_from {
+        voice.glide_from(program, from_pitch, glide_samples);
+      }
+    }
+    self.last_pitch = Some(pitch);
+  }
+
+  /// Moves the voices of the last note to the new key if it is still held
+  fn legato_note_on(&mut self, program: &Program<F>, key: u8, glide_from: Option<F>) -> bool {
+    let last_key = match self.get_last_voice() {
+      Some(voice) if voice.is_gate_on(program) => voice.get_key(program),
+      _ => return false,
+    };
+
+    let pitch = self.tuning.frequency(key);
+    let glide_samples = (self.glide_time * self.sample_rate).round();
+    let glide_samples = glide_samples.to_usize().unwrap_or(0);
+
+    for active_voice_index in 0..self.active_voices.len() {
+      let index = self.active_voices[active_voice_index];
+      let voice = &mut self.voices[index];
+      if voice.is_gate_on(program) && voice.get_key(program) == last_key {
+        self.sustained[index] = false;
+        voice.legato_note_on(program, key, pitch);
+        if let Some(from_pitch) = glide_from {
+          voice.glide_from(program, from_pitch, glide_samples);
+        }
+      }
+    }
+    self.last_pitch = Some(pitch);
+    true
+  }
+
+  /// Position of a unison voice between -1 and 1
+  fn unison_offset(unison_index: usize, unison_voices: usize) -> F {
+    if unison_voices > 1 {
+      F::val(2 * unison_index) / F::val(unison_voices - 1) - F::one()
+    } else {
+      F::zero()
+    }
+  }
+
+  /// Returns the pitch to glide from when a new note starts, if it has to glide
+  fn glide_from(&self, program: &Program<F>) -> Option<F> {
+    let note_held = self
+      .active_voices
+      .iter()
+      .any(|index| self.voices[*index].is_gate_on(program));
+
+    let current_pitch = self
+      .get_last_voice()
+      .map(|voice| voice.get_key_pitch())
+      .or(self.last_pitch);
+
+    match self.glide_mode {
+      GlideMode::Off => None,
+      GlideMode::Legato if !note_held => None,
+      GlideMode::Legato | GlideMode::Always => current_pitch,
+    }
+  }
+
+  /// Set the channel pressure for all the voices
+  pub fn set_aftertouch(&mut self, program: &Program<F>, aftertouch: F) {
+    for voice in self.voices.iter_mut() {
+      voice.set_aftertouch(program, aftertouch);
+    }
+  }
+
+  /// Set the pressure for the voices playing the given key
+  pub fn set_poly_pressure(&mut self, program: &Program<F>, key: u8, pressure: F) {
+    for active_voice_index in 0..self.active_voices.len() {
+      let voice = &mut self.voices[self.active_voices[active_voice_index]];
+      if voice.get_key(program) == key {
+        voice.set_poly_pressure(program, pressure);
+      }
+    }
+  }
+
+  pub fn note_off(&mut self, program: &Program<F>, key: u8) {
+    if let Some(note_priority) = self.mono {
+      self.release_held_key(key);
+      let sounding_key = self.get_last_voice().map(|voice| voice.get_key(program));
+      if sounding_key == Some(key) {
+        if let Some((key, velocity)) = note_priority.select(self.held_keys.as_ref()) {
+          self.mono_note_on(program, key, velocity);
+          return;
+        }
+      }
+    }
+
+    for active_voice_index in 0..self.active_voices.len() {
+      let voice_index = self.active_voices[active_voice_index];
+      let voice = &mut self.voices[voice_index];
+      if voice.get_key(program) == key {
+        if self.sustain {
+          self.sustained[voice_index] = true;
+        } else {
+          voice.note_off(program)
+        }
+      }
+    }
+  }
+
+  /// While the sustain is on the released keys keep sounding until it goes off
+  pub fn set_sustain(&mut self, program: &Program<F>, sustain: bool) {
+    self.sustain = sustain;
+    if !sustain {
+      for active_voice_index in 0..self.active_voices.len() {
+        let voice_index = self.active_voices[active_voice_index];
+        if self.sustained[voice_index] {
+          self.sustained[voice_index] = false;
+          self.voices[voice_index].note_off(program);
+        }
+      }
+    }
+  }
+
+  pub fn get_sustain(&self) -> bool {
+    self.sustain
+  }
+
+  /// Release all the active voices, including the ones held by the sustain
+  pub fn all_notes_off(&mut self, program: &Program<F>) {
+    self.held_keys.clear();
+    for active_voice_index in 0..self.active_voices.len() {
+      let voice_index = self.active_voices[active_voice_index];
+      self.sustained[voice_index] = false;
+      self.voices[voice_index].note_off(program);
+    }
+  }
+
+  /// Silence all the voices immediately without going through their release
+  pub fn panic(&mut self, program: &Program<F>) {
+    self.held_keys.clear();
+    self.sustain = false;
+    self.last_pitch = None;
+    while let Some(voice_index) = self.active_voices.pop() {
+      self.free_voice(program, voice_index);
+    }
+  }
+
+  /// Silence a voice already removed from the active ones and make it available again
+  fn free_voice(&mut self, program: &Program<F>, voice_index: usize) {
+    self.voices[voice_index].silence(program);
+    self.sustained[voice_index] = false;
+    self.levels[voice_index] = F::zero();
+    self.free_voices.push(voice_index).unwrap();
+  }
+
+  /// Returns the position in the active voices of one playing the key
+  fn active_voice_playing(&self, program: &Program<F>, key: u8) -> Option<usize> {
+    self
+      .active_voices
+      .iter()
+      .position(|index| self.voices[*index].get_key(program) == key)
+  }
+
+  /// Returns the position in the active voices of the sustained one playing the key
+  fn sustained_voice(&self, program: &Program<F>, key: u8) -> Option<usize> {
+    self
+      .active_voices
+      .iter()
+      .position(|index| self.sustained[*index] && self.voices[*index].get_key(program) == key)
+  }
+
+  pub fn process(&mut self, program: &mut Program<F>, globals: &SynthGlobals<F>) -> (F, F) {
+    let (mut left, mut right) = (F::zero(), F::zero());
+
+    let level_decay = F::val(Self::LEVEL_DECAY);
+    let voices = self.active_voices.len();
+    let mut active_voice_index = 0;
+    while active_voice_index < self.active_voices.len() {
+      let voice_index = self.active_voices[active_voice_index];
+      let voice = &mut self.voices[voice_index];
+
+      voice.process(program, globals);
+      let (voice_left, voice_right) = voice.output(program);
+      let pan = self.pans[voice_index];
+      left = left + voice_left * (F::one() - pan).min(F::one());
+      right = right + voice_right * (F::one() + pan).min(F::one());
+
+      let level = voice_left.abs().max(voice_right.abs());
+      self.levels[voice_index] = level.max(self.levels[voice_index] * level_decay);
+
+      if voice.is_off(program) {
+        self.remove_active_voice(active_voice_index);
+        self.free_voices.push(voice_index).unwrap();
+      } else {
+        active_voice_index += 1;
+      }
+    }
+
+    (self.normalize(voices, left), self.normalize(voices, right))
+  }
+
+  /// Process a block of frames adding the output of every voice into the slices.
+  /// The voices still run their processors frame by frame, it only saves the per frame
+  /// bookkeeping of the allocator (panning, levels and freeing voices) that is done per chunk.
+  pub fn process_block(
+    &mut self,
+    program: &mut Program<F>,
+    globals: &SynthGlobals<F>,
+    left: &mut [F],
+    right: &mut [F],
+  ) {
+    let frames = left.len().min(right.len());
+    for sample in left.iter_mut().chain(right.iter_mut()) {
+      *sample = F::zero();
+    }
+
+    let mut voice_left = [F::zero(); BLOCK_FRAMES];
+    let mut voice_right = [F::zero(); BLOCK_FRAMES];
+    let mut offset = 0;
+    while offset < frames {
+      let chunk = (frames - offset).min(BLOCK_FRAMES);
+      let level_decay = F::val(Self::LEVEL_DECAY).powi(chunk as i32);
+
+      let voices = self.active_voices.len();
+      let mut active_voice_index = 0;
+      while active_voice_index < self.active_voices.len() {
+        let voice_index = self.active_voices[active_voice_index];
+        let voice = &mut self.voices[voice_index];
+
+        voice.process_block(program, globals, chunk, &mut voice_left, &mut voice_right);
+        let pan = self.pans[voice_index];
+        let left_gain = (F::one() - pan).min(F::one());
+        let right_gain = (F::one() + pan).min(F::one());
+        let mut level = F::zero();
+        for frame in 0..chunk {
+          left[offset + frame] = left[offset + frame] + voice_left[frame] * left_gain;
+          right[offset + frame] = right[offset + frame] + voice_right[frame] * right_gain;
+          level = level.max(voice_left[frame].abs().max(voice_right[frame].abs()));
+        }
+        self.levels[voice_index] = level.max(self.levels[voice_index] * level_decay);
+
+        if voice.is_off(program) {
+          self.remove_active_voice(active_voice_index);
+          self.free_voices.push(voice_index).unwrap();
+        } else {
+          active_voice_index += 1;
+        }
+      }
+
+      if self.normalization != Normalization::None {
+        for frame in offset..offset + chunk {
+          left[frame] = self.normalize(voices, left[frame]);
+          right[frame] = self.normalize(voices, right[frame]);
+        }
+      }
+
+      offset += chunk;
+    }
+  }
+
+  /// Scale the mix of the given number of voices
+  fn normalize(&self, voices: usize, value: F) -> F {
+    match self.normalization {
+      Normalization::None => value,
+      Normalization::InverseSqrt => value / F::val(voices.max(1)).sqrt(),
+      Normalization::SoftClip => value.tanh(),
+    }
+  }
+
+  /// Takes a free voice or steals an active one when the polyphony limit is reached,
+  /// `pending` are the voices already taken for the note being started
+  fn allocate_voice(&mut self, program: &Program<F>, pending: usize) -> usize {
+    let limited = self.active_voices.len() + pending >= self.max_polyphony;
+    if self.free_voices.is_empty() || (limited && !self.active_voices.is_empty()) {
+      let position = self.voice_to_steal(program);
+      return self.remove_active_voice(position);
+    }
+
+    if self.steal_policy == StealPolicy::RoundRobin {
+      // the voices are freed at the end, so the first one is the least recently used
+      self.free_voices.rotate_left(1);
+    }
+    self.free_voices.pop().unwrap()
+  }
+
+  /// Removes the active voice at the given position keeping the order of the rest
+  fn remove_active_voice(&mut self, position: usize) -> usize {
+    self.active_voices[position..].rotate_left(1);
+    self.active_voices.pop().unwrap()
+  }
+
+  /// Returns the position in the active voices of the one to steal
+  fn voice_to_steal(&self, program: &Program<F>) -> usize {
+    match self.steal_policy {
+      StealPolicy::Oldest | StealPolicy::RoundRobin => 0,
+      StealPolicy::Quietest => {
+        let mut quietest = 0;
+        for position in 1..self.active_voices.len() {
+          if self.is_quieter(program, position, quietest) {
+            quietest = position;
+          }
+        }
+        quietest
+      }
+    }
+  }
+
+  fn is_quieter(&self, program: &Program<F>, position: usize, other_position: usize) -> bool {
+    let index = self.active_voices[position];
+    let other_index = self.active_voices[other_position];
+    let gate_on = self.voices[index].is_gate_on(program);
+    let other_gate_on = self.voices[other_index].is_gate_on(program);
+    if gate_on != other_gate_on {
+      !gate_on
+    } else {
+      self.levels[index] < self.levels[other_index]
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use typenum::marker_traits::Unsigned;
+
+  use crate::allocator::{
+    GlideMode, MaxVoices, Normalization, NotePriority, StealPolicy, VoiceAllocator, VoiceInfo,
+  };
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::{envgen, noise};
+  use crate::program::{Block, Program, ProgramBuilder};
+  use crate::tuning::Tuning;
+
+  fn program<'a>() -> Program<'a, f32> {
+    let mut builder = ProgramBuilder::new();
+    let voice = builder.voice().clone();
+    builder.out(voice.velocity, voice.velocity);
+    builder.build()
+  }
+
+  /// Program with an EG that turns the voice off when the release ends
+  fn envelope_program<'a>() -> Program<'a, f32> {
+    let mut builder = ProgramBuilder::<f32>::new();
+    let voice = builder.voice().clone();
+    let inputs = envgen::Inputs {
+      attack: builder.const_value(0.01),
+      decay: builder.const_value(0.01),
+      sustain: builder.const_value(0.5),
+      release: builder.const_value(0.01),
+      mode: builder.const_value(2.0),
+      legato: builder.const_zero(),
+      reset_to_zero: builder.const_zero(),
+    };
+    let outputs = envgen::Outputs {
+      normal: builder.signal(),
+      biased: builder.signal(),
+      voice_off: voice.off,
+    };
+    let output = outputs.normal;
+    builder.block(Block::EG(envgen::Block { inputs, outputs }));
+    builder.out(output, output);
+    builder.build()
+  }
+
+  #[test]
+  fn process_block_matches_process() {
+    let mut builder = ProgramBuilder::new();
+    let kind = builder.const_one();
+    let noise = builder.signal();
+    builder.block(Block::Noise(noise::Block {
+      inputs: noise::Inputs { kind },
+      output: noise,
+    }));
+    builder.out(noise, noise);
+    let mut program: Program<f32> = builder.build();
+    let globals = SynthGlobals::new();
+
+    let mut frames = VoiceAllocator::new(44100.0, &program);
+    let mut blocks = VoiceAllocator::new(44100.0, &program);
+    for allocator in [&mut frames, &mut blocks].iter_mut() {
+      allocator.set_unison(3, 10.0, 1.0);
+      allocator.note_on(&program, 60, 1.0);
+    }
+
+    let expected: Vec<(f32, f32)> = (0..300)
+      .map(|_| frames.process(&mut program, &globals))
+      .collect();
+    let mut left = vec![0.0; 300];
+    let mut right = vec![0.0; 300];
+    blocks.process_block(&mut program, &globals, &mut left, &mut right);
+
+    assert!(expected.iter().any(|(left, _)| left.abs() > 1e-3));
+    for (index, (left_value, right_value)) in expected.iter().enumerate() {
+      assert!((left[index] - left_value).abs() < 1e-6);
+      assert!((right[index] - right_value).abs() < 1e-6);
+    }
+  }
+
+  /// RMS of the left channel for some notes of noise
+  fn noise_rms(notes: u8, normalization: Normalization) -> f32 {
+    let mut builder = ProgramBuilder::new();
+    let kind = builder.const_zero();
+    let noise = builder.signal();
+    builder.block(Block::Noise(noise::Block {
+      inputs: noise::Inputs { kind },
+      output: noise,
+    }));
+    builder.out(noise, noise);
+    let mut program: Program<f32> = builder.build();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    allocator.set_normalization(normalization);
+    for key in 0..notes {
+      allocator.note_on(&program, 60 + key, 1.0);
+    }
+
+    let mut left = vec![0.0; 44100];
+    let mut right = vec![0.0; 44100];
+    allocator.process_block(&mut program, &globals, &mut left, &mut right);
+    let power = left.iter().map(|value| value * value).sum::<f32>() / left.len() as f32;
+    power.sqrt()
+  }
+
+  #[test]
+  fn inverse_sqrt_normalization_keeps_the_level_of_one_voice() {
+    let one = noise_rms(1, Normalization::None);
+    assert!((noise_rms(4, Normalization::None) / one - 2.0).abs() < 0.1);
+    assert!((noise_rms(4, Normalization::InverseSqrt) / one - 1.0).abs() < 0.05);
+  }
+
+  #[test]
+  fn soft_clip_normalization_keeps_the_mix_in_range() {
+    let mut program = program();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    allocator.set_normalization(Normalization::SoftClip);
+    for key in 60..64 {
+      allocator.note_on(&program, key, 1.0);
+    }
+    let (left, right) = allocator.process(&mut program, &globals);
+    assert!((left - 4.0f32.tanh()).abs() < 1e-6);
+    assert!((right - 4.0f32.tanh()).abs() < 1e-6);
+  }
+
+  fn active_keys(allocator: &VoiceAllocator<f32>, program: &Program<f32>) -> Vec<u8> {
+    allocator
+      .active_voices
+      .iter()
+      .map(|index| allocator.voices[*index].get_key(program))
+      .collect()
+  }
+
+  #[test]
+  fn note_on_overflow_steals_oldest() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    let max_voices = MaxVoices::to_usize();
+
+    for key in 0..max_voices as u8 {
+      allocator.note_on(&program, key, 1.0);
+    }
+    assert_eq!(allocator.get_num_active_voices(), max_voices);
+
+    allocator.note_on(&program, 100, 1.0);
+    assert_eq!(allocator.get_num_active_voices(), max_voices);
+
+    let keys = active_keys(&allocator, &program);
+    assert!(!keys.contains(&0));
+    assert_eq!(keys.last(), Some(&100));
+  }
+
+  #[test]
+  fn note_on_overflow_steals_quietest() {
+    let mut program = program();
+    let globals = SynthGlobals::new();
+    let mut allocator =
+      VoiceAllocator::new(44100.0, &program).with_steal_policy(StealPolicy::Quietest);
+    let max_voices = MaxVoices::to_usize();
+
+    for key in 0..max_voices as u8 {
+      let velocity = if key == 5 { 0.1 } else { 1.0 };
+      allocator.note_on(&program, key, velocity);
+    }
+    allocator.process(&mut program, &globals);
+
+    allocator.note_on(&program, 100, 1.0);
+
+    let keys = active_keys(&allocator, &program);
+    assert!(!keys.contains(&5));
+    assert!(keys.contains(&0));
+  }
+
+  #[test]
+  fn max_polyphony_steals_before_running_out_of_voices() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    allocator.set_max_polyphony(&program, 2);
+
+    allocator.note_on(&program, 60, 1.0);
+    allocator.note_on(&program, 64, 1.0);
+    allocator.note_on(&program, 67, 1.0);
+
+    assert_eq!(allocator.get_num_active_voices(), 2);
+    assert_eq!(active_keys(&allocator, &program), vec![64, 67]);
+  }
+
+  #[test]
+  fn lowering_max_polyphony_steals_down_to_the_limit() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    for key in 60..64 {
+      allocator.note_on(&program, key, 1.0);
+    }
+
+    allocator.set_max_polyphony(&program, 1);
+
+    assert_eq!(allocator.get_num_active_voices(), 1);
+    assert_eq!(active_keys(&allocator, &program), vec![63]);
+    allocator.set_max_polyphony(&program, 0);
+    assert_eq!(allocator.get_max_polyphony(), 1);
+  }
+
+  #[test]
+  fn note_off_on_stolen_key_is_ignored() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    let max_voices = MaxVoices::to_usize();
+
+    for key in 0..max_voices as u8 {
+      allocator.note_on(&program, key, 1.0);
+    }
+    allocator.note_on(&program, 100, 1.0);
+
+    allocator.note_off(&program, 0);
+
+    let gates_on = allocator
+      .active_voices
+      .iter()
+      .filter(|index| allocator.voices[**index].is_gate_on(&program))
+      .count();
+    assert_eq!(gates_on, max_voices);
+  }
+
+  #[test]
+  fn glide_interpolates_frequency() {
+    let mut program = program();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(1000.0, &program);
+    allocator.set_glide(GlideMode::Always, 0.1);
+
+    allocator.note_on(&program, 57, 1.0);
+    allocator.process(&mut program, &globals);
+    allocator.note_off(&program, 57);
+
+    allocator.note_on(&program, 69, 1.0);
+    let pitch = |allocator: &VoiceAllocator<f32>, program: &Program<f32>| {
+      allocator.get_last_voice().unwrap().get_note_pitch(program)
+    };
+    assert!((pitch(&allocator, &program) - 220.0).abs() < 1e-3);
+
+    for _ in 0..50 {
+      allocator.process(&mut program, &globals);
+    }
+    assert!((pitch(&allocator, &program) - 330.0).abs() < 1e-2);
+
+    for _ in 0..50 {
+      allocator.process(&mut program, &globals);
+    }
+    assert!((pitch(&allocator, &program) - 440.0).abs() < 1e-3);
+  }
+
+  #[test]
+  fn pitch_bend_applies_to_held_voices() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(1000.0, &program);
+    let pitches = |allocator: &VoiceAllocator<f32>| -> Vec<f32> {
+      allocator
+        .active_voices
+        .iter()
+        .map(|index| allocator.voices[*index].get_note_pitch(&program))
+        .collect()
+    };
+
+    allocator.note_on(&program, 57, 1.0);
+    allocator.note_on(&program, 69, 1.0);
+
+    allocator.set_pitch_bend(&program, 1.0);
+    let bent = pitches(&allocator);
+    assert!((bent[0] - 246.942).abs() < 1e-2);
+    assert!((bent[1] - 493.883).abs() < 1e-2);
+
+    allocator.set_pitch_bend_range(&program, 12.0);
+    let bent = pitches(&allocator);
+    assert!((bent[0] - 440.0).abs() < 1e-2);
+    assert!((bent[1] - 880.0).abs() < 1e-2);
+
+    allocator.note_on(&program, 60, 1.0);
+    let voice = allocator.get_last_voice().unwrap();
+    assert!((voice.get_pitch_bend(&program) - 12.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn pitch_bend_combines_with_glide() {
+    let mut program = program();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(1000.0, &program);
+    allocator.set_glide(GlideMode::Always, 0.1);
+    allocator.set_pitch_bend_range(&program, 12.0);
+
+    allocator.note_on(&program, 57, 1.0);
+    allocator.note_off(&program, 57);
+    allocator.note_on(&program, 69, 1.0);
+    for _ in 0..50 {
+      allocator.process(&mut program, &globals);
+    }
+
+    allocator.set_pitch_bend(&program, -1.0);
+    let pitch = allocator.get_last_voice().unwrap().get_note_pitch(&program);
+    assert!((pitch - 165.0).abs() < 1e-2);
+
+    for _ in 0..50 {
+      allocator.process(&mut program, &globals);
+    }
+    let pitch = allocator.get_last_voice().unwrap().get_note_pitch(&program);
+    assert!((pitch - 220.0).abs() < 1e-3);
+  }
+
+  #[test]
+  fn poly_pressure_targets_only_its_key() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+
+    allocator.note_on(&program, 60, 1.0);
+    allocator.note_on(&program, 64, 1.0);
+    allocator.set_poly_pressure(&program, 64, 0.8);
+    allocator.set_aftertouch(&program, 0.5);
+
+    for index in allocator.active_voices.iter() {
+      let voice = &allocator.voices[*index];
+      let expected = if voice.get_key(&program) == 64 {
+        0.8
+      } else {
+        0.0
+      };
+      assert!((voice.get_poly_pressure(&program) - expected).abs() < 1e-6);
+      assert!((voice.get_aftertouch(&program) - 0.5).abs() < 1e-6);
+    }
+  }
+
+  #[test]
+  fn sustain_defers_note_off_until_pedal_up() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    let gates = |allocator: &VoiceAllocator<f32>| -> Vec<(u8, bool)> {
+      allocator
+        .active_voices
+        .iter()
+        .map(|index| {
+          let voice = &allocator.voices[*index];
+          (voice.get_key(&program), voice.is_gate_on(&program))
+        })
+        .collect()
+    };
+
+    allocator.note_on(&program, 60, 1.0);
+    allocator.note_on(&program, 62, 1.0);
+    allocator.note_off(&program, 62);
+    allocator.set_sustain(&program, true);
+    allocator.note_off(&program, 60);
+    allocator.note_on(&program, 64, 1.0);
+    allocator.note_on(&program, 67, 1.0);
+    allocator.note_off(&program, 64);
+    assert_eq!(
+      gates(&allocator),
+      vec![(60, true), (62, false), (64, true), (67, true)]
+    );
+
+    allocator.set_sustain(&program, false);
+    assert_eq!(
+      gates(&allocator),
+      vec![(60, false), (62, false), (64, false), (67, true)]
+    );
+  }
+
+  #[test]
+  fn sustained_key_pressed_again_reuses_its_voice() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+
+    allocator.note_on(&program, 60, 1.0);
+    allocator.note_on(&program, 64, 1.0);
+    allocator.set_sustain(&program, true);
+    allocator.note_off(&program, 60);
+    allocator.note_on(&program, 60, 0.5);
+
+    assert_eq!(allocator.get_num_active_voices(), 2);
+    assert_eq!(active_keys(&allocator, &program), vec![64, 60]);
+    let voice = allocator.get_last_voice().unwrap();
+    assert!((voice.get_velocity(&program) - 0.5).abs() < 1e-6);
+
+    allocator.set_sustain(&program, false);
+    assert!(allocator.get_last_voice().unwrap().is_gate_on(&program));
+  }
+
+  #[test]
+  fn all_notes_off_releases_the_sustained_voices() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+
+    allocator.set_sustain(&program, true);
+    allocator.note_on(&program, 60, 1.0);
+    allocator.note_on(&program, 64, 1.0);
+    allocator.note_off(&program, 60);
+    allocator.all_notes_off(&program);
+
+    assert_eq!(allocator.get_num_active_voices(), 2);
+    for index in allocator.active_voices.iter() {
+      assert!(!allocator.voices[*index].is_gate_on(&program));
+      assert!(!allocator.sustained[*index]);
+    }
+  }
+
+  #[test]
+  fn panic_silences_all_the_voices() {
+    let mut program = program();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+
+    allocator.set_sustain(&program, true);
+    for key in 60..64 {
+      allocator.note_on(&program, key, 1.0);
+    }
+    allocator.note_off(&program, 60);
+    allocator.process(&mut program, &globals);
+    let playing = allocator.active_voices.clone();
+    allocator.panic(&program);
+
+    assert_eq!(allocator.get_num_active_voices(), 0);
+    assert!(!allocator.get_sustain());
+    for index in playing.iter() {
+      assert!(allocator.voices[*index].is_off(&program));
+    }
+    assert!(allocator.sustained.iter().all(|sustained| !sustained));
+    let (left, right) = allocator.process(&mut program, &globals);
+    assert!(left.abs() < 1e-6 && right.abs() < 1e-6);
+
+    allocator.note_on(&program, 60, 1.0);
+    assert_eq!(allocator.get_num_active_voices(), 1);
+    let (left, _) = allocator.process(&mut program, &globals);
+    assert!((left - 1.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn note_on_uses_the_tuning() {
+    struct Octaves;
+    impl Tuning<f32> for Octaves {
+      fn frequency(&self, key: u8) -> f32 {
+        2.0f32.powi(key as i32)
+      }
+    }
+
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    allocator.note_on(&program, 69, 1.0);
+    let pitch = allocator.get_last_voice().unwrap().get_note_pitch(&program);
+    assert!((pitch - 440.0).abs() < 1e-3);
+
+    allocator.set_tuning(Octaves);
+    allocator.note_on(&program, 3, 1.0);
+    let pitch = allocator.get_last_voice().unwrap().get_note_pitch(&program);
+    assert!((pitch - 8.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn unison_detunes_outer_voices() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    allocator.set_unison(3, 10.0, 1.0);
+
+    allocator.note_on(&program, 69, 1.0);
+    assert_eq!(allocator.get_num_active_voices(), 3);
+
+    let pitches: Vec<f32> = allocator
+      .active_voices
+      .iter()
+      .map(|index| allocator.voices[*index].get_note_pitch(&program))
+      .collect();
+    let cents = 2.0f32.powf(10.0 / 1200.0);
+    assert!((pitches[0] - 440.0 / cents).abs() < 1e-3);
+    assert!((pitches[1] - 440.0).abs() < 1e-3);
+    assert!((pitches[2] - 440.0 * cents).abs() < 1e-3);
+    assert!((allocator.pans[allocator.active_voices[0]] + 1.0).abs() < 1e-6);
+    assert!((allocator.pans[allocator.active_voices[2]] - 1.0).abs() < 1e-6);
+
+    allocator.note_off(&program, 69);
+    assert!(allocator
+      .active_voices
+      .iter()
+      .all(|index| !allocator.voices[*index].is_gate_on(&program)));
+  }
+
+  #[test]
+  fn unison_steals_when_full() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    let max_voices = MaxVoices::to_usize();
+    allocator.set_unison(3, 10.0, 0.0);
+
+    for key in 0..(max_voices / 3) as u8 {
+      allocator.note_on(&program, key, 1.0);
+    }
+    allocator.note_on(&program, 100, 1.0);
+    assert_eq!(allocator.get_num_active_voices(), max_voices);
+
+    let keys = active_keys(&allocator, &program);
+    assert_eq!(&keys[max_voices - 3..], &[100, 100, 100]);
+    let stolen = 3 - (max_voices - 3 * (max_voices / 3));
+    assert!(keys[..3 - stolen].iter().all(|key| *key == 0));
+    assert_ne!(keys[3 - stolen], 0);
+  }
+
+  #[test]
+  fn unison_is_limited_by_the_polyphony() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    allocator.set_unison(4, 10.0, 1.0);
+    allocator.set_max_polyphony(&program, 2);
+
+    allocator.note_on(&program, 60, 1.0);
+    assert_eq!(allocator.get_num_active_voices(), 2);
+    let pans: Vec<f32> = allocator
+      .active_voices
+      .iter()
+      .map(|index| allocator.pans[*index])
+      .collect();
+    assert_eq!(pans, vec![-1.0, 1.0]);
+
+    allocator.note_on(&program, 64, 1.0);
+    assert_eq!(allocator.get_num_active_voices(), 2);
+    assert_eq!(active_keys(&allocator, &program), vec![64, 64]);
+  }
+
+  #[test]
+  fn trigger_pulses_on_overlapping_notes() {
+    let mut program = program();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+
+    allocator.note_on(&program, 60, 1.0);
+    allocator.process(&mut program, &globals);
+    allocator.note_on(&program, 64, 1.0);
+
+    assert_eq!(allocator.get_num_active_voices(), 2);
+    assert!(allocator.get_last_voice().unwrap().is_trigger_on(&program));
+  }
+
+  #[test]
+  fn legato_does_not_retrigger_overlapping_notes() {
+    let mut program = program();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    allocator.set_legato(true);
+
+    allocator.note_on(&program, 60, 1.0);
+    assert!(allocator.get_last_voice().unwrap().is_trigger_on(&program));
+    allocator.process(&mut program, &globals);
+    allocator.note_on(&program, 64, 0.5);
+
+    assert_eq!(allocator.get_num_active_voices(), 1);
+    let voice = allocator.get_last_voice().unwrap();
+    assert!(!voice.is_trigger_on(&program));
+    assert_eq!(voice.get_key(&program), 64);
+    assert!((voice.get_note_pitch(&program) - 329.628).abs() < 1e-3);
+    assert!((voice.get_velocity(&program) - 1.0).abs() < 1e-6);
+
+    allocator.note_off(&program, 64);
+    allocator.note_on(&program, 67, 1.0);
+    assert_eq!(allocator.get_num_active_voices(), 2);
+    assert!(allocator.get_last_voice().unwrap().is_trigger_on(&program));
+  }
+
+  fn sounding_key(allocator: &VoiceAllocator<f32>, program: &Program<f32>) -> Option<u8> {
+    allocator
+      .get_last_voice()
+      .filter(|voice| voice.is_gate_on(program))
+      .map(|voice| voice.get_key(program))
+  }
+
+  fn mono_hold_and_release(note_priority: NotePriority) -> Vec<Option<u8>> {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    allocator.set_mono(Some(note_priority));
+
+    let mut sounding = Vec::new();
+    for key in &[64, 67, 60] {
+      allocator.note_on(&program, *key, 1.0);
+      sounding.push(sounding_key(&allocator, &program));
+    }
+    for key in &[67, 60, 64] {
+      allocator.note_off(&program, *key);
+      sounding.push(sounding_key(&allocator, &program));
+    }
+    assert_eq!(allocator.get_num_active_voices(), 1);
+    sounding
+  }
+
+  #[test]
+  fn mono_last_note_priority() {
+    assert_eq!(
+      mono_hold_and_release(NotePriority::Last),
+      vec![Some(64), Some(67), Some(60), Some(60), Some(64), None]
+    );
+
+    let program = program();
+    let mut allocator = VoiceAllocator::new(44100.0, &program);
+    allocator.set_mono(Some(NotePriority::Last));
+    allocator.note_on(&program, 60, 1.0);
+    allocator.note_on(&program, 64, 1.0);
+    allocator.note_off(&program, 64);
+    let voice = allocator.get_last_voice().unwrap();
+    assert!((voice.get_note_pitch(&program) - 261.626).abs() < 1e-3);
+  }
+
+  #[test]
+  fn mono_highest_note_priority() {
+    assert_eq!(
+      mono_hold_and_release(NotePriority::Highest),
+      vec![Some(64), Some(67), Some(67), Some(64), Some(64), None]
+    );
+  }
+
+  #[test]
+  fn mono_lowest_note_priority() {
+    assert_eq!(
+      mono_hold_and_release(NotePriority::Lowest),
+      vec![Some(64), Some(64), Some(60), Some(60), Some(64), None]
+    );
+  }
+
+  #[test]
+  fn mono_glide_retriggers_for_one_sample() {
+    let mut builder = ProgramBuilder::new();
+    let voice = builder.voice().clone();
+    builder.out(voice.trigger, voice.trigger);
+    let mut program: Program<f32> = builder.build();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(1000.0, &program);
+    allocator.set_mono(Some(NotePriority::Last));
+    allocator.set_glide(GlideMode::Always, 0.01);
+
+    fn triggered(
+      allocator: &mut VoiceAllocator<f32>,
+      program: &mut Program<f32>,
+      globals: &SynthGlobals<f32>,
+    ) -> Vec<usize> {
+      let mut left = vec![0.0; 40];
+      let mut right = vec![0.0; 40];
+      allocator.process_block(program, globals, &mut left, &mut right);
+      left
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| **value > 0.0)
+        .map(|(index, _)| index)
+        .collect()
+    }
+
+    allocator.note_on(&program, 57, 1.0);
+    assert_eq!(triggered(&mut allocator, &mut program, &globals), vec![0]);
+    allocator.note_on(&program, 69, 1.0);
+    assert_eq!(triggered(&mut allocator, &mut program, &globals), vec![0]);
+
+    allocator.set_legato(true);
+    allocator.note_on(&program, 64, 1.0);
+    assert!(triggered(&mut allocator, &mut program, &globals).is_empty());
+  }
+
+  #[test]
+  fn legato_glide_only_with_held_notes() {
+    let program = program();
+    let mut allocator = VoiceAllocator::new(1000.0, &program);
+    allocator.set_glide(GlideMode::Legato, 0.1);
+    let pitch = |allocator: &VoiceAllocator<f32>| {
+      allocator.get_last_voice().unwrap().get_note_pitch(&program)
+    };
+
+    allocator.note_on(&program, 57, 1.0);
+    allocator.note_off(&program, 57);
+    allocator.note_on(&program, 69, 1.0);
+    assert!((pitch(&allocator) - 440.0).abs() < 1e-3);
+
+    allocator.note_on(&program, 57, 1.0);
+    assert!((pitch(&allocator) - 440.0).abs() < 1e-3);
+  }
+
+  /// Output of the last voice after playing a note over another one already in its sustain
+  fn overlapping_note_envelope(legato: bool) -> Vec<f32> {
+    let mut program = envelope_program();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(1000.0, &program);
+    allocator.set_legato(legato);
+
+    allocator.note_on(&program, 60, 1.0);
+    for _ in 0..100 {
+      allocator.process(&mut program, &globals);
+    }
+    allocator.note_on(&program, 64, 1.0);
+    (0..30)
+      .map(|_| {
+        allocator.process(&mut program, &globals);
+        allocator.get_last_voice().unwrap().output(&program).0
+      })
+      .collect()
+  }
+
+  #[test]
+  fn legato_notes_do_not_restart_the_envelope() {
+    let legato = overlapping_note_envelope(true);
+    assert!(legato.iter().all(|value| (value - 0.5).abs() < 1e-3));
+
+    let retriggered = overlapping_note_envelope(false);
+    assert!(retriggered[0] < 0.5);
+    let peak = retriggered.iter().cloned().fold(0.0f32, f32::max);
+    assert!((peak - 1.0).abs() < 1e-3);
+  }
+
+  #[test]
+  fn releasing_voices_are_active_until_freed() {
+    let mut program = envelope_program();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(1000.0, &program);
+
+    allocator.note_on(&program, 60, 1.0);
+    allocator.note_on(&program, 64, 1.0);
+    for _ in 0..50 {
+      allocator.process(&mut program, &globals);
+    }
+    allocator.note_off(&program, 60);
+    allocator.process(&mut program, &globals);
+
+    let releasing = VoiceInfo {
+      index: 0,
+      key: 60,
+      gate: false,
+      off: false,
+    };
+    let held = VoiceInfo {
+      index: 1,
+      key: 64,
+      gate: true,
+      off: false,
+    };
+    assert_eq!(allocator.active_voices(&program), [releasing, held]);
+
+    for _ in 0..20 {
+      allocator.process(&mut program, &globals);
+    }
+    assert_eq!(allocator.active_voices(&program), [held]);
+  }
+
+  #[test]
+  fn round_robin_cycles_through_the_voices() {
+    let mut program = envelope_program();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(1000.0, &program);
+
+    let mut play = |allocator: &mut VoiceAllocator<f32>| {
+      allocator.note_on(&program, 60, 1.0);
+      let index = allocator.active_voices(&program).last().unwrap().index;
+      allocator.note_off(&program, 60);
+      while allocator.get_num_active_voices() > 0 {
+        allocator.process(&mut program, &globals);
+      }
+      index
+    };
+
+    // by default the voice just released is reused
+    let indices: Vec<usize> = (0..3).map(|_| play(&mut allocator)).collect();
+    assert!(indices.iter().all(|index| *index == indices[0]));
+
+    allocator.set_steal_policy(StealPolicy::RoundRobin);
+    let max_voices = MaxVoices::to_usize();
+    let mut indices: Vec<usize> = (0..max_voices).map(|_| play(&mut allocator)).collect();
+    indices.sort();
+    indices.dedup();
+    assert_eq!(indices.len(), max_voices);
+  }
+
+  #[test]
+  fn round_robin_keeps_the_order_when_stealing() {
+    let mut program = envelope_program();
+    let globals = SynthGlobals::new();
+
+    let release_all = |allocator: &mut VoiceAllocator<f32>, program: &mut Program<f32>| {
+      for key in &[60, 64] {
+        allocator.note_off(program, *key);
+      }
+      while allocator.get_num_active_voices() > 0 {
+        allocator.process(program, &globals);
+      }
+    };
+    let last_index = |allocator: &VoiceAllocator<f32>, program: &Program<f32>| {
+      allocator.active_voices(program).last().unwrap().index
+    };
+
+    let mut expected = VoiceAllocator::new(1000.0, &program);
+    expected.set_steal_policy(StealPolicy::RoundRobin);
+    let mut expected_indices = Vec::new();
+    for _ in 0..2 {
+      expected.note_on(&program, 60, 1.0);
+      expected_indices.push(last_index(&expected, &program));
+      release_all(&mut expected, &mut program);
+    }
+
+    // the second note steals the first voice and the free voice it skipped stays the next one
+    let mut allocator = VoiceAllocator::new(1000.0, &program);
+    allocator.set_steal_policy(StealPolicy::RoundRobin);
+    allocator.set_max_polyphony(&program, 1);
+    allocator.note_on(&program, 60, 1.0);
+    allocator.note_on(&program, 64, 1.0);
+    assert_eq!(last_index(&allocator, &program), expected_indices[0]);
+    release_all(&mut allocator, &mut program);
+
+    allocator.note_on(&program, 60, 1.0);
+    assert_eq!(last_index(&allocator, &program), expected_indices[1]);
+  }
+}
@@ -0,0 +1,39 @@
+use heapless::consts;
+use heapless::Vec;
+use typenum::marker_traits::Unsigned;
+
+use crate::float::Float;
+
+pub type MaxControllers = consts::U128;
+
+/// Last values received for the MIDI continuous controllers
+#[derive(Debug, Clone)]
+pub struct ControllerBus<F: Float> {
+  values: Vec<Option<F>, MaxControllers>,
+}
+
+impl<F: Float> Default for ControllerBus<F> {
+  fn default() -> Self {
+    ControllerBus::new()
+  }
+}
+
+impl<F: Float> ControllerBus<F> {
+  pub fn new() -> Self {
+    let mut values = Vec::new();
+    values.resize(MaxControllers::to_usize(), None).unwrap();
+    ControllerBus { values }
+  }
+
+  /// Set the value for a controller, ignoring the ones out of range
+  pub fn set(&mut self, cc: u8, value: F) {
+    if let Some(current) = self.values.get_mut(cc as usize) {
+      *current = Some(value);
+    }
+  }
+
+  /// The last value for a controller, if any was received
+  pub fn get(&self, cc: u8) -> Option<F> {
+    self.values.get(cc as usize).cloned().flatten()
+  }
+}
@@ -1,5 +1,11 @@
 use crate::float::Float;
-use crate::program::{ParamRef, SourceRef};
+use crate::program::{BlockRef, ParamRef, SourceRef};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+  /// The value for the param is NaN
+  InvalidParamValue(ParamRef),
+}
 
 #[derive(Debug, Clone)]
 pub enum Message<F: Float> {
@@ -11,10 +17,16 @@ pub enum Message<F: Float> {
     key: u8,
     velocity: F,
   },
+  /// Set the value of a param clamped into its range, NaN values are ignored
   ParamValue {
     param_ref: ParamRef,
     value: F,
   },
+  /// Set the value of a param without clamping it, for callers doing their own mapping
+  ParamValueUnclamped {
+    param_ref: ParamRef,
+    value: F,
+  },
   ParamChange {
     param_ref: ParamRef,
     change: F,
@@ -28,6 +40,56 @@ pub enum Message<F: Float> {
     source_ref: SourceRef,
     param_ref: ParamRef,
   },
+  ControlChange {
+    cc: u8,
+    value: F,
+  },
+  Sustain {
+    on: bool,
+  },
+  Aftertouch {
+    value: F,
+  },
+  PolyPressure {
+    key: u8,
+    value: F,
+  },
+  PitchBend {
+    amount: F,
+  },
+  Tempo {
+    bpm: F,
+  },
+  SampleRate {
+    sample_rate: F,
+  },
+  /// Clear the state of the processor of a block in all the voices, as the tail of a delay,
+  /// without affecting the rest of the program
+  ResetBlock {
+    block_ref: BlockRef,
+  },
+}
+
+impl<F: Float> Message<F> {
+  /// Message to set the value of a param clamped into its range, rejecting NaN values
+  pub fn param_value(param_ref: ParamRef, value: F) -> Result<Self, Error> {
+    Self::check_param_value(param_ref, value)?;
+    Ok(Message::ParamValue { param_ref, value })
+  }
+
+  /// Message to set the value of a param without clamping it, rejecting NaN values
+  pub fn param_value_unclamped(param_ref: ParamRef, value: F) -> Result<Self, Error> {
+    Self::check_param_value(param_ref, value)?;
+    Ok(Message::ParamValueUnclamped { param_ref, value })
+  }
+
+  fn check_param_value(param_ref: ParamRef, value: F) -> Result<(), Error> {
+    if value.is_nan() {
+      Err(Error::InvalidParamValue(param_ref))
+    } else {
+      Ok(())
+    }
+  }
 }
 
 #[derive(Debug, Clone)]
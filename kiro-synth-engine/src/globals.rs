@@ -1,11 +1,20 @@
 use kiro_synth_dsp::float::Float;
 
-use crate::waveforms::{LfoWaveforms, OscWaveforms};
+use crate::controllers::ControllerBus;
+use crate::samples::Samples;
+use crate::waveforms::{LfoWaveforms, OscWaveforms, WaveTables};
 
 #[derive(Debug, Clone, Default)]
 pub struct SynthGlobals<F: Float> {
   pub osc_waveforms: OscWaveforms<F>,
   pub lfo_waveforms: LfoWaveforms<F>,
+  pub wavetables: WaveTables<F>,
+  pub samples: Samples<F>,
+  pub controllers: ControllerBus<F>,
+  /// Tempo in beats per minute
+  pub tempo: F,
+  /// Master seed the random sequences of every voice derive from
+  pub seed: u32,
 }
 
 impl<F: Float> SynthGlobals<F> {
@@ -13,6 +22,16 @@ impl<F: Float> SynthGlobals<F> {
     SynthGlobals {
       osc_waveforms: OscWaveforms::new(),
       lfo_waveforms: LfoWaveforms::new(),
+      wavetables: WaveTables::new(),
+      samples: Samples::new(),
+      controllers: ControllerBus::new(),
+      tempo: F::val(120),
+      seed: 0,
     }
   }
+
+  /// Seed for the voice in the given position, different for every voice and master seed
+  pub fn voice_seed(&self, voice_index: usize) -> u32 {
+    self.seed.wrapping_mul(0x85eb_ca6b) ^ (voice_index as u32).wrapping_add(1)
+  }
 }
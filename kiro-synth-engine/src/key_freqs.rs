@@ -1,7 +1,6 @@
-#![allow(unused_attributes)]
-#![rustfmt::skip]
 #![allow(clippy::all)]
 
+#[rustfmt::skip]
 pub const KEY_FREQ: [f32; 128] = [
      8.176,      8.662,      9.177,      9.723,     10.301,     10.913,     11.562,     12.250,     12.978,     13.750,     14.568,     15.434,
     16.352,     17.324,     18.354,     19.445,     20.602,     21.827,     23.125,     24.500,     25.957,     27.500,     29.135,     30.868,
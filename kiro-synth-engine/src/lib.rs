@@ -1,4 +1,6 @@
-//#![no_std]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 #[macro_use]
 extern crate hash32_derive;
@@ -9,10 +11,21 @@ mod key_freqs;
 mod processor;
 mod voice;
 
+pub mod allocator;
+pub mod controllers;
 pub mod event;
 pub mod globals;
+pub mod midi;
+#[cfg(feature = "std")]
+pub mod offline;
 pub mod program;
+pub mod samples;
+#[cfg(feature = "std")]
+pub mod scala;
+#[cfg(feature = "std")]
 pub mod synth;
+pub mod tuning;
+pub mod velocity;
 pub mod waveforms;
 
 pub use kiro_synth_dsp::float;
@@ -0,0 +1,180 @@
+use crate::event::Message;
+use crate::float::Float;
+
+const NOTE_OFF: u8 = 0x80;
+const NOTE_ON: u8 = 0x90;
+const POLY_PRESSURE: u8 = 0xa0;
+const CONTROL_CHANGE: u8 = 0xb0;
+const PROGRAM_CHANGE: u8 = 0xc0;
+const CHANNEL_PRESSURE: u8 = 0xd0;
+const PITCH_BEND: u8 = 0xe0;
+/// First of the real time statuses, they can be interleaved anywhere without breaking the running status
+const TIMING_CLOCK: u8 = 0xf8;
+
+const SUSTAIN_CC: u8 = 64;
+
+/// Parses a stream of raw MIDI bytes into the messages for the synth.
+/// It supports running status and ignores the system messages.
+#[derive(Debug, Clone, Default)]
+pub struct MidiInput {
+  /// Channel to listen to (0 to 15), or all of them when none
+  channel: Option<u8>,
+  /// Status of the channel message being parsed, kept for the messages in running status
+  status: Option<u8>,
+  data: [u8; 2],
+  len: usize,
+}
+
+impl MidiInput {
+  pub fn new() -> Self {
+    MidiInput::default()
+  }
+
+  /// Listen only to one channel (0 to 15), or to all of them with none
+  pub fn set_channel(&mut self, channel: Option<u8>) {
+    self.channel = channel.map(|channel| channel & 0x0f);
+  }
+
+  pub fn get_channel(&self) -> Option<u8> {
+    self.channel
+  }
+
+  /// Parse the bytes calling `dispatch` with every complete message,
+  /// the incomplete ones are finished by the following calls
+  pub fn parse<F, D>(&mut self, bytes: &[u8], mut dispatch: D)
+  where
+    F: Float,
+    D: FnMut(Message<F>),
+  {
+    for byte in bytes.iter().cloned() {
+      if byte >= TIMING_CLOCK {
+        continue;
+      }
+
+      if byte & 0x80 != 0 {
+        // system common messages and sysex cancel the running status
+        self.status = if byte < 0xf0 { Some(byte) } else { None };
+        self.len = 0;
+        continue;
+      }
+
+      if let Some(status) = self.status {
+        self.data[self.len] = byte;
+        self.len += 1;
+        if self.len == Self::data_len(status) {
+          self.len = 0;
+          let channel = status & 0x0f;
+          if self.channel.is_none_or(|listening| listening == channel) {
+            if let Some(message) = Self::message(status, self.data) {
+              dispatch(message);
+            }
+          }
+        }
+      }
+    }
+  }
+
+  fn data_len(status: u8) -> usize {
+    match status & 0xf0 {
+      PROGRAM_CHANGE | CHANNEL_PRESSURE => 1,
+      _ => 2,
+    }
+  }
+
+  fn message<F: Float>(status: u8, data: [u8; 2]) -> Option<Message<F>> {
+    let unipolar = |value: u8| F::val(value) / F::val(127);
+    match status & 0xf0 {
+      NOTE_ON if data[1] > 0 => Some(Message::NoteOn {
+        key: data[0],
+        velocity: unipolar(data[1]),
+      }),
+      // a note on without velocity is a note off
+      NOTE_OFF | NOTE_ON => Some(Message::NoteOff {
+        key: data[0],
+        velocity: unipolar(data[1]),
+      }),
+      POLY_PRESSURE => Some(Message::PolyPressure {
+        key: data[0],
+        value: unipolar(data[1]),
+      }),
+      CONTROL_CHANGE if data[0] == SUSTAIN_CC => Some(Message::Sustain { on: data[1] >= 64 }),
+      CONTROL_CHANGE => Some(Message::ControlChange {
+        cc: data[0],
+        value: unipolar(data[1]),
+      }),
+      CHANNEL_PRESSURE => Some(Message::Aftertouch {
+        value: unipolar(data[0]),
+      }),
+      PITCH_BEND => {
+        let value = (u16::from(data[1]) << 7) | u16::from(data[0]);
+        let amount = (F::val(value) - F::val(8192)) / F::val(8192);
+        Some(Message::PitchBend {
+          amount: amount.max(F::one().neg()).min(F::one()),
+        })
+      }
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::event::Message;
+  use crate::midi::MidiInput;
+
+  fn parse(input: &mut MidiInput, bytes: &[u8]) -> Vec<Message<f32>> {
+    let mut messages = Vec::new();
+    input.parse(bytes, |message| messages.push(message));
+    messages
+  }
+
+  #[test]
+  fn running_status() {
+    let mut input = MidiInput::new();
+    let messages = parse(
+      &mut input,
+      &[0x90, 60, 127, 64, 127, 0xf8, 67, 127, 0xe0, 0, 0x40],
+    );
+    assert_eq!(messages.len(), 4);
+    let keys: Vec<u8> = messages[..3]
+      .iter()
+      .map(|message| match message {
+        Message::NoteOn { key, velocity } if (*velocity - 1.0).abs() < 1e-6 => *key,
+        _ => panic!("{:?}", message),
+      })
+      .collect();
+    assert_eq!(keys, vec![60, 64, 67]);
+    match messages[3] {
+      Message::PitchBend { amount } => assert!(amount.abs() < 1e-6),
+      _ => panic!("{:?}", messages[3]),
+    }
+
+    // the running status continues in the next call
+    let messages = parse(&mut input, &[0x7f]);
+    assert!(messages.is_empty());
+    match parse(&mut input, &[0x7f]).as_slice() {
+      [Message::PitchBend { amount }] => assert!(*amount > 0.99),
+      messages => panic!("{:?}", messages),
+    }
+  }
+
+  #[test]
+  fn note_on_without_velocity_is_a_note_off() {
+    let mut input = MidiInput::new();
+    match parse(&mut input, &[0x91, 60, 0]).as_slice() {
+      [Message::NoteOff { key: 60, .. }] => {}
+      messages => panic!("{:?}", messages),
+    }
+  }
+
+  #[test]
+  fn filters_the_channel() {
+    let mut input = MidiInput::new();
+    input.set_channel(Some(1));
+    let messages = parse(&mut input, &[0x90, 60, 100, 0x91, 62, 100, 0xb1, 64, 127]);
+    match messages.as_slice() {
+      [Message::NoteOn { key: 62, .. }, Message::Sustain { on: true }] => {}
+      messages => panic!("{:?}", messages),
+    }
+  }
+}
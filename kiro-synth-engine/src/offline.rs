@@ -0,0 +1,166 @@
+use std::io::{self, Write};
+
+use ringbuf::{Producer, RingBuffer};
+
+use crate::event::Event;
+use crate::float::Float;
+use crate::globals::SynthGlobals;
+use crate::program::Program;
+use crate::synth::Synth;
+
+/// Drives a synth without an audio device, with the timestamps of the events in frames
+pub struct OfflineRenderer<'a, F: Float> {
+  synth: Box<Synth<'a, F>>,
+  events: Producer<Event<F>>,
+  frame: u64,
+}
+
+impl<'a, F: Float> OfflineRenderer<'a, F> {
+  const EVENTS_CAPACITY: usize = 1024;
+
+  pub fn new(sample_rate: F, program: Program<'a, F>, globals: SynthGlobals<F>) -> Self {
+    let (events, consumer) = RingBuffer::new(Self::EVENTS_CAPACITY).split();
+    let synth = Box::new(Synth::new(sample_rate, consumer, program, globals));
+    OfflineRenderer {
+      synth,
+      events,
+      frame: 0,
+    }
+  }
+
+  pub fn get_synth_mut(&mut self) -> &mut Synth<'a, F> {
+    &mut self.synth
+  }
+
+  /// Number of frames rendered so far
+  pub fn get_frame(&self) -> u64 {
+    self.frame
+  }
+
+  /// Render the frames returning interleaved stereo samples.
+  /// The events, sorted by timestamp, are sent to the synth right before rendering their frame,
+  /// the ones in the past are sent before the first frame.
+  pub fn render(&mut self, events: &[Event<F>], frames: usize) -> Vec<F> {
+    let mut output = Vec::with_capacity(frames * 2);
+    let mut next_event = 0;
+    for _ in 0..frames {
+      while next_event < events.len() && events[next_event].timestamp <= self.frame {
+        while self.events.push(events[next_event].clone()).is_err() {
+          self.synth.prepare();
+        }
+        next_event += 1;
+      }
+      self.synth.prepare();
+      let (left, right) = self.synth.process();
+      output.push(left);
+      output.push(right);
+      self.frame += 1;
+    }
+    output
+  }
+}
+
+/// Write interleaved stereo samples as a 32 bits float WAV file
+pub fn write_wav<F: Float, W: Write>(
+  mut writer: W,
+  sample_rate: u32,
+  samples: &[F],
+) -> io::Result<()> {
+  const CHANNELS: u16 = 2;
+  const BYTES_PER_SAMPLE: u16 = 4;
+  const IEEE_FLOAT_FORMAT: u16 = 3;
+
+  let data_size = (samples.len() * BYTES_PER_SAMPLE as usize) as u32;
+  let block_align = CHANNELS * BYTES_PER_SAMPLE;
+
+  writer.write_all(b"RIFF")?;
+  writer.write_all(&(36 + data_size).to_le_bytes())?;
+  writer.write_all(b"WAVE")?;
+  writer.write_all(b"fmt ")?;
+  writer.write_all(&16u32.to_le_bytes())?;
+  writer.write_all(&IEEE_FLOAT_FORMAT.to_le_bytes())?;
+  writer.write_all(&CHANNELS.to_le_bytes())?;
+  writer.write_all(&sample_rate.to_le_bytes())?;
+  writer.write_all(&(sample_rate * u32::from(block_align)).to_le_bytes())?;
+  writer.write_all(&block_align.to_le_bytes())?;
+  writer.write_all(&(BYTES_PER_SAMPLE * 8).to_le_bytes())?;
+  writer.write_all(b"data")?;
+  writer.write_all(&data_size.to_le_bytes())?;
+  for sample in samples {
+    writer.write_all(&sample.to_f32().unwrap_or(0.0).to_le_bytes())?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::event::{Event, Message};
+  use crate::globals::SynthGlobals;
+  use crate::offline::{write_wav, OfflineRenderer};
+  use crate::program::blocks::noise;
+  use crate::program::{Block, Program, ProgramBuilder};
+
+  fn program<'a>() -> Program<'a, f32> {
+    let mut builder = ProgramBuilder::new();
+    let kind = builder.const_value(2.0);
+    let output = builder.signal();
+    builder.block(Block::Noise(noise::Block {
+      inputs: noise::Inputs { kind },
+      output,
+    }));
+    builder.out(output, output);
+    builder.build()
+  }
+
+  fn render(seed: u32) -> Vec<f32> {
+    let events = [
+      Event::new(
+        100,
+        Message::NoteOn {
+          key: 60,
+          velocity: 1.0,
+        },
+      ),
+      Event::new(
+        100,
+        Message::NoteOn {
+          key: 64,
+          velocity: 0.5,
+        },
+      ),
+      Event::new(300, Message::PitchBend { amount: 0.5 }),
+    ];
+    let mut globals = SynthGlobals::new();
+    globals.seed = seed;
+    let mut renderer = OfflineRenderer::new(44100.0, program(), globals);
+    renderer.render(&events, 500)
+  }
+
+  #[test]
+  fn renders_deterministically() {
+    let samples = render(0);
+    assert_eq!(samples.len(), 1000);
+    assert!(samples[..200].iter().all(|sample| sample.abs() < 1e-9));
+    assert!(samples[200..].iter().any(|sample| sample.abs() > 1e-4));
+    assert_eq!(render(0), samples);
+  }
+
+  #[test]
+  fn master_seed_selects_the_random_sequences() {
+    let samples = render(1234);
+    assert_eq!(render(1234), samples);
+    assert_ne!(render(0), samples);
+    assert_ne!(render(1235), samples);
+  }
+
+  #[test]
+  fn wav_header() {
+    let mut wav = Vec::new();
+    write_wav(&mut wav, 44100, &[0.5f32, -0.5]).unwrap();
+    assert_eq!(wav.len(), 44 + 8);
+    assert_eq!(&wav[0..4], b"RIFF");
+    assert_eq!(&wav[8..16], b"WAVEfmt ");
+    assert_eq!(&wav[36..40], b"data");
+    assert_eq!(&wav[44..48], &0.5f32.to_le_bytes());
+  }
+}
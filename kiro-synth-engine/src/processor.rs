@@ -1,20 +1,45 @@
+use alloc::boxed::Box;
+
 use crate::float::Float;
 use crate::globals::SynthGlobals;
 use crate::program::blocks::*;
-use crate::program::{Block, ParamBlock, ParamRef, Program, SignalRef};
+use heapless::Vec;
+
+use crate::program::{Block, MaxOutputChannels, ParamBlock, ParamRef, Program, SignalRef};
 use crate::signal::SignalBus;
 
 #[derive(Debug)]
 pub(crate) enum Processor<F: Float> {
   Const(F, SignalRef),
   Param(ParamRef),
+  AmpMod(amp_mod::Processor),
+  Comb(Box<comb::Processor<F>>),
+  Crush(crush::Processor<F>),
+  #[allow(clippy::upper_case_acronyms)]
   DCA(dca::Processor<F>),
+  DcBlocker(dc_blocker::Processor<F>),
+  Delay(Box<delay::Processor<F>>),
   EG(envgen::Processor<F>),
+  EnvFollower(env_follower::Processor<F>),
   Expr(expr::Processor<F>),
   Filter(filter::Processor<F>),
+  FmOperator(fm_operator::Processor<F>),
   Lfo(lfo::Processor<F>),
+  MidSide(mid_side::Processor<F>),
+  MidiCc(midi_cc::Processor<F>),
+  Noise(noise::Processor<F>),
   Osc(osc::Processor<F>),
+  Pan(pan::Processor<F>),
+  Probe(Box<probe::Processor<F>>),
+  RingMod(ring_mod::Processor),
+  SampleHold(sample_hold::Processor<F>),
+  Sampler(sampler::Processor<F>),
+  Shaper(shaper::Processor<F>),
+  Slew(slew::Processor<F>),
+  Svf(svf::Processor<F>),
+  WaveTableOsc(wavetable_osc::Processor<F>),
   Out(SignalRef, SignalRef),
+  OutChannels(Vec<SignalRef, MaxOutputChannels>),
 }
 
 impl<F: Float> Processor<F> {
@@ -26,15 +51,117 @@ impl<F: Float> Processor<F> {
         out_signal_ref: _,
         mod_signal_ref: _,
       }) => Processor::Param(reference),
+      Block::AmpMod(amp_mod_block) => {
+        Processor::AmpMod(amp_mod::Processor::new(sample_rate, amp_mod_block))
+      }
+      Block::Comb(comb_block) => {
+        Processor::Comb(Box::new(comb::Processor::new(sample_rate, comb_block)))
+      }
+      Block::Crush(crush_block) => {
+        Processor::Crush(crush::Processor::new(sample_rate, crush_block))
+      }
       Block::DCA(dca_block) => Processor::DCA(dca::Processor::new(sample_rate, dca_block)),
+      Block::DcBlocker(dc_blocker_block) => {
+        Processor::DcBlocker(dc_blocker::Processor::new(sample_rate, dc_blocker_block))
+      }
+      Block::Delay(delay_block) => {
+        Processor::Delay(Box::new(delay::Processor::new(sample_rate, delay_block)))
+      }
       Block::EG(eg_block) => Processor::EG(envgen::Processor::new(sample_rate, eg_block)),
+      Block::FmOperator(fm_operator_block) => {
+        Processor::FmOperator(fm_operator::Processor::new(sample_rate, fm_operator_block))
+      }
       Block::Lfo(lfo_block) => Processor::Lfo(lfo::Processor::new(sample_rate, lfo_block)),
+      Block::MidSide(mid_side_block) => {
+        Processor::MidSide(mid_side::Processor::new(sample_rate, mid_side_block))
+      }
+      Block::MidiCc(cc_block) => Processor::MidiCc(midi_cc::Processor::new(sample_rate, cc_block)),
+      Block::Noise(noise_block) => {
+        Processor::Noise(noise::Processor::new(sample_rate, noise_block))
+      }
       Block::Osc(osc_block) => Processor::Osc(osc::Processor::new(sample_rate, osc_block)),
+      Block::Pan(pan_block) => Processor::Pan(pan::Processor::new(sample_rate, pan_block)),
+      Block::Probe(probe_block) => {
+        Processor::Probe(Box::new(probe::Processor::new(sample_rate, probe_block)))
+      }
+      Block::RingMod(ring_mod_block) => {
+        Processor::RingMod(ring_mod::Processor::new(sample_rate, ring_mod_block))
+      }
+      Block::SampleHold(sample_hold_block) => {
+        Processor::SampleHold(sample_hold::Processor::new(sample_rate, sample_hold_block))
+      }
+      Block::Sampler(sampler_block) => {
+        Processor::Sampler(sampler::Processor::new(sample_rate, sampler_block))
+      }
+      Block::Shaper(shaper_block) => {
+        Processor::Shaper(shaper::Processor::new(sample_rate, shaper_block))
+      }
+      Block::Slew(slew_block) => Processor::Slew(slew::Processor::new(sample_rate, slew_block)),
+      Block::Svf(svf_block) => Processor::Svf(svf::Processor::new(sample_rate, svf_block)),
+      Block::WaveTableOsc(wt_block) => {
+        Processor::WaveTableOsc(wavetable_osc::Processor::new(sample_rate, wt_block))
+      }
+      Block::EnvFollower(env_follower_block) => Processor::EnvFollower(
+        env_follower::Processor::new(sample_rate, env_follower_block),
+      ),
       Block::Expr(expr_block) => Processor::Expr(expr::Processor::new(expr_block)),
       Block::Filter(filt_block) => {
         Processor::Filter(filter::Processor::new(sample_rate, filt_block))
       }
       Block::Out { left, right } => Processor::Out(left, right),
+      Block::OutChannels(channels) => Processor::OutChannels(channels),
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    match self {
+      Processor::Const(_, _) => {}
+      Processor::Param(_) => {}
+      Processor::AmpMod(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Comb(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Crush(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::DCA(_) => {}
+      Processor::DcBlocker(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Delay(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::EG(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::EnvFollower(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Expr(_) => {}
+      Processor::Filter(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::FmOperator(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Lfo(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::MidSide(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::MidiCc(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Noise(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Osc(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Pan(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Probe(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::RingMod(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::SampleHold(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Sampler(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Shaper(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Slew(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Svf(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::WaveTableOsc(ref mut proc) => proc.set_sample_rate(sample_rate),
+      Processor::Out(ref _left, ref _right) => {}
+      Processor::OutChannels(ref _channels) => {}
+    }
+  }
+
+  /// Seed for the processors generating random sequences
+  pub fn set_seed(&mut self, seed: u32) {
+    match self {
+      Processor::Lfo(ref mut proc) => proc.set_seed(seed),
+      Processor::Noise(ref mut proc) => proc.set_seed(seed),
+      _ => {}
+    }
+  }
+
+  /// Move the samples recorded by a probe on the signal into the buffer,
+  /// or None when this is not a probe for it
+  pub fn drain_probe(&mut self, signal: SignalRef, buffer: &mut [F]) -> Option<usize> {
+    match self {
+      Processor::Probe(ref mut proc) if proc.input() == signal => Some(proc.drain(buffer)),
+      _ => None,
     }
   }
 
@@ -42,13 +169,33 @@ impl<F: Float> Processor<F> {
     match self {
       Processor::Const(_, _) => {}
       Processor::Param(_) => {}
+      Processor::AmpMod(ref mut proc) => proc.reset(),
+      Processor::Comb(ref mut proc) => proc.reset(),
+      Processor::Crush(ref mut proc) => proc.reset(),
       Processor::DCA(ref mut proc) => proc.reset(),
+      Processor::DcBlocker(ref mut proc) => proc.reset(),
+      Processor::Delay(ref mut proc) => proc.reset(),
       Processor::EG(ref mut proc) => proc.reset(),
+      Processor::EnvFollower(ref mut proc) => proc.reset(),
       Processor::Expr(ref mut proc) => proc.reset(),
       Processor::Filter(ref mut proc) => proc.reset(),
+      Processor::FmOperator(ref mut proc) => proc.reset(),
       Processor::Lfo(ref mut proc) => proc.reset(),
+      Processor::MidSide(ref mut proc) => proc.reset(),
+      Processor::MidiCc(ref mut proc) => proc.reset(),
+      Processor::Noise(ref mut proc) => proc.reset(),
       Processor::Osc(ref mut proc) => proc.reset(),
+      Processor::Pan(ref mut proc) => proc.reset(),
+      Processor::Probe(ref mut proc) => proc.reset(),
+      Processor::RingMod(ref mut proc) => proc.reset(),
+      Processor::SampleHold(ref mut proc) => proc.reset(),
+      Processor::Sampler(ref mut proc) => proc.reset(),
+      Processor::Shaper(ref mut proc) => proc.reset(),
+      Processor::Slew(ref mut proc) => proc.reset(),
+      Processor::Svf(ref mut proc) => proc.reset(),
+      Processor::WaveTableOsc(ref mut proc) => proc.reset(),
       Processor::Out(ref _left, ref _right) => {}
+      Processor::OutChannels(ref _channels) => {}
     }
   }
 
@@ -71,16 +218,35 @@ impl<F: Float> Processor<F> {
           }
           signals[param.mod_signal_ref].set(value);
           value = value + param.value.get();
-          value = value.max(param.values.min).min(param.values.max);
+          value = param.values.clamp(value);
           signals[param.out_signal_ref].set(value);
         }
       }
+      Processor::AmpMod(ref mut proc) => proc.process(signals, program),
+      Processor::Comb(ref mut proc) => proc.process(signals, program),
+      Processor::Crush(ref mut proc) => proc.process(signals, program),
       Processor::DCA(ref mut proc) => proc.process(signals, program),
+      Processor::DcBlocker(ref mut proc) => proc.process(signals, program),
+      Processor::Delay(ref mut proc) => proc.process(signals, program),
       Processor::EG(ref mut proc) => proc.process(signals, program),
+      Processor::EnvFollower(ref mut proc) => proc.process(signals, program),
       Processor::Expr(ref mut proc) => proc.process(signals, program),
       Processor::Filter(ref mut proc) => proc.process(signals, program),
+      Processor::FmOperator(ref mut proc) => proc.process(signals, program),
       Processor::Lfo(ref mut proc) => proc.process(signals, program, synth_globals),
+      Processor::MidSide(ref mut proc) => proc.process(signals, program),
+      Processor::MidiCc(ref mut proc) => proc.process(signals, program, synth_globals),
+      Processor::Noise(ref mut proc) => proc.process(signals, program),
       Processor::Osc(ref mut proc) => proc.process(signals, program, synth_globals),
+      Processor::Pan(ref mut proc) => proc.process(signals, program),
+      Processor::Probe(ref mut proc) => proc.process(signals, program),
+      Processor::RingMod(ref mut proc) => proc.process(signals, program),
+      Processor::SampleHold(ref mut proc) => proc.process(signals, program),
+      Processor::Sampler(ref mut proc) => proc.process(signals, program, synth_globals),
+      Processor::Shaper(ref mut proc) => proc.process(signals, program),
+      Processor::Slew(ref mut proc) => proc.process(signals, program),
+      Processor::Svf(ref mut proc) => proc.process(signals, program),
+      Processor::WaveTableOsc(ref mut proc) => proc.process(signals, program, synth_globals),
       Processor::Out(ref left, ref right) => {
         let voice = program.voice();
         let left_value = signals[*left].consume();
@@ -88,6 +254,13 @@ impl<F: Float> Processor<F> {
         let right_value = signals[*right].consume();
         signals[voice.output_right].set(right_value);
       }
+      Processor::OutChannels(ref channels) => {
+        let voice = program.voice();
+        for (signal, output) in channels.iter().zip(voice.output_channels.iter()) {
+          let value = signals[*signal].consume();
+          signals[*output].set(value);
+        }
+      }
     }
   }
 }
@@ -0,0 +1,81 @@
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub input: SignalRef,
+  /// Modulation between [0.0, 1.0]
+  pub mod_in: SignalRef,
+  /// Amount of modulation between [0.0, 1.0], at zero the input passes unchanged
+  pub depth: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor {
+  block: Block,
+}
+
+impl Processor {
+  pub fn new<F: Float>(_sample_rate: F, block: Block) -> Self {
+    Processor { block }
+  }
+
+  pub fn set_sample_rate<F: Float>(&mut self, _sample_rate: F) {}
+
+  pub fn reset(&mut self) {}
+
+  pub fn process<'a, F: Float>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { inputs, output } = self.block.clone();
+    let depth = signals[inputs.depth].get();
+    let gain = F::one() - depth + depth * signals[inputs.mod_in].get();
+    let value = signals[inputs.input].get() * gain;
+    signals[output].set(value);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::blocks::amp_mod::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  fn amp_mod(input: f32, mod_in: f32, depth: f32) -> f32 {
+    let (mut bench, (inputs, output)) = TestBench::<f32>::new(|builder| {
+      let inputs = Inputs {
+        input: builder.signal(),
+        mod_in: builder.signal(),
+        depth: builder.signal(),
+      };
+      (inputs, builder.signal())
+    });
+    bench.set(inputs.input, input);
+    bench.set(inputs.mod_in, mod_in);
+    bench.set(inputs.depth, depth);
+
+    let mut processor = Processor::new(44100.0, Block { inputs, output });
+    bench.step(|signals, program| processor.process(signals, program));
+    bench.get(output)
+  }
+
+  #[test]
+  fn zero_depth_passes_the_input_unchanged() {
+    for mod_in in [0.0, 0.3, 1.0].iter() {
+      assert!((amp_mod(0.8, *mod_in, 0.0) - 0.8).abs() < 1e-6);
+    }
+  }
+
+  #[test]
+  fn full_depth_follows_the_modulation() {
+    assert!(amp_mod(0.8, 0.0, 1.0).abs() < 1e-6);
+    assert!((amp_mod(0.8, 0.5, 1.0) - 0.4).abs() < 1e-6);
+    assert!((amp_mod(0.8, 0.5, 0.5) - 0.6).abs() < 1e-6);
+  }
+}
@@ -0,0 +1,162 @@
+use typenum::marker_traits::Unsigned;
+
+use kiro_synth_dsp::funcs::denormals::flush_denormal;
+
+use crate::float::Float;
+use crate::program::blocks::delay::{DelayLine, MaxDelaySamples};
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub input: SignalRef,
+  /// Length of the loop in samples, the period of the resonance
+  pub delay_samples: SignalRef,
+  /// Amount of the loop fed back, clamped to keep it stable
+  pub feedback: SignalRef,
+  /// Low pass filtering inside the loop between [0.0, 1.0), higher values decay the highs faster
+  pub damping: SignalRef,
+}
+
+/// Feedback comb filter with a one pole low pass in the loop (Karplus-Strong when excited with noise)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block,
+  delay_line: DelayLine<F>,
+  delay: F,
+  feedback: F,
+  damping: F,
+  filtered: F,
+}
+
+impl<F: Float> Processor<F> {
+  const MAX_FEEDBACK: f64 = 0.999;
+  const MAX_DAMPING: f64 = 0.99;
+
+  pub fn new(_sample_rate: F, block: Block) -> Self {
+    Processor {
+      block,
+      delay_line: DelayLine::new(),
+      delay: F::one(),
+      feedback: F::zero(),
+      damping: F::zero(),
+      filtered: F::zero(),
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, _sample_rate: F) {
+    self.reset();
+  }
+
+  pub fn reset(&mut self) {
+    self.delay_line.clear();
+    self.filtered = F::zero();
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { inputs, output } = self.block.clone();
+
+    let max_delay = F::val(MaxDelaySamples::to_usize() - 1);
+    signals[inputs.delay_samples].if_updated(|value| {
+      self.delay = value.max(F::one()).min(max_delay);
+    });
+
+    let max_feedback = F::val(Self::MAX_FEEDBACK);
+    signals[inputs.feedback].if_updated(|value| {
+      self.feedback = value.max(max_feedback.neg()).min(max_feedback);
+    });
+
+    let max_damping = F::val(Self::MAX_DAMPING);
+    signals[inputs.damping].if_updated(|value| {
+      self.damping = value.max(F::zero()).min(max_damping);
+    });
+
+    let delayed = self.delay_line.read(self.delay);
+    self.filtered =
+      flush_denormal(delayed * (F::one() - self.damping) + self.filtered * self.damping);
+    let input = signals[inputs.input].get();
+    self.delay_line.write(input + self.filtered * self.feedback);
+
+    signals[output].set(delayed);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::blocks::comb::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  fn impulse_response(processor_reset: bool) -> Vec<f64> {
+    let (mut bench, (inputs, output)) = TestBench::<f64>::new(|builder| {
+      let inputs = Inputs {
+        input: builder.signal(),
+        delay_samples: builder.signal(),
+        feedback: builder.signal(),
+        damping: builder.signal(),
+      };
+      (inputs, builder.signal())
+    });
+    bench.set(inputs.delay_samples, 100.0);
+    bench.set(inputs.feedback, 0.95);
+    bench.set(inputs.damping, 0.3);
+
+    let input = inputs.input;
+    let mut processor = Processor::new(44100.0, Block { inputs, output });
+    let render = |bench: &mut TestBench<f64>, processor: &mut Processor<f64>| {
+      (0..1000)
+        .map(|index| {
+          bench.set(input, if index == 0 { 1.0 } else { 0.0 });
+          bench.step(|signals, program| processor.process(signals, program));
+          bench.get(output)
+        })
+        .collect::<Vec<f64>>()
+    };
+
+    let response = render(&mut bench, &mut processor);
+    if processor_reset {
+      processor.reset();
+      render(&mut bench, &mut processor)
+    } else {
+      response
+    }
+  }
+
+  #[test]
+  fn impulse_rings_at_the_delay_period() {
+    let response = impulse_response(false);
+
+    let peaks: Vec<usize> = (1..response.len() - 1)
+      .filter(|&index| {
+        response[index] > 0.05
+          && response[index] >= response[index - 1]
+          && response[index] > response[index + 1]
+      })
+      .collect();
+    assert!(peaks.len() >= 8);
+    for pair in peaks.windows(2) {
+      assert!((pair[1] - pair[0] == 100) || (pair[1] - pair[0] == 101));
+    }
+
+    let peak_values: Vec<f64> = peaks.iter().map(|&index| response[index]).collect();
+    for pair in peak_values.windows(2) {
+      assert!(pair[1] < pair[0]);
+    }
+  }
+
+  #[test]
+  fn reset_clears_the_loop() {
+    let after_reset = impulse_response(true);
+    let fresh = impulse_response(false);
+    for (value, expected) in after_reset.iter().zip(fresh.iter()) {
+      assert!((value - expected).abs() < 1e-12);
+    }
+  }
+}
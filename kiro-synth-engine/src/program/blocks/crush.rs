@@ -0,0 +1,135 @@
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub input: SignalRef,
+  /// Resolution of the amplitude between [1, 24] bits
+  pub bits: SignalRef,
+  /// Number of frames every sample is held, 1 keeps the original sample rate
+  pub rate_divisor: SignalRef,
+}
+
+/// Bit crusher and sample rate reducer
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block,
+  counter: usize,
+  held: F,
+}
+
+impl<F: Float> Processor<F> {
+  const MAX_BITS: f64 = 24.0;
+
+  pub fn new(_sample_rate: F, block: Block) -> Self {
+    Processor {
+      block,
+      counter: 0,
+      held: F::zero(),
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, _sample_rate: F) {}
+
+  pub fn reset(&mut self) {
+    self.counter = 0;
+    self.held = F::zero();
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { inputs, output } = self.block.clone();
+
+    let rate_divisor = signals[inputs.rate_divisor]
+      .get()
+      .to_usize()
+      .unwrap_or(1)
+      .max(1);
+    if self.counter == 0 {
+      self.held = signals[inputs.input].get();
+    }
+    self.counter = (self.counter + 1) % rate_divisor;
+
+    let bits = signals[inputs.bits]
+      .get()
+      .floor()
+      .max(F::one())
+      .min(F::val(Self::MAX_BITS));
+    signals[output].set(Self::quantize(self.held, bits));
+  }
+
+  /// Round the value into one of the `2^bits` levels evenly spread between [-1.0, 1.0]
+  fn quantize(value: F, bits: F) -> F {
+    let step = F::val(2.0) / (F::val(2.0).powf(bits) - F::one());
+    let value = value.max(F::one().neg()).min(F::one());
+    ((value + F::one()) / step).round() * step - F::one()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::blocks::crush::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  fn render(bits: f64, rate_divisor: f64, input: &[f64]) -> Vec<f64> {
+    let (mut bench, (inputs, output)) = TestBench::<f64>::new(|builder| {
+      let inputs = Inputs {
+        input: builder.signal(),
+        bits: builder.signal(),
+        rate_divisor: builder.signal(),
+      };
+      (inputs, builder.signal())
+    });
+    bench.set(inputs.bits, bits);
+    bench.set(inputs.rate_divisor, rate_divisor);
+
+    let input_signal = inputs.input;
+    let mut processor = Processor::new(44100.0, Block { inputs, output });
+    input
+      .iter()
+      .map(|value| {
+        bench.set(input_signal, *value);
+        bench.step(|signals, program| processor.process(signals, program));
+        bench.get(output)
+      })
+      .collect()
+  }
+
+  fn assert_close(output: &[f64], expected: &[f64]) {
+    for (value, expected) in output.iter().zip(expected.iter()) {
+      assert!((value - expected).abs() < 1e-9, "{:?}", output);
+    }
+  }
+
+  #[test]
+  fn one_bit_is_the_sign_of_the_input() {
+    let input: Vec<f64> = (0..100).map(|index| (index as f64 * 0.1).sin()).collect();
+    let output = render(1.0, 1.0, &input);
+    for (value, input) in output.iter().zip(input.iter()) {
+      assert!((value - input.signum()).abs() < 1e-9);
+    }
+  }
+
+  #[test]
+  fn rate_divisor_holds_the_samples() {
+    let output = render(24.0, 2.0, &[0.1, 0.2, 0.3, 0.4, 0.5]);
+    for (value, expected) in output.iter().zip([0.1, 0.1, 0.3, 0.3, 0.5].iter()) {
+      assert!((value - expected).abs() < 1e-6);
+    }
+  }
+
+  #[test]
+  fn two_bits_have_four_levels() {
+    let output = render(2.0, 1.0, &[-1.0, -0.4, 0.2, 0.9, 2.0]);
+    let third = 1.0 / 3.0;
+    assert_close(&output, &[-1.0, -third, third, 1.0, 1.0]);
+  }
+}
@@ -0,0 +1,41 @@
+use kiro_synth_dsp::filters::dc_blocker::DcBlocker;
+
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub input: SignalRef,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  blocker: DcBlocker<F>,
+  block: Block,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(sample_rate: F, block: Block) -> Self {
+    Processor {
+      blocker: DcBlocker::new(sample_rate),
+      block,
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.blocker.set_sample_rate(sample_rate)
+  }
+
+  pub fn reset(&mut self) {
+    self.blocker.reset()
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { input, output } = self.block;
+    let value = self.blocker.process(signals[input].get());
+    signals[output].set(value);
+  }
+}
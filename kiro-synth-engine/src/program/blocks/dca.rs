@@ -4,7 +4,8 @@ use kiro_synth_dsp::float::Float;
 use crate::program::{Program, SignalRef};
 use crate::signal::SignalBus;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Inputs {
   pub left: SignalRef,
   pub right: SignalRef,
@@ -16,13 +17,15 @@ pub struct Inputs {
   pub pan_mod: SignalRef,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Outputs {
   pub left: SignalRef,
   pub right: SignalRef,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
   pub inputs: Inputs,
   pub outputs: Outputs,
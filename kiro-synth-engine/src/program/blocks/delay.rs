@@ -0,0 +1,221 @@
+use heapless::consts;
+use heapless::Vec;
+use typenum::marker_traits::Unsigned;
+
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+/// Capacity of the delay line, ~185ms at 44.1KHz or ~170ms at 48KHz
+pub type MaxDelaySamples = consts::U8192;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub input: SignalRef,
+  /// Delay time in seconds
+  pub time: SignalRef,
+  /// Amount of the output fed back into the delay line, clamped to keep it stable
+  pub feedback: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct DelayLine<F: Float> {
+  head: usize,
+  buffer: Vec<F, MaxDelaySamples>,
+}
+
+impl<F: Float> DelayLine<F> {
+  pub fn new() -> Self {
+    let mut buffer = Vec::new();
+    buffer.resize(MaxDelaySamples::to_usize(), F::zero()).ok();
+    DelayLine { head: 0, buffer }
+  }
+
+  pub fn clear(&mut self) {
+    self.head = 0;
+    for sample in self.buffer.iter_mut() {
+      *sample = F::zero();
+    }
+  }
+
+  pub fn write(&mut self, input: F) {
+    self.buffer[self.head] = input;
+    self.head = (self.head + 1) % self.buffer.len();
+  }
+
+  /// Sample written the given number of samples ago, starting from 1 for the last one
+  pub fn sample(&self, delay: usize) -> F {
+    let len = self.buffer.len();
+    self.buffer[(self.head + len - delay) % len]
+  }
+
+  /// Linearly interpolated sample for a fractional delay between [1, capacity - 1]
+  pub fn read(&self, delay: F) -> F {
+    let index = delay.floor();
+    let fraction = delay - index;
+    let index = index.to_usize().unwrap_or(1);
+    let current = self.sample(index);
+    current + (self.sample(index + 1) - current) * fraction
+  }
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block,
+  sample_rate: F,
+  delay_line: DelayLine<F>,
+  time: F,
+  delay: F,
+  feedback: F,
+}
+
+impl<F: Float> Processor<F> {
+  const MAX_FEEDBACK: f64 = 0.98;
+
+  pub fn new(sample_rate: F, block: Block) -> Self {
+    Processor {
+      block,
+      sample_rate,
+      delay_line: DelayLine::new(),
+      time: F::zero(),
+      delay: F::one(),
+      feedback: F::zero(),
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.sample_rate = sample_rate;
+    self.update_delay();
+    self.delay_line.clear();
+  }
+
+  pub fn reset(&mut self) {
+    self.delay_line.clear();
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { inputs, output } = self.block.clone();
+
+    signals[inputs.time].if_updated(|value| {
+      self.time = value;
+      self.update_delay();
+    });
+
+    let max_feedback = F::val(Self::MAX_FEEDBACK);
+    signals[inputs.feedback].if_updated(|value| {
+      self.feedback = value.max(max_feedback.neg()).min(max_feedback);
+    });
+
+    let input = signals[inputs.input].get();
+    let delayed = self.delay_line.read(self.delay);
+    self.delay_line.write(input + delayed * self.feedback);
+
+    signals[output].set(delayed);
+  }
+
+  /// Delay in samples for the last time received, kept within the capacity of the line
+  fn update_delay(&mut self) {
+    let max_delay = F::val(MaxDelaySamples::to_usize() - 1);
+    self.delay = (self.time * self.sample_rate).max(F::one()).min(max_delay);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::delay::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+  use crate::program::{self, ProgramBuilder};
+  use crate::voice::Voice;
+
+  fn render(time: f64, feedback: f64, input: &[f64]) -> Vec<f64> {
+    let (mut bench, (inputs, output)) = TestBench::<f64>::new(|builder| {
+      let inputs = Inputs {
+        input: builder.signal(),
+        time: builder.signal(),
+        feedback: builder.signal(),
+      };
+      (inputs, builder.signal())
+    });
+    bench.set(inputs.time, time);
+    bench.set(inputs.feedback, feedback);
+
+    let input_signal = inputs.input;
+    let mut processor = Processor::new(1000.0, Block { inputs, output });
+    input
+      .iter()
+      .map(|value| {
+        bench.set(input_signal, *value);
+        bench.step(|signals, program| processor.process(signals, program));
+        bench.get(output)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn one_sample_delay_shifts_the_input() {
+    let input = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let output = render(0.001, 0.0, &input);
+    let expected = [0.0, 1.0, 2.0, 3.0, 4.0];
+    for (value, expected) in output.iter().zip(expected.iter()) {
+      assert!((value - expected).abs() < 1e-9);
+    }
+  }
+
+  #[test]
+  fn fractional_delay_interpolates() {
+    let output = render(0.0015, 0.0, &[0.0, 1.0, 0.0, 0.0]);
+    let expected = [0.0, 0.0, 0.5, 0.5];
+    for (value, expected) in output.iter().zip(expected.iter()) {
+      assert!((value - expected).abs() < 1e-9);
+    }
+  }
+
+  #[test]
+  fn feedback_is_clamped() {
+    let mut input = vec![0.0; 1000];
+    input[0] = 1.0;
+    let output = render(0.002, 10.0, &input);
+    assert!(output.iter().all(|value| value.abs() <= 1.0));
+    assert!(output[999].abs() < output[2].abs());
+  }
+
+  #[test]
+  fn sample_rate_change_recomputes_the_delay() {
+    let mut builder = ProgramBuilder::<f64>::new();
+    let inputs = Inputs {
+      input: builder.const_one(),
+      time: builder.const_value(0.01),
+      feedback: builder.const_zero(),
+    };
+    let output = builder.signal();
+    builder.block(program::Block::Delay(Block { inputs, output }));
+    builder.out(output, output);
+    let mut program = builder.build();
+    let globals = SynthGlobals::new();
+
+    let mut voice = Voice::new(1000.0, &program);
+    voice.note_on(&program, 60, 261.626, 1.0);
+    let mut silent_samples = |voice: &mut Voice<f64>| {
+      (0..100)
+        .take_while(|_| {
+          voice.process(&mut program, &globals);
+          voice.output(&program).0 == 0.0
+        })
+        .count()
+    };
+    assert_eq!(silent_samples(&mut voice), 10);
+
+    // the time signal is not updated again, the delay follows the new rate anyway
+    voice.set_sample_rate(2000.0);
+    assert_eq!(silent_samples(&mut voice), 20);
+  }
+}
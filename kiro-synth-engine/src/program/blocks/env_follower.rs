@@ -0,0 +1,98 @@
+use kiro_synth_dsp::effects::envelope_follower::EnvelopeFollower;
+
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub input: SignalRef,
+  /// Time constant in seconds to follow a rising level
+  pub attack: SignalRef,
+  /// Time constant in seconds to follow a falling level
+  pub release: SignalRef,
+}
+
+/// Control signal following the level of the input
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  follower: EnvelopeFollower<F>,
+  block: Block,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(sample_rate: F, block: Block) -> Self {
+    Processor {
+      follower: EnvelopeFollower::new(sample_rate, F::zero(), F::zero()),
+      block,
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.follower.set_sample_rate(sample_rate)
+  }
+
+  pub fn reset(&mut self) {
+    self.follower.reset()
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Inputs {
+      input,
+      attack,
+      release,
+    } = self.block.inputs;
+
+    signals[attack].if_updated(|value| self.follower.set_attack_time_sec(value));
+    signals[release].if_updated(|value| self.follower.set_release_time_sec(value));
+
+    let value = self.follower.process(signals[input].get());
+    signals[self.block.output].set(value);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::blocks::env_follower::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  #[test]
+  fn follows_the_input_level_and_resets_to_zero() {
+    let (mut bench, (inputs, output)) = TestBench::<f64>::new(|builder| {
+      let inputs = Inputs {
+        input: builder.signal(),
+        attack: builder.signal(),
+        release: builder.signal(),
+      };
+      (inputs, builder.signal())
+    });
+    bench.set(inputs.attack, 0.001);
+    bench.set(inputs.release, 0.1);
+
+    let input = inputs.input;
+    let mut processor = Processor::new(1000.0, Block { inputs, output });
+    let mut render = |processor: &mut Processor<f64>, value: f64, samples: usize| {
+      for _ in 0..samples {
+        bench.set(input, value);
+        bench.step(|signals, program| processor.process(signals, program));
+      }
+      bench.get(output)
+    };
+
+    // it reaches the level of the step quickly and falls down slowly
+    assert!((render(&mut processor, -0.5, 20) - 0.5).abs() < 1e-6);
+    let released = render(&mut processor, 0.0, 100);
+    assert!((released - 0.5 * (-1.0f64).exp()).abs() < 1e-6);
+
+    processor.reset();
+    assert!(render(&mut processor, 0.0, 1).abs() < 1e-12);
+  }
+}
@@ -4,7 +4,8 @@ use kiro_synth_dsp::float::Float;
 use crate::program::{Program, SignalRef};
 use crate::signal::SignalBus;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Inputs {
   pub attack: SignalRef,
   pub decay: SignalRef,
@@ -15,14 +16,16 @@ pub struct Inputs {
   pub reset_to_zero: SignalRef,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Outputs {
   pub normal: SignalRef,
   pub biased: SignalRef,
   pub voice_off: SignalRef,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
   pub inputs: Inputs,
   pub outputs: Outputs,
@@ -42,6 +45,10 @@ impl<F: Float> Processor<F> {
     }
   }
 
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.envgen.set_sample_rate(sample_rate)
+  }
+
   pub fn reset(&mut self) {
     self.envgen.reset()
   }
@@ -85,6 +92,7 @@ impl<F: Float> Processor<F> {
     signals[mode].if_updated(|value| match value {
       v if v == F::zero() => self.envgen.set_mode(Mode::Analog),
       v if v == F::one() => self.envgen.set_mode(Mode::Digital),
+      v if v == F::val(2) => self.envgen.set_mode(Mode::Linear),
       _ => {}
     });
 
@@ -99,3 +107,59 @@ impl<F: Float> Processor<F> {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::allocator::VoiceAllocator;
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::envgen::{Block, Inputs, Outputs};
+  use crate::program::{Block as ProgramBlock, Program, ProgramBuilder};
+
+  fn program<'a>() -> Program<'a, f32> {
+    let mut builder = ProgramBuilder::<f32>::new();
+    let voice = builder.voice().clone();
+    let inputs = Inputs {
+      attack: builder.const_value(0.01),
+      decay: builder.const_value(0.01),
+      sustain: builder.const_value(0.5),
+      release: builder.const_value(0.01),
+      mode: builder.const_value(2.0),
+      legato: builder.const_zero(),
+      reset_to_zero: builder.const_zero(),
+    };
+    let outputs = Outputs {
+      normal: builder.signal(),
+      biased: builder.signal(),
+      voice_off: voice.off,
+    };
+    let output = outputs.normal;
+    builder.block(ProgramBlock::EG(Block { inputs, outputs }));
+    builder.out(output, output);
+    builder.build()
+  }
+
+  #[test]
+  fn voice_off_when_release_ends() {
+    let mut program = program();
+    let globals = SynthGlobals::new();
+    let mut allocator = VoiceAllocator::new(1000.0, &program);
+
+    allocator.note_on(&program, 60, 1.0);
+    let mut peak = 0.0f32;
+    for _ in 0..100 {
+      peak = peak.max(allocator.process(&mut program, &globals).0);
+    }
+    assert!((peak - 1.0).abs() < 1e-6);
+    assert_eq!(allocator.get_num_active_voices(), 1);
+
+    allocator.note_off(&program, 60);
+    for _ in 0..4 {
+      allocator.process(&mut program, &globals);
+    }
+    assert_eq!(allocator.get_num_active_voices(), 1);
+    for _ in 0..4 {
+      allocator.process(&mut program, &globals);
+    }
+    assert_eq!(allocator.get_num_active_voices(), 0);
+  }
+}
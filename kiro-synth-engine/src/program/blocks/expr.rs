@@ -7,10 +7,12 @@ use crate::signal::SignalBus;
 
 type MaxOps = consts::U8;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct OpRef(usize);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub enum Op<F: Float> {
   Value(F),
   Param(ParamRef),
@@ -32,7 +34,8 @@ pub enum Op<F: Float> {
   MulSignalParam(SignalRef, ParamRef),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block<F: Float> {
   ops: Vec<Op<F>, MaxOps>,
   pub output: SignalRef,
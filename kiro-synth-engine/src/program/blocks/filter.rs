@@ -7,7 +7,8 @@ use crate::program::{Program, SignalRef};
 use crate::signal::SignalBus;
 use kiro_synth_dsp::filters::q_control::QControl;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
   PassThrough,
   VAOnePole(va_one_pole::Mode),
@@ -36,7 +37,8 @@ impl Mode {
   }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Params {
   pub mode: SignalRef,
   pub freq: SignalRef,
@@ -44,7 +46,8 @@ pub struct Params {
   pub q: SignalRef,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
   pub input: SignalRef,
   pub params: Params,
@@ -108,6 +111,11 @@ impl<F: Float> Processor<F> {
     }
   }
 
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.va_one_pole.set_sample_rate(sample_rate);
+    self.oberheim_sem.set_sample_rate(sample_rate);
+  }
+
   pub fn reset(&mut self) {
     match self.mode {
       Mode::PassThrough => {}
@@ -0,0 +1,115 @@
+use kiro_synth_dsp::oscillators::fm_operator::FmOperator;
+
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  /// Frequency in Hz
+  pub freq: SignalRef,
+  /// Phase modulation in radians, usually the output of another operator
+  pub mod_in: SignalRef,
+  /// Self modulation amount in radians
+  pub feedback: SignalRef,
+  /// Output gain, for a modulator it is the modulation index
+  pub level: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  operator: FmOperator<F>,
+  block: Block,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(sample_rate: F, block: Block) -> Self {
+    Processor {
+      operator: FmOperator::new(sample_rate),
+      block,
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.operator.set_sample_rate(sample_rate)
+  }
+
+  pub fn reset(&mut self) {
+    self.operator.reset()
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { inputs, output } = self.block.clone();
+
+    signals[inputs.freq].if_updated(|value| self.operator.set_frequency(value));
+    signals[inputs.feedback].if_updated(|value| self.operator.set_feedback(value));
+    signals[inputs.level].if_updated(|value| self.operator.set_level(value));
+
+    let modulation = signals[inputs.mod_in].get();
+    signals[output].set(self.operator.generate(modulation));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::blocks::fm_operator::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  #[test]
+  fn modulator_drives_the_carrier_phase() {
+    let (mut bench, (zero, modulator, carrier)) = TestBench::<f32>::new(|builder| {
+      let zero = builder.const_zero();
+      let modulator = [(); 3].map(|_| builder.signal());
+      (zero, modulator, [(); 3].map(|_| builder.signal()))
+    });
+    let [modulator_freq, modulator_level, modulator_output] = modulator;
+    let [carrier_freq, carrier_level, carrier_output] = carrier;
+    bench.set(modulator_freq, 100.0);
+    bench.set(modulator_level, 2.0);
+    bench.set(carrier_freq, 0.0);
+    bench.set(carrier_level, 0.5);
+
+    let mut modulator = Processor::new(
+      1000.0,
+      Block {
+        inputs: Inputs {
+          freq: modulator_freq,
+          mod_in: zero,
+          feedback: zero,
+          level: modulator_level,
+        },
+        output: modulator_output,
+      },
+    );
+    let mut carrier = Processor::new(
+      1000.0,
+      Block {
+        inputs: Inputs {
+          freq: carrier_freq,
+          mod_in: modulator_output,
+          feedback: zero,
+          level: carrier_level,
+        },
+        output: carrier_output,
+      },
+    );
+
+    for _ in 0..20 {
+      bench.step(|signals, program| {
+        modulator.process(signals, program);
+        carrier.process(signals, program);
+      });
+      let modulation: f32 = bench.get(modulator_output);
+      let expected: f32 = 0.5 * modulation.sin();
+      assert!((bench.get(carrier_output) - expected).abs() < 1e-6);
+    }
+  }
+}
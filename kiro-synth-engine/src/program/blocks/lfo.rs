@@ -5,15 +5,21 @@ use crate::globals::SynthGlobals;
 use crate::program::{Program, SignalRef};
 use crate::signal::SignalBus;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Inputs {
   pub shape: SignalRef,
   pub rate: SignalRef,
   pub phase: SignalRef,
   pub depth: SignalRef,
+  /// Length of a cycle in beats synchronized to the tempo, or zero to use the rate in Hz
+  pub sync: SignalRef,
+  /// Whether the phase restarts when the voice is triggered, otherwise it runs freely
+  pub retrigger: SignalRef,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
   pub inputs: Inputs,
   pub output: SignalRef,
@@ -23,23 +29,41 @@ pub struct Block {
 pub(crate) struct Processor<F: Float> {
   lfo: Lfo<F>,
   block: Block,
+  rate: F,
+  sync: F,
+  tempo: F,
+  retrigger: bool,
 }
 
 impl<F: Float> Processor<F> {
   pub fn new(sample_rate: F, block: Block) -> Self {
     let lfo = Lfo::new(sample_rate);
 
-    Processor { lfo, block }
+    Processor {
+      lfo,
+      block,
+      rate: F::one(),
+      sync: F::zero(),
+      tempo: F::zero(),
+      retrigger: true,
+    }
   }
 
-  pub fn reset(&mut self) {
-    self.lfo.reset()
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.lfo.set_sample_rate(sample_rate)
+  }
+
+  pub fn reset(&mut self) {}
+
+  /// Set the seed for the voice owning this processor
+  pub fn set_seed(&mut self, seed: u32) {
+    self.lfo.set_seed(seed)
   }
 
   pub fn process<'a>(
     &mut self,
     signals: &mut SignalBus<'a, F>,
-    _program: &Program<F>,
+    program: &Program<F>,
     synth_globals: &SynthGlobals<F>,
   ) {
     let Block { inputs, output } = self.block.clone();
@@ -48,6 +72,8 @@ impl<F: Float> Processor<F> {
       rate,
       phase,
       depth,
+      sync,
+      retrigger,
     } = inputs;
 
     signals[shape].if_updated(|value| {
@@ -58,10 +84,108 @@ impl<F: Float> Processor<F> {
           .clone(),
       )
     });
-    signals[rate].if_updated(|value| self.lfo.set_rate(value));
     signals[phase].if_updated(|value| self.lfo.set_phase(value));
     signals[depth].if_updated(|value| self.lfo.set_depth(value));
+    signals[retrigger].if_updated(|value| self.retrigger = value > F::zero());
+
+    let mut rate_invalidated = self.tempo != synth_globals.tempo;
+    self.tempo = synth_globals.tempo;
+    signals[rate].if_updated(|value| {
+      self.rate = value;
+      rate_invalidated = true;
+    });
+    signals[sync].if_updated(|value| {
+      self.sync = value;
+      rate_invalidated = true;
+    });
+    if rate_invalidated {
+      self.lfo.set_rate(self.effective_rate());
+    }
+
+    let retrigger = self.retrigger;
+    signals[program.voice().trigger].if_updated(|value| {
+      if retrigger && value > F::zero() {
+        self.lfo.reset()
+      }
+    });
 
     signals[output].set(self.lfo.generate());
   }
+
+  fn effective_rate(&self) -> F {
+    if self.sync > F::zero() {
+      self.tempo / (F::val(60) * self.sync)
+    } else {
+      self.rate
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::lfo::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  /// Returns the sample indices where the saw waveform wraps around
+  fn saw_wraps(globals: &SynthGlobals<f32>, rate: f32, sync: f32) -> Vec<usize> {
+    let (mut bench, (inputs, output)) = TestBench::<f32>::new(|builder| {
+      let inputs = Inputs {
+        shape: builder.signal(),
+        rate: builder.signal(),
+        phase: builder.signal(),
+        depth: builder.signal(),
+        sync: builder.signal(),
+        retrigger: builder.signal(),
+      };
+      (inputs, builder.signal())
+    });
+    bench.set(inputs.shape, 2.0);
+    bench.set(inputs.rate, rate);
+    bench.set(inputs.depth, 1.0);
+    bench.set(inputs.sync, sync);
+    bench.set(inputs.retrigger, 1.0);
+
+    let block = Block { inputs, output };
+    let mut processor = Processor::new(1024.0, block);
+
+    let mut wraps = Vec::new();
+    let mut previous = 0.0;
+    for index in 0..2048 {
+      bench.step(|signals, program| processor.process(signals, program, globals));
+      let value = bench.get(output);
+      if value < previous {
+        wraps.push(index);
+      }
+      previous = value;
+    }
+    wraps
+  }
+
+  #[test]
+  fn free_running_rate() {
+    let wraps = saw_wraps(&SynthGlobals::new(), 8.0, 0.0);
+    assert!(wraps.len() >= 15);
+    for pair in wraps.windows(2) {
+      assert_eq!(pair[1] - pair[0], 128);
+    }
+  }
+
+  #[test]
+  fn synchronized_to_tempo() {
+    let mut globals = SynthGlobals::new();
+    globals.tempo = 60.0;
+    // a sixteenth note at 60 bpm lasts 250ms
+    let wraps = saw_wraps(&globals, 8.0, 0.25);
+    assert!(wraps.len() >= 7);
+    for pair in wraps.windows(2) {
+      assert_eq!(pair[1] - pair[0], 256);
+    }
+
+    globals.tempo = 120.0;
+    let wraps = saw_wraps(&globals, 8.0, 1.0);
+    for pair in wraps.windows(2) {
+      assert_eq!(pair[1] - pair[0], 512);
+    }
+  }
 }
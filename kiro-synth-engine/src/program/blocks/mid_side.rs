@@ -0,0 +1,109 @@
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub left: SignalRef,
+  pub right: SignalRef,
+  /// Gain of the side signal, 0 collapses to mono, 1 keeps the input and above 1 widens it
+  pub width: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outputs {
+  pub left: SignalRef,
+  pub right: SignalRef,
+}
+
+/// Stereo width through mid/side processing
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub outputs: Outputs,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block,
+  width: F,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(_sample_rate: F, block: Block) -> Self {
+    Processor {
+      block,
+      width: F::one(),
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, _sample_rate: F) {}
+
+  pub fn reset(&mut self) {}
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { inputs, outputs } = self.block.clone();
+
+    signals[inputs.width].if_updated(|value| self.width = value.max(F::zero()));
+
+    let (left, right) = (signals[inputs.left].get(), signals[inputs.right].get());
+    let half = F::val(0.5);
+    let mid = (left + right) * half;
+    let side = (left - right) * half * self.width;
+    signals[outputs.left].set(mid + side);
+    signals[outputs.right].set(mid - side);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::blocks::mid_side::{Block, Inputs, Outputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  fn mid_side(left: f32, right: f32, width: f32) -> (f32, f32) {
+    let (mut bench, (inputs, outputs)) = TestBench::<f32>::new(|builder| {
+      let inputs = Inputs {
+        left: builder.signal(),
+        right: builder.signal(),
+        width: builder.signal(),
+      };
+      let outputs = Outputs {
+        left: builder.signal(),
+        right: builder.signal(),
+      };
+      (inputs, outputs)
+    });
+    bench.set(inputs.left, left);
+    bench.set(inputs.right, right);
+    bench.set(inputs.width, width);
+
+    let (left, right) = (outputs.left, outputs.right);
+    let mut processor = Processor::new(44100.0, Block { inputs, outputs });
+    bench.step(|signals, program| processor.process(signals, program));
+    (bench.get(left), bench.get(right))
+  }
+
+  #[test]
+  fn zero_width_is_mono() {
+    let (left, right) = mid_side(0.8, -0.2, 0.0);
+    assert!((left - 0.3).abs() < 1e-6);
+    assert!((right - 0.3).abs() < 1e-6);
+  }
+
+  #[test]
+  fn unity_width_keeps_the_input() {
+    let (left, right) = mid_side(0.8, -0.2, 1.0);
+    assert!((left - 0.8).abs() < 1e-6);
+    assert!((right + 0.2).abs() < 1e-6);
+  }
+
+  #[test]
+  fn wider_increases_the_side() {
+    let (left, right) = mid_side(0.8, -0.2, 2.0);
+    assert!((left - 1.3).abs() < 1e-6);
+    assert!((right + 0.7).abs() < 1e-6);
+  }
+}
@@ -0,0 +1,100 @@
+use crate::float::Float;
+use crate::globals::SynthGlobals;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block<F: Float> {
+  pub cc: u8,
+  /// Value used until the controller is received for the first time
+  pub default: F,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block<F>,
+  coefficient: F,
+  value: Option<F>,
+}
+
+impl<F: Float> Processor<F> {
+  /// Time in seconds for the output to reach ~63% of a controller change
+  const SMOOTHING_TIME: f64 = 0.005;
+
+  pub fn new(sample_rate: F, block: Block<F>) -> Self {
+    Processor {
+      block,
+      coefficient: Self::coefficient(sample_rate),
+      value: None,
+    }
+  }
+
+  fn coefficient(sample_rate: F) -> F {
+    (-(F::val(Self::SMOOTHING_TIME) * sample_rate).recip()).exp()
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.coefficient = Self::coefficient(sample_rate);
+  }
+
+  pub fn reset(&mut self) {
+    self.value = None;
+  }
+
+  pub fn process<'a>(
+    &mut self,
+    signals: &mut SignalBus<'a, F>,
+    _program: &Program<F>,
+    synth_globals: &SynthGlobals<F>,
+  ) {
+    let Block {
+      cc,
+      default,
+      output,
+    } = self.block;
+
+    let target = synth_globals.controllers.get(cc).unwrap_or(default);
+    let value = match self.value {
+      Some(value) => target + (value - target) * self.coefficient,
+      None => target,
+    };
+    self.value = Some(value);
+
+    signals[output].set(value);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::midi_cc::{Block, Processor};
+  use crate::program::blocks::TestBench;
+
+  #[test]
+  fn smooths_towards_controller_value() {
+    let (mut bench, output) = TestBench::<f32>::new(|builder| builder.signal());
+    let mut globals = SynthGlobals::new();
+
+    let block = Block {
+      cc: 1,
+      default: 0.25,
+      output,
+    };
+    let mut processor = Processor::new(1000.0, block);
+
+    bench.step(|signals, program| processor.process(signals, program, &globals));
+    assert!((bench.get(output) - 0.25).abs() < 1e-6);
+
+    globals.controllers.set(1, 1.0);
+    bench.step(|signals, program| processor.process(signals, program, &globals));
+    let value = bench.get(output);
+    assert!(value > 0.25 && value < 1.0);
+
+    for _ in 0..100 {
+      bench.step(|signals, program| processor.process(signals, program, &globals));
+    }
+    assert!((bench.get(output) - 1.0).abs() < 1e-3);
+  }
+}
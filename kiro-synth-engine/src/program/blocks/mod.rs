@@ -1,6 +1,73 @@
+pub mod amp_mod;
+pub mod comb;
+pub mod crush;
+pub mod dc_blocker;
 pub mod dca;
+pub mod delay;
+pub mod env_follower;
 pub mod envgen;
 pub mod expr;
 pub mod filter;
+pub mod fm_operator;
 pub mod lfo;
+pub mod mid_side;
+pub mod midi_cc;
+pub mod noise;
 pub mod osc;
+pub mod pan;
+pub mod probe;
+pub mod ring_mod;
+pub mod sample_hold;
+pub mod sampler;
+pub mod shaper;
+pub mod slew;
+pub mod svf;
+pub mod wavetable_osc;
+
+#[cfg(test)]
+pub(crate) use test_bench::TestBench;
+
+#[cfg(test)]
+mod test_bench {
+  use crate::float::Float;
+  use crate::program::{Program, ProgramBuilder, SignalRef};
+  use crate::signal::{Signal, SignalBus};
+
+  /// Program and signals to drive the processors of the blocks under test
+  pub(crate) struct TestBench<'a, F: Float> {
+    pub program: Program<'a, F>,
+    signals: Vec<Signal<F>>,
+  }
+
+  impl<'a, F: Float> TestBench<'a, F> {
+    /// Builds a program with the signals allocated by `build`, returning also what it returns
+    pub fn new<T, B>(build: B) -> (Self, T)
+    where
+      B: FnOnce(&mut ProgramBuilder<'a, F>) -> T,
+    {
+      let mut builder = ProgramBuilder::new();
+      let allocated = build(&mut builder);
+      let program = builder.build();
+      let signals = vec![Signal::default(); program.get_signals_count()];
+      (TestBench { program, signals }, allocated)
+    }
+
+    pub fn set(&mut self, signal: SignalRef, value: F) {
+      self.signals[signal.0].set(value);
+    }
+
+    pub fn get(&self, signal: SignalRef) -> F {
+      self.signals[signal.0].get()
+    }
+
+    /// Runs the processors for one frame and updates the state of the signals for the next one
+    pub fn step<P>(&mut self, process: P)
+    where
+      P: FnOnce(&mut SignalBus<F>, &Program<F>),
+    {
+      let mut signals = SignalBus::new(self.signals.as_mut_slice());
+      process(&mut signals, &self.program);
+      signals.update();
+    }
+  }
+}
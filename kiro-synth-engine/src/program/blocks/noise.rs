@@ -0,0 +1,106 @@
+use kiro_synth_dsp::oscillators::noise::{Noise, NoiseKind};
+
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  /// 0: white, 1: pink, 2: brown
+  pub kind: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  noise: Noise<F>,
+  block: Block,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(_sample_rate: F, block: Block) -> Self {
+    Processor {
+      noise: Noise::new(0),
+      block,
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, _sample_rate: F) {}
+
+  /// Set the seed for the voice owning this processor
+  pub fn set_seed(&mut self, seed: u32) {
+    self.noise.set_seed(seed)
+  }
+
+  pub fn reset(&mut self) {
+    self.noise.reset()
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { inputs, output } = self.block.clone();
+
+    signals[inputs.kind].if_updated(|value| {
+      let kind = match value.to_usize().unwrap_or(0) {
+        0 => NoiseKind::White,
+        1 => NoiseKind::Pink,
+        _ => NoiseKind::Brown,
+      };
+      self.noise.set_kind(kind)
+    });
+
+    signals[output].set(self.noise.generate());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::noise::{Block, Inputs};
+  use crate::program::{self, Program, ProgramBuilder};
+  use crate::voice::Voice;
+
+  fn program<'a>() -> Program<'a, f32> {
+    let mut builder = ProgramBuilder::<f32>::new();
+    let kind = builder.const_value(1.0);
+    let output = builder.signal();
+    builder.block(program::Block::Noise(Block {
+      inputs: Inputs { kind },
+      output,
+    }));
+    builder.out(output, output);
+    builder.build()
+  }
+
+  fn render(voice: &mut Voice<f32>, program: &mut Program<f32>) -> Vec<f32> {
+    let globals = SynthGlobals::new();
+    voice.note_on(program, 60, 261.626, 1.0);
+    (0..64)
+      .map(|_| {
+        voice.process(program, &globals);
+        voice.output(program).0
+      })
+      .collect()
+  }
+
+  #[test]
+  fn reset_reproduces_the_voice_sequence() {
+    let mut program = program();
+    let mut voice = Voice::new(44100.0, &program);
+    voice.set_index(0);
+    let first = render(&mut voice, &mut program);
+    let second = render(&mut voice, &mut program);
+    assert!(first.iter().any(|value| value.abs() > 1e-3));
+    assert_eq!(first, second);
+
+    let mut other_voice = Voice::new(44100.0, &program);
+    other_voice.set_index(1);
+    assert_ne!(first, render(&mut other_voice, &mut program));
+  }
+}
@@ -6,7 +6,8 @@ use crate::globals::SynthGlobals;
 use crate::program::{Program, SignalRef};
 use crate::signal::SignalBus;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Inputs {
   pub shape: SignalRef,
   pub amplitude: SignalRef,
@@ -17,18 +18,27 @@ pub struct Inputs {
   pub note_pitch: SignalRef,
   pub pitch_bend: SignalRef,
   pub freq_mod: SignalRef,
+  /// Width of the pulse for the pulse waveforms, from 0 to 1
+  pub pulse_width: SignalRef,
+  /// A rising edge restarts the cycle (hard sync), with a value up to 1 as the position
+  /// of the restart before the next sample. It is usually the sync output of another oscillator.
+  pub sync: SignalRef,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
   pub inputs: Inputs,
   pub output: SignalRef,
+  /// One sample pulse when the cycle restarts, to drive the sync of other oscillators
+  pub sync_output: SignalRef,
 }
 
 #[derive(Debug)]
 pub(crate) struct Processor<F: Float> {
   osc: PitchedOscillator<F>,
   block: Block,
+  last_sync: F,
 }
 
 impl<F: Float> Processor<F> {
@@ -36,11 +46,20 @@ impl<F: Float> Processor<F> {
     let waveform = OscWaveform::default();
     let osc = PitchedOscillator::new(sample_rate, waveform, F::zero());
 
-    Processor { osc, block }
+    Processor {
+      osc,
+      block,
+      last_sync: F::zero(),
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.osc.set_sample_rate(sample_rate)
   }
 
   pub fn reset(&mut self) {
-    self.osc.reset()
+    self.osc.reset();
+    self.last_sync = F::zero();
   }
 
   pub fn process<'a>(
@@ -49,7 +68,11 @@ impl<F: Float> Processor<F> {
     _program: &Program<F>,
     synth_globals: &SynthGlobals<F>,
   ) {
-    let Block { inputs, output } = self.block.clone();
+    let Block {
+      inputs,
+      output,
+      sync_output,
+    } = self.block.clone();
     let Inputs {
       shape,
       amplitude,
@@ -60,6 +83,8 @@ impl<F: Float> Processor<F> {
       note_pitch,
       pitch_bend,
       freq_mod,
+      pulse_width,
+      sync,
     } = inputs;
 
     signals[shape].if_updated(|value| {
@@ -78,7 +103,83 @@ impl<F: Float> Processor<F> {
     signals[note_pitch].if_updated(|value| self.osc.set_pitch_frequency(value));
     signals[pitch_bend].if_updated(|value| self.osc.set_pitch_bend(value));
     signals[freq_mod].if_updated(|value| self.osc.set_frequency_modulation(value));
+    signals[pulse_width].if_updated(|value| self.osc.set_pulse_width(value));
+
+    let sync = signals[sync].get();
+    if sync > F::zero() && self.last_sync <= F::zero() {
+      self.osc.sync(sync.min(F::one()));
+    }
+    self.last_sync = sync;
 
     signals[output].set(self.osc.generate());
+    signals[sync_output].set(self.osc.get_restart_position());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::osc::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+  use crate::program::SignalRef;
+
+  #[test]
+  fn hard_sync_from_another_oscillator() {
+    let (mut bench, (inputs, outputs)) = TestBench::<f32>::new(|builder| {
+      let inputs = [(); 5].map(|_| builder.signal());
+      (inputs, [(); 4].map(|_| builder.signal()))
+    });
+    let [zero, one, saw, master_pitch, slave_pitch] = inputs;
+    let [master_sync, slave_sync, master_output, slave_output] = outputs;
+    let globals = SynthGlobals::new();
+    bench.set(one, 1.0);
+    bench.set(saw, 2.0);
+    bench.set(master_pitch, 480.0);
+    bench.set(slave_pitch, 1584.0);
+
+    let block = |note_pitch: SignalRef, sync: SignalRef, output, sync_output| Block {
+      inputs: Inputs {
+        shape: saw,
+        amplitude: one,
+        amp_mod: zero,
+        octaves: zero,
+        semitones: zero,
+        cents: zero,
+        note_pitch,
+        pitch_bend: zero,
+        freq_mod: zero,
+        pulse_width: zero,
+        sync,
+      },
+      output,
+      sync_output,
+    };
+    let mut master = Processor::new(
+      48000.0,
+      block(master_pitch, zero, master_output, master_sync),
+    );
+    let mut slave = Processor::new(
+      48000.0,
+      block(slave_pitch, master_sync, slave_output, slave_sync),
+    );
+
+    let mut output = Vec::new();
+    let mut master_restarts = 0;
+    for _ in 0..2000 {
+      bench.step(|signals, program| {
+        master.process(signals, program, &globals);
+        slave.process(signals, program, &globals);
+      });
+      output.push(bench.get(slave_output));
+      if bench.get(master_sync) > 0.0 {
+        master_restarts += 1;
+        assert!(bench.get(slave_sync) > 0.0);
+      }
+    }
+
+    assert_eq!(master_restarts, 20);
+    for (a, b) in output[1000..1900].iter().zip(output[1100..].iter()) {
+      assert!((a - b).abs() < 1e-3);
+    }
   }
 }
@@ -0,0 +1,105 @@
+use kiro_synth_dsp::funcs::pan::constant_power_pan;
+
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub input: SignalRef,
+  /// Position between [-1.0, 1.0] from left to right
+  pub pan: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outputs {
+  pub left: SignalRef,
+  pub right: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub outputs: Outputs,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block,
+  left_gain: F,
+  right_gain: F,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(_sample_rate: F, block: Block) -> Self {
+    let (left_gain, right_gain) = constant_power_pan(F::zero());
+    Processor {
+      block,
+      left_gain,
+      right_gain,
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, _sample_rate: F) {}
+
+  pub fn reset(&mut self) {}
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { inputs, outputs } = self.block.clone();
+
+    signals[inputs.pan].if_updated(|value| {
+      let (left_gain, right_gain) = constant_power_pan(value);
+      self.left_gain = left_gain;
+      self.right_gain = right_gain;
+    });
+
+    let input = signals[inputs.input].get();
+    signals[outputs.left].set(input * self.left_gain);
+    signals[outputs.right].set(input * self.right_gain);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::blocks::pan::{Block, Inputs, Outputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  fn pan(position: f32) -> (f32, f32) {
+    let (mut bench, (inputs, outputs)) = TestBench::<f32>::new(|builder| {
+      let inputs = Inputs {
+        input: builder.signal(),
+        pan: builder.signal(),
+      };
+      let outputs = Outputs {
+        left: builder.signal(),
+        right: builder.signal(),
+      };
+      (inputs, outputs)
+    });
+    bench.set(inputs.input, 1.0);
+    bench.set(inputs.pan, position);
+
+    let (left, right) = (outputs.left, outputs.right);
+    let mut processor = Processor::new(44100.0, Block { inputs, outputs });
+    bench.step(|signals, program| processor.process(signals, program));
+    (bench.get(left), bench.get(right))
+  }
+
+  #[test]
+  fn center_is_minus_3db_on_both_channels() {
+    let (left, right) = pan(0.0);
+    let minus_3db = 0.5f32.sqrt();
+    assert!((left - minus_3db).abs() < 1e-6);
+    assert!((right - minus_3db).abs() < 1e-6);
+  }
+
+  #[test]
+  fn hard_left_routes_everything_to_the_left() {
+    let (left, right) = pan(-1.0);
+    assert!((left - 1.0).abs() < 1e-6);
+    assert!(right.abs() < 1e-6);
+  }
+}
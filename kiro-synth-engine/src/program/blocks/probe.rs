@@ -0,0 +1,98 @@
+use heapless::consts;
+use heapless::spsc::Queue;
+
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+pub type MaxProbeSamples = consts::U1024;
+
+/// Passes the input through while recording it, so it can be drained later (i.e. for a scope view)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub input: SignalRef,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block,
+  samples: Queue<F, MaxProbeSamples>,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(_sample_rate: F, block: Block) -> Self {
+    Processor {
+      block,
+      samples: Queue::new(),
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, _sample_rate: F) {}
+
+  /// The samples recorded are kept so they are not lost when the voice is retriggered
+  pub fn reset(&mut self) {}
+
+  /// The signal being recorded
+  pub fn input(&self) -> SignalRef {
+    self.block.input
+  }
+
+  /// Move the recorded samples, from the oldest to the newest, into the buffer
+  /// returning how many of them were written
+  pub fn drain(&mut self, buffer: &mut [F]) -> usize {
+    let mut count = 0;
+    while count < buffer.len() {
+      match self.samples.dequeue() {
+        Some(sample) => buffer[count] = sample,
+        None => break,
+      }
+      count += 1;
+    }
+    count
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { input, output } = self.block;
+    let value = signals[input].get();
+    // When nobody drains the samples the oldest ones are dropped to keep the latest
+    if let Err(value) = self.samples.enqueue(value) {
+      self.samples.dequeue();
+      self.samples.enqueue(value).ok();
+    }
+    signals[output].set(value);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use typenum::marker_traits::Unsigned;
+
+  use crate::program::blocks::probe::{Block, MaxProbeSamples, Processor};
+  use crate::program::blocks::TestBench;
+
+  #[test]
+  fn constant_input_fills_the_probe() {
+    let (mut bench, block) = TestBench::<f32>::new(|builder| Block {
+      input: builder.signal(),
+      output: builder.signal(),
+    });
+    bench.set(block.input, 0.25);
+
+    let output = block.output;
+    let mut processor = Processor::new(44100.0, block);
+    let max_samples = MaxProbeSamples::to_usize();
+    for _ in 0..max_samples + 10 {
+      bench.step(|signals, program| processor.process(signals, program));
+      assert!((bench.get(output) - 0.25).abs() < 1e-6);
+    }
+
+    let mut buffer = vec![0.0; max_samples * 2];
+    assert_eq!(processor.drain(&mut buffer), max_samples);
+    for sample in buffer[..max_samples].iter() {
+      assert!((sample - 0.25).abs() < 1e-6);
+    }
+    assert_eq!(processor.drain(&mut buffer), 0);
+  }
+}
@@ -0,0 +1,90 @@
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub a: SignalRef,
+  pub b: SignalRef,
+}
+
+/// Multiplies both inputs, giving the sum and difference of their frequencies
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor {
+  block: Block,
+}
+
+impl Processor {
+  pub fn new<F: Float>(_sample_rate: F, block: Block) -> Self {
+    Processor { block }
+  }
+
+  pub fn set_sample_rate<F: Float>(&mut self, _sample_rate: F) {}
+
+  pub fn reset(&mut self) {}
+
+  pub fn process<'a, F: Float>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { inputs, output } = self.block.clone();
+    let value = signals[inputs.a].get() * signals[inputs.b].get();
+    signals[output].set(value);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::blocks::ring_mod::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  const SAMPLE_RATE: f32 = 8000.0;
+
+  /// Amplitude of the frequency in the values using the Goertzel algorithm
+  fn amplitude(values: &[f32], freq: f32) -> f32 {
+    let coefficient = 2.0 * (2.0 * std::f32::consts::PI * freq / SAMPLE_RATE).cos();
+    let (mut s1, mut s2) = (0.0f32, 0.0f32);
+    for value in values {
+      let s0 = value + coefficient * s1 - s2;
+      s2 = s1;
+      s1 = s0;
+    }
+    let power = s1 * s1 + s2 * s2 - coefficient * s1 * s2;
+    2.0 * power.max(0.0).sqrt() / values.len() as f32
+  }
+
+  #[test]
+  fn produces_sum_and_difference_frequencies() {
+    let (mut bench, (inputs, output)) = TestBench::<f32>::new(|builder| {
+      let inputs = Inputs {
+        a: builder.signal(),
+        b: builder.signal(),
+      };
+      (inputs, builder.signal())
+    });
+
+    let (a, b) = (inputs.a, inputs.b);
+    let mut processor = Processor::new(SAMPLE_RATE, Block { inputs, output });
+    let sine = |freq: f32, index: usize| {
+      (2.0 * std::f32::consts::PI * freq * index as f32 / SAMPLE_RATE).sin()
+    };
+    let values: Vec<f32> = (0..8000)
+      .map(|index| {
+        bench.set(a, sine(300.0, index));
+        bench.set(b, sine(100.0, index));
+        bench.step(|signals, program| processor.process(signals, program));
+        bench.get(output)
+      })
+      .collect();
+
+    assert!((amplitude(&values, 200.0) - 0.5).abs() < 0.01);
+    assert!((amplitude(&values, 400.0) - 0.5).abs() < 0.01);
+    assert!(amplitude(&values, 100.0) < 0.01);
+    assert!(amplitude(&values, 300.0) < 0.01);
+  }
+}
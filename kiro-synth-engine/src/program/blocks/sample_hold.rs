@@ -0,0 +1,91 @@
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub input: SignalRef,
+  /// The input is latched when it rises above zero
+  pub trigger: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block,
+  held: F,
+  last_trigger: F,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(_sample_rate: F, block: Block) -> Self {
+    Processor {
+      block,
+      held: F::zero(),
+      last_trigger: F::zero(),
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, _sample_rate: F) {}
+
+  pub fn reset(&mut self) {
+    self.held = F::zero();
+    self.last_trigger = F::zero();
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Inputs { input, trigger } = self.block.inputs;
+
+    let trigger = signals[trigger].get();
+    if trigger > F::zero() && self.last_trigger <= F::zero() {
+      self.held = signals[input].get();
+    }
+    self.last_trigger = trigger;
+
+    signals[self.block.output].set(self.held);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::blocks::sample_hold::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  #[test]
+  fn latches_on_trigger() {
+    let (mut bench, (inputs, output)) = TestBench::<f32>::new(|builder| {
+      let inputs = Inputs {
+        input: builder.signal(),
+        trigger: builder.signal(),
+      };
+      (inputs, builder.signal())
+    });
+
+    let (input, trigger) = (inputs.input, inputs.trigger);
+    let mut processor = Processor::new(44100.0, Block { inputs, output });
+    let steps = [
+      (0.3, 0.0, 0.0),
+      (0.4, 1.0, 0.4),
+      (0.5, 1.0, 0.4),
+      (0.6, 0.0, 0.4),
+      (0.7, 1.0, 0.7),
+    ];
+    for (value, pulse, expected) in steps.iter() {
+      bench.set(input, *value);
+      bench.set(trigger, *pulse);
+      bench.step(|signals, program| processor.process(signals, program));
+      assert!((bench.get(output) - expected).abs() < 1e-6);
+    }
+
+    processor.reset();
+    bench.step(|signals, program| processor.process(signals, program));
+    assert!((bench.get(output) - 0.7).abs() < 1e-6);
+  }
+}
@@ -0,0 +1,204 @@
+use crate::float::Float;
+use crate::globals::SynthGlobals;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  /// Playback speed, 1 plays the sample as recorded and 2 one octave up in half the time
+  pub rate: SignalRef,
+  /// Start of the loop as a fraction of the sample length, from 0 to 1
+  pub loop_start: SignalRef,
+  /// End of the loop as a fraction of the sample length, there is no loop unless it is after the start
+  pub loop_end: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outputs {
+  pub output: SignalRef,
+  /// Raised when the end of the sample is reached
+  pub voice_off: SignalRef,
+}
+
+/// Plays back a sample from the globals at a variable rate
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  /// Index of the sample in the globals
+  pub sample: usize,
+  pub inputs: Inputs,
+  pub outputs: Outputs,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block,
+  inv_sample_rate: F,
+  /// Position in frames of the sample
+  position: F,
+  finished: bool,
+}
+
+impl<F: Float> Processor<F> {
+  /// Fastest playback rate, four octaves up
+  const MAX_RATE: f64 = 16.0;
+
+  pub fn new(sample_rate: F, block: Block) -> Self {
+    Processor {
+      block,
+      inv_sample_rate: sample_rate.recip(),
+      position: F::zero(),
+      finished: false,
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.inv_sample_rate = sample_rate.recip();
+  }
+
+  pub fn reset(&mut self) {
+    self.position = F::zero();
+    self.finished = false;
+  }
+
+  pub fn process<'a>(
+    &mut self,
+    signals: &mut SignalBus<'a, F>,
+    _program: &Program<F>,
+    synth_globals: &SynthGlobals<F>,
+  ) {
+    let Block {
+      sample,
+      inputs,
+      outputs,
+    } = self.block.clone();
+
+    let sample = match synth_globals.samples.sample(sample) {
+      Some(sample) if !self.finished => sample,
+      _ => {
+        signals[outputs.output].set(F::zero());
+        return;
+      }
+    };
+
+    signals[outputs.output].set(sample.interpolate(self.position));
+
+    let rate = signals[inputs.rate].get();
+    let rate = if rate.is_finite() {
+      num_traits::clamp(rate, F::zero(), F::val(Self::MAX_RATE))
+    } else {
+      F::zero()
+    };
+    self.position = self.position + rate * sample.sample_rate() * self.inv_sample_rate;
+
+    let len = F::val(sample.len());
+    let loop_start = signals[inputs.loop_start]
+      .get()
+      .max(F::zero())
+      .min(F::one())
+      * len;
+    let loop_end = signals[inputs.loop_end].get().max(F::zero()).min(F::one()) * len;
+    if loop_end > loop_start {
+      if self.position >= loop_end {
+        self.position = loop_start + (self.position - loop_start) % (loop_end - loop_start);
+      }
+    } else if self.position >= len {
+      self.finished = true;
+      signals[outputs.voice_off].set(F::one());
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::sampler::{Block, Inputs, Outputs, Processor};
+  use crate::program::blocks::TestBench;
+  use crate::samples::Sample;
+
+  /// Renders a 441 Hz sine of 0.1 seconds until the voice goes off
+  /// or for a second, returning the output
+  fn render(rate: f32, loop_points: (f32, f32)) -> Vec<f32> {
+    let sine: Vec<f32> = (0..4410)
+      .map(|index| (index as f32 * 2.0 * std::f32::consts::PI / 100.0).sin())
+      .collect();
+    let mut globals = SynthGlobals::new();
+    let sample = globals
+      .samples
+      .add("sine", Sample::new(&sine, 44100.0))
+      .unwrap();
+
+    let (mut bench, (inputs, outputs)) = TestBench::<f32>::new(|builder| {
+      let inputs = Inputs {
+        rate: builder.signal(),
+        loop_start: builder.signal(),
+        loop_end: builder.signal(),
+      };
+      let outputs = Outputs {
+        output: builder.signal(),
+        voice_off: builder.signal(),
+      };
+      (inputs, outputs)
+    });
+    bench.set(inputs.rate, rate);
+    bench.set(inputs.loop_start, loop_points.0);
+    bench.set(inputs.loop_end, loop_points.1);
+
+    let (output, voice_off) = (outputs.output, outputs.voice_off);
+    let block = Block {
+      sample,
+      inputs,
+      outputs,
+    };
+    let mut processor = Processor::new(44100.0, block);
+    let mut rendered = Vec::new();
+    while rendered.len() < 44100 && bench.get(voice_off) < 1.0 {
+      bench.step(|signals, program| processor.process(signals, program, &globals));
+      rendered.push(bench.get(output));
+    }
+    rendered
+  }
+
+  fn rising_crossings(output: &[f32]) -> usize {
+    output
+      .windows(2)
+      .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+      .count()
+  }
+
+  #[test]
+  fn double_rate_halves_the_duration_and_raises_an_octave() {
+    let original = render(1.0, (0.0, 0.0));
+    let doubled = render(2.0, (0.0, 0.0));
+
+    assert_eq!(original.len(), 4410);
+    assert_eq!(doubled.len(), 2205);
+
+    let original_crossings = rising_crossings(&original);
+    let doubled_crossings = rising_crossings(&doubled);
+    assert!(original_crossings >= 43);
+    assert!((original_crossings as isize - doubled_crossings as isize).abs() <= 1);
+  }
+
+  #[test]
+  fn loop_keeps_playing() {
+    let output = render(1.0, (0.5, 1.0));
+    assert_eq!(output.len(), 44100);
+    let end = &output[output.len() - 1000..];
+    assert!(end.iter().any(|value| *value > 0.9));
+  }
+  #[test]
+  fn wild_rates_are_limited() {
+    let output = render(f32::INFINITY, (0.5, 1.0));
+    assert!(output.iter().all(|value| value.abs() < 1e-6));
+
+    let output = render(1e30, (0.5, 1.0));
+    assert_eq!(output.len(), 44100);
+    assert!(output.iter().all(|value| value.is_finite()));
+
+    let output = render(f32::NAN, (0.0, 0.0));
+    assert_eq!(output.len(), 44100);
+  }
+}
@@ -0,0 +1,51 @@
+use kiro_synth_dsp::effects::shaper::{Shape, Shaper};
+
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub input: SignalRef,
+  pub drive: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub shape: Shape,
+  /// Shape at twice the sample rate to reduce aliasing
+  pub oversampling: bool,
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  shaper: Shaper<F>,
+  block: Block,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(_sample_rate: F, block: Block) -> Self {
+    let mut shaper = Shaper::new(block.shape);
+    shaper.set_oversampling(block.oversampling);
+    Processor { shaper, block }
+  }
+
+  pub fn set_sample_rate(&mut self, _sample_rate: F) {}
+
+  pub fn reset(&mut self) {
+    self.shaper.reset()
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Inputs { input, drive } = self.block.inputs;
+
+    signals[drive].if_updated(|value| self.shaper.set_drive(value));
+
+    let value = self.shaper.process(signals[input].get());
+    signals[self.block.output].set(value);
+  }
+}
@@ -0,0 +1,117 @@
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub input: SignalRef,
+  /// Seconds needed to rise by one unit, zero to follow the input immediately
+  pub rise: SignalRef,
+  /// Seconds needed to fall by one unit, zero to follow the input immediately
+  pub fall: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub output: SignalRef,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block,
+  sample_rate: F,
+  value: Option<F>,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(sample_rate: F, block: Block) -> Self {
+    Processor {
+      block,
+      sample_rate,
+      value: None,
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.sample_rate = sample_rate;
+  }
+
+  pub fn reset(&mut self) {
+    self.value = None;
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Inputs { input, rise, fall } = self.block.inputs;
+
+    let target = signals[input].get();
+    let value = match self.value {
+      Some(value) if target > value => {
+        let step = self.max_step(signals[rise].get());
+        (value + step).min(target)
+      }
+      Some(value) if target < value => {
+        let step = self.max_step(signals[fall].get());
+        (value - step).max(target)
+      }
+      _ => target,
+    };
+    self.value = Some(value);
+
+    signals[self.block.output].set(value);
+  }
+
+  fn max_step(&self, time: F) -> F {
+    if time > F::zero() {
+      (time * self.sample_rate).recip()
+    } else {
+      F::infinity()
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::blocks::slew::{Block, Inputs, Processor};
+  use crate::program::blocks::TestBench;
+
+  #[test]
+  fn asymmetric_rise_and_fall() {
+    let (mut bench, (inputs, output)) = TestBench::<f64>::new(|builder| {
+      let inputs = Inputs {
+        input: builder.signal(),
+        rise: builder.signal(),
+        fall: builder.signal(),
+      };
+      (inputs, builder.signal())
+    });
+    bench.set(inputs.rise, 0.01);
+    bench.set(inputs.fall, 0.04);
+
+    let input = inputs.input;
+    let mut processor = Processor::new(1000.0, Block { inputs, output });
+    let mut render = |value: f64, samples: usize| -> Vec<f64> {
+      (0..samples)
+        .map(|_| {
+          bench.set(input, value);
+          bench.step(|signals, program| processor.process(signals, program));
+          bench.get(output)
+        })
+        .collect()
+    };
+
+    assert!(render(0.0, 1)[0].abs() < 1e-9);
+
+    let rising = render(1.0, 12);
+    assert!((rising[0] - 0.1).abs() < 1e-9);
+    assert!((rising[4] - 0.5).abs() < 1e-9);
+    assert!((rising[11] - 1.0).abs() < 1e-9);
+
+    let falling = render(0.0, 42);
+    assert!((falling[0] - 0.975).abs() < 1e-9);
+    assert!((falling[19] - 0.5).abs() < 1e-9);
+    assert!(falling[41].abs() < 1e-9);
+  }
+}
@@ -0,0 +1,66 @@
+use kiro_synth_dsp::filters::svf::StateVariableFilter;
+
+use crate::float::Float;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub input: SignalRef,
+  pub cutoff: SignalRef,
+  pub resonance: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outputs {
+  pub lowpass: SignalRef,
+  pub highpass: SignalRef,
+  pub bandpass: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  pub inputs: Inputs,
+  pub outputs: Outputs,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  svf: StateVariableFilter<F>,
+  block: Block,
+}
+
+impl<F: Float> Processor<F> {
+  /// Cutoff frequency until the cutoff signal is updated
+  const DEFAULT_CUTOFF: f64 = 1000.0;
+
+  pub fn new(sample_rate: F, block: Block) -> Self {
+    Processor {
+      svf: StateVariableFilter::new(sample_rate, F::val(Self::DEFAULT_CUTOFF)),
+      block,
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.svf.set_sample_rate(sample_rate)
+  }
+
+  pub fn reset(&mut self) {
+    self.svf.reset()
+  }
+
+  pub fn process<'a>(&mut self, signals: &mut SignalBus<'a, F>, _program: &Program<F>) {
+    let Block { inputs, outputs } = self.block.clone();
+
+    signals[inputs.cutoff].if_updated(|value| self.svf.set_cutoff(value));
+    signals[inputs.resonance].if_updated(|value| self.svf.set_resonance(value));
+
+    let output = self.svf.process(signals[inputs.input].get());
+    signals[outputs.lowpass].set(output.lowpass);
+    signals[outputs.highpass].set(output.highpass);
+    signals[outputs.bandpass].set(output.bandpass);
+  }
+}
@@ -0,0 +1,138 @@
+use kiro_synth_dsp::oscillators::clamp_modulo;
+
+use crate::float::Float;
+use crate::globals::SynthGlobals;
+use crate::program::{Program, SignalRef};
+use crate::signal::SignalBus;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inputs {
+  pub freq: SignalRef,
+  /// Position between the frames of the table, from 0 to 1
+  pub position: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outputs {
+  /// Phase of the cycle, from 0 to 1
+  pub phase: SignalRef,
+  pub output: SignalRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+  /// Index of the table in the globals
+  pub table: usize,
+  pub inputs: Inputs,
+  pub outputs: Outputs,
+}
+
+#[derive(Debug)]
+pub(crate) struct Processor<F: Float> {
+  block: Block,
+  inv_sample_rate: F,
+  modulo: F,
+}
+
+impl<F: Float> Processor<F> {
+  pub fn new(sample_rate: F, block: Block) -> Self {
+    Processor {
+      block,
+      inv_sample_rate: sample_rate.recip(),
+      modulo: F::zero(),
+    }
+  }
+
+  pub fn set_sample_rate(&mut self, sample_rate: F) {
+    self.inv_sample_rate = sample_rate.recip();
+  }
+
+  pub fn reset(&mut self) {
+    self.modulo = F::zero();
+  }
+
+  pub fn process<'a>(
+    &mut self,
+    signals: &mut SignalBus<'a, F>,
+    _program: &Program<F>,
+    synth_globals: &SynthGlobals<F>,
+  ) {
+    let Block {
+      table,
+      inputs,
+      outputs,
+    } = self.block.clone();
+
+    let phase_inc = signals[inputs.freq].get() * self.inv_sample_rate;
+    let position = signals[inputs.position].get();
+    let value = synth_globals
+      .wavetables
+      .table(table)
+      .map(|table| table.sample(position, self.modulo, phase_inc))
+      .unwrap_or_else(F::zero);
+
+    signals[outputs.phase].set(self.modulo);
+    signals[outputs.output].set(value);
+
+    self.modulo = clamp_modulo(self.modulo + phase_inc);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::wavetable_osc::{Block, Inputs, Outputs, Processor};
+  use crate::program::blocks::TestBench;
+  use crate::waveforms::WaveTable;
+
+  #[test]
+  fn renders_table_at_frequency() {
+    let sine: Vec<f32> = (0..64)
+      .map(|index| (index as f32 * 2.0 * std::f32::consts::PI / 64.0).sin())
+      .collect();
+    let mut table = WaveTable::new();
+    table.push_frame(&sine).unwrap();
+    let mut globals = SynthGlobals::new();
+    let table = globals.wavetables.add("sine", table).unwrap();
+
+    let (mut bench, (inputs, outputs)) = TestBench::<f32>::new(|builder| {
+      let inputs = Inputs {
+        freq: builder.signal(),
+        position: builder.signal(),
+      };
+      let outputs = Outputs {
+        phase: builder.signal(),
+        output: builder.signal(),
+      };
+      (inputs, outputs)
+    });
+    bench.set(inputs.freq, 441.0);
+
+    let output = outputs.output;
+    let block = Block {
+      table,
+      inputs,
+      outputs,
+    };
+    let mut processor = Processor::new(44100.0, block);
+
+    let mut rising_crossings = Vec::new();
+    let mut previous = 0.0;
+    for index in 0..1000 {
+      bench.step(|signals, program| processor.process(signals, program, &globals));
+      let value = bench.get(output);
+      if previous < 0.0 && value >= 0.0 {
+        rising_crossings.push(index);
+      }
+      previous = value;
+    }
+
+    assert!(rising_crossings.len() >= 9);
+    for pair in rising_crossings.windows(2) {
+      assert_eq!(pair[1] - pair[0], 100);
+    }
+  }
+}
@@ -1,12 +1,13 @@
 use heapless::Vec;
+use typenum::marker_traits::Unsigned;
 
 use crate::float::Float;
 use crate::program::blocks::expr::{self, ExprBuilder, OpRef};
 use crate::program::modulations::Modulations;
 use crate::program::references::{BlockRef, ParamRef, SignalRef, SignalRefs, SourceRef};
 use crate::program::{
-  Block, MaxBlocks, MaxParams, MaxSources, Param, ParamBlock, ParamValues, Program, Source,
-  VoiceBlock,
+  Block, MaxBlocks, MaxOutputChannels, MaxParams, MaxSignals, MaxSources, Param, ParamBlock,
+  ParamValues, Program, Source, VoiceBlock,
 };
 use crate::signal::Signal;
 
@@ -18,23 +19,45 @@ pub struct ProgramBuilder<'a, F: Float> {
   params: Vec<Param<'a, F>, MaxParams>,
   blocks: Vec<Block<F>, MaxBlocks>,
   modulations: Modulations<F>,
+  signal_names: Vec<(&'a str, SignalRef), MaxSignals>,
 }
 
 impl<'a, F: Float> ProgramBuilder<'a, F> {
   pub fn new() -> Self {
+    Self::with_output_channels(2)
+  }
+
+  /// Program for voices with the given number of output channels, at least the left and right ones
+  pub fn with_output_channels(channels: usize) -> Self {
+    assert!(
+      channels >= 2 && channels <= MaxOutputChannels::to_usize(),
+      "Invalid number of output channels: {}",
+      channels
+    );
+
     let mut signal_refs = SignalRefs::new();
 
-    let voice = VoiceBlock {
+    let mut voice = VoiceBlock {
       key: signal_refs.create(),
       velocity: signal_refs.create(),
       note_pitch: signal_refs.create(),
+      pitch_bend: signal_refs.create(),
+      aftertouch: signal_refs.create(),
+      poly_pressure: signal_refs.create(),
       gate: signal_refs.create(),
       trigger: signal_refs.create(),
       off: signal_refs.create(),
       output_left: signal_refs.create(),
       output_right: signal_refs.create(),
+      output_channels: Vec::new(),
     };
 
+    voice.output_channels.push(voice.output_left).unwrap();
+    voice.output_channels.push(voice.output_right).unwrap();
+    for _ in 2..channels {
+      voice.output_channels.push(signal_refs.create()).unwrap();
+    }
+
     ProgramBuilder {
       signal_refs,
       voice,
@@ -42,6 +65,7 @@ impl<'a, F: Float> ProgramBuilder<'a, F> {
       params: Vec::new(),
       blocks: Vec::new(),
       modulations: Modulations::default(),
+      signal_names: Vec::new(),
     }
   }
 
@@ -72,6 +96,12 @@ impl<'a, F: Float> ProgramBuilder<'a, F> {
   }
 
   pub fn param(&mut self, id: &'a str, values: ParamValues<F>) -> ParamBlock {
+    assert!(
+      values.min <= values.max,
+      "Invalid range for param {}: min > max",
+      id
+    );
+
     let out_signal_ref = self.signal_refs.create();
     let mod_signal_ref = self.signal_refs.create();
 
@@ -97,6 +127,18 @@ impl<'a, F: Float> ProgramBuilder<'a, F> {
     self.signal_refs.create()
   }
 
+  /// Give a name to a signal so it can be looked up later, replacing any previous one
+  pub fn name_signal(&mut self, signal: SignalRef, name: &'a str) {
+    match self
+      .signal_names
+      .iter_mut()
+      .find(|(_, named_signal)| *named_signal == signal)
+    {
+      Some(signal_name) => signal_name.0 = name,
+      None => self.signal_names.push((name, signal)).unwrap(),
+    }
+  }
+
   pub fn modulation<P: Into<ParamRef>>(&mut self, param: P, source_ref: SourceRef, amount: F) {
     self
       .modulations
@@ -122,6 +164,18 @@ impl<'a, F: Float> ProgramBuilder<'a, F> {
     block_ref
   }
 
+  /// Output the signals into the voice output channels in the same order
+  pub fn out_channels(&mut self, channels: &[SignalRef]) -> BlockRef {
+    assert!(
+      channels.len() <= self.voice.output_channels.len(),
+      "There are more signals than output channels"
+    );
+    let block_ref = BlockRef(self.blocks.len());
+    let channels = Vec::from_slice(channels).unwrap();
+    self.blocks.push(Block::OutChannels(channels)).unwrap();
+    block_ref
+  }
+
   pub fn build(self) -> Program<'a, F> {
     Program {
       signals_count: self.signal_refs.count(),
@@ -130,6 +184,7 @@ impl<'a, F: Float> ProgramBuilder<'a, F> {
       params: self.params,
       blocks: self.blocks,
       modulations: self.modulations,
+      signal_names: self.signal_names,
     }
   }
 }
@@ -3,7 +3,8 @@ pub mod builder;
 pub mod modulations;
 pub mod references;
 
-use std::ops::{Deref, DerefMut};
+use core::fmt::Write;
+use core::ops::{Deref, DerefMut};
 
 use heapless::consts;
 use heapless::Vec;
@@ -21,20 +22,47 @@ pub type MaxSources = consts::U32;
 pub type MaxModulations = consts::U4;
 pub type MaxParams = consts::U128;
 pub type MaxBlocks = consts::U128;
+pub type MaxOutputChannels = consts::U8;
+pub type MaxParamDisplayLength = consts::U32;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Source<'a> {
   pub id: &'a str,
   pub signal: SignalRef,
 }
 
-#[derive(Debug, Clone)]
+/// Unit of the values of a parameter, to display them
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParamUnit {
+  Hertz,
+  /// The value is a linear gain that is displayed in decibels
+  Decibels,
+  Seconds,
+  /// The value goes from 0 to 1 and is displayed from 0% to 100%
+  Percent,
+  Raw,
+}
+
+/// How the range of a parameter is presented, for example by a knob
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParamScale {
+  Linear,
+  Logarithmic,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParamValues<F: Float> {
   pub initial_value: F,
   pub origin: F,
   pub min: F,
   pub max: F,
   pub resolution: F,
+  pub unit: ParamUnit,
+  pub display_scale: ParamScale,
 }
 
 impl<F: Float> ParamValues<F> {
@@ -44,9 +72,79 @@ impl<F: Float> ParamValues<F> {
       ..self
     }
   }
+
+  pub fn with_unit(self, unit: ParamUnit) -> Self {
+    Self { unit, ..self }
+  }
+
+  pub fn with_display_scale(self, display_scale: ParamScale) -> Self {
+    Self {
+      display_scale,
+      ..self
+    }
+  }
+
+  /// Clamp a value into the `[min, max]` range of the parameter
+  pub fn clamp(&self, value: F) -> F {
+    value.max(self.min).min(self.max)
+  }
+
+  /// Format a value with its unit and as many decimals as the resolution needs (up to 3).
+  /// It does not affect how the value is processed.
+  pub fn format(&self, value: F) -> heapless::String<MaxParamDisplayLength> {
+    let mut text = heapless::String::new();
+    let value = self.clamp(value);
+    let result = match self.unit {
+      ParamUnit::Hertz => write!(
+        text,
+        "{:.*} Hz",
+        self.precision(F::one()),
+        value.to_f64().unwrap()
+      ),
+      ParamUnit::Decibels if value <= F::zero() => write!(text, "-inf dB"),
+      ParamUnit::Decibels => {
+        let decibels = F::val(20.0) * value.log10();
+        write!(text, "{:.1} dB", decibels.to_f64().unwrap())
+      }
+      ParamUnit::Seconds => write!(
+        text,
+        "{:.*} s",
+        self.precision(F::one()),
+        value.to_f64().unwrap()
+      ),
+      ParamUnit::Percent => {
+        let percent = value * F::val(100.0);
+        write!(
+          text,
+          "{:.*}%",
+          self.precision(F::val(100.0)),
+          percent.to_f64().unwrap()
+        )
+      }
+      ParamUnit::Raw => write!(
+        text,
+        "{:.*}",
+        self.precision(F::one()),
+        value.to_f64().unwrap()
+      ),
+    };
+    // a value too long for the capacity is left incomplete
+    result.ok();
+    text
+  }
+
+  fn precision(&self, scale: F) -> usize {
+    let step = (self.resolution * scale).max(F::val(0.001));
+    (step.log10().ceil().neg())
+      .max(F::zero())
+      .min(F::val(3.0))
+      .to_usize()
+      .unwrap_or(0)
+  }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Param<'a, F: Float> {
   pub id: &'a str,
   pub values: ParamValues<F>,
@@ -55,54 +153,119 @@ pub struct Param<'a, F: Float> {
   pub mod_signal_ref: SignalRef,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParamBlock {
   pub reference: ParamRef,
   pub out_signal_ref: SignalRef,
   pub mod_signal_ref: SignalRef,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub enum Block<F: Float> {
-  Const { value: F, signal: SignalRef },
+  Const {
+    value: F,
+    signal: SignalRef,
+  },
 
   Param(ParamBlock),
 
+  AmpMod(amp_mod::Block),
+
+  Comb(comb::Block),
+
+  Crush(crush::Block),
+
   DCA(dca::Block),
 
+  DcBlocker(dc_blocker::Block),
+
+  Delay(delay::Block),
+
   EG(envgen::Block),
 
+  EnvFollower(env_follower::Block),
+
   Expr(expr::Block<F>),
 
   Filter(filter::Block),
 
+  FmOperator(fm_operator::Block),
+
   Lfo(lfo::Block),
 
+  MidSide(mid_side::Block),
+
+  MidiCc(midi_cc::Block<F>),
+
+  Noise(noise::Block),
+
   Osc(osc::Block),
 
-  Out { left: SignalRef, right: SignalRef },
+  Pan(pan::Block),
+
+  Probe(probe::Block),
+
+  RingMod(ring_mod::Block),
+
+  SampleHold(sample_hold::Block),
+
+  Sampler(sampler::Block),
+
+  Shaper(shaper::Block),
+
+  Slew(slew::Block),
+
+  Svf(svf::Block),
+
+  WaveTableOsc(wavetable_osc::Block),
+
+  Out {
+    left: SignalRef,
+    right: SignalRef,
+  },
+
+  /// Routes every signal to the output channel in the same position
+  OutChannels(Vec<SignalRef, MaxOutputChannels>),
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct VoiceBlock {
   pub key: SignalRef,
   pub velocity: SignalRef,
   pub note_pitch: SignalRef,
+  /// Channel pitch bend in semitones, already applied to the note pitch
+  pub pitch_bend: SignalRef,
+  /// Channel pressure, shared by all the voices
+  pub aftertouch: SignalRef,
+  /// Pressure of the key of the voice
+  pub poly_pressure: SignalRef,
   pub gate: SignalRef,
   pub trigger: SignalRef,
   pub off: SignalRef,
   pub output_left: SignalRef,
   pub output_right: SignalRef,
+  /// Output signals of all the channels, the first two are the left and right ones
+  pub output_channels: Vec<SignalRef, MaxOutputChannels>,
 }
 
+/// With the `serialize` feature it can be saved and loaded with serde,
+/// the ids and names are borrowed from the serialized data
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Program<'a, F: Float> {
   signals_count: usize,
   voice: VoiceBlock,
+  #[cfg_attr(feature = "serialize", serde(borrow))]
   sources: Vec<Source<'a>, MaxSources>,
+  #[cfg_attr(feature = "serialize", serde(borrow))]
   params: Vec<Param<'a, F>, MaxParams>,
   blocks: Vec<Block<F>, MaxBlocks>,
   modulations: Modulations<F>,
+  #[cfg_attr(feature = "serialize", serde(borrow))]
+  signal_names: Vec<(&'a str, SignalRef), MaxSignals>,
 }
 
 impl<'a, F: Float> Program<'a, F> {
@@ -179,7 +342,7 @@ impl<'a, F: Float> Program<'a, F> {
     self.modulations.delete(param_ref, source_ref)
   }
 
-  pub fn get_param_modulations(&self, param_ref: ParamRef) -> modulations::Iter<F> {
+  pub fn get_param_modulations(&self, param_ref: ParamRef) -> modulations::Iter<'_, F> {
     self.modulations.get_param_modulations(param_ref)
   }
 
@@ -209,6 +372,196 @@ impl<'a, F: Float> Program<'a, F> {
   }
 
   pub fn get_blocks(&self) -> &[Block<F>] {
-    &*self.blocks
+    &self.blocks
+  }
+
+  /// The signal with the given name, if any was named like that when building the program
+  pub fn get_signal_by_name(&self, name: &str) -> Option<SignalRef> {
+    self
+      .signal_names
+      .iter()
+      .find(|(signal_name, _)| *signal_name == name)
+      .map(|(_, signal)| *signal)
+  }
+
+  pub fn get_signal_name(&self, signal: SignalRef) -> Option<&'a str> {
+    self
+      .signal_names
+      .iter()
+      .find(|(_, named_signal)| *named_signal == signal)
+      .map(|(name, _)| *name)
+  }
+
+  /// The names given to the signals when building the program
+  pub fn signal_names(&self) -> impl Iterator<Item = (&'a str, SignalRef)> + '_ {
+    self.signal_names.iter().cloned()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::program::{ParamScale, ParamUnit, ParamValues, ProgramBuilder};
+
+  fn values(min: f32, max: f32, resolution: f32, unit: ParamUnit) -> ParamValues<f32> {
+    ParamValues {
+      initial_value: min,
+      origin: min,
+      min,
+      max,
+      resolution,
+      unit,
+      display_scale: ParamScale::Linear,
+    }
+  }
+
+  #[test]
+  fn clamp() {
+    let gain = values(0.0, 1.0, 0.01, ParamUnit::Raw);
+    assert!((gain.clamp(1.5) - 1.0).abs() < 1e-6);
+    assert!(gain.clamp(-0.5).abs() < 1e-6);
+    assert!((gain.clamp(0.25) - 0.25).abs() < 1e-6);
+  }
+
+  #[test]
+  #[should_panic(expected = "Invalid range for param gain: min > max")]
+  fn inverted_range_is_rejected_when_creating_the_param() {
+    let mut builder = ProgramBuilder::new();
+    builder.param("gain", values(1.0, 0.0, 0.01, ParamUnit::Raw));
+  }
+
+  #[test]
+  fn format_decibels() {
+    let gain = values(0.0, 1.0, 0.01, ParamUnit::Decibels);
+    assert_eq!(gain.format(0.5).as_str(), "-6.0 dB");
+    assert_eq!(gain.format(1.0).as_str(), "0.0 dB");
+    assert_eq!(gain.format(0.0).as_str(), "-inf dB");
+    // values out of the range are clamped
+    assert_eq!(gain.format(2.0).as_str(), "0.0 dB");
+  }
+
+  #[test]
+  fn format_with_the_precision_of_the_resolution() {
+    let freq = values(20.0, 20000.0, 10.0, ParamUnit::Hertz);
+    assert_eq!(freq.format(440.0).as_str(), "440 Hz");
+    let time = values(0.0, 10.0, 0.01, ParamUnit::Seconds);
+    assert_eq!(time.format(0.25).as_str(), "0.25 s");
+    let amount = values(0.0, 1.0, 0.01, ParamUnit::Percent);
+    assert_eq!(amount.format(0.5).as_str(), "50%");
+    let raw = values(-1.0, 1.0, f32::EPSILON, ParamUnit::Raw);
+    assert_eq!(raw.format(-0.5).as_str(), "-0.500");
+  }
+}
+
+#[cfg(all(test, feature = "serialize"))]
+mod serialize_tests {
+  use kiro_synth_dsp::effects::shaper::Shape;
+
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::{lfo, noise, shaper};
+  use crate::program::{Block, ParamScale, ParamUnit, ParamValues, Program, ProgramBuilder};
+  use crate::voice::Voice;
+
+  fn program<'a>() -> Program<'a, f32> {
+    let mut builder = ProgramBuilder::new();
+    let drive = builder.param(
+      "drive",
+      ParamValues {
+        initial_value: 2.0,
+        origin: 1.0,
+        min: 0.1,
+        max: 10.0,
+        resolution: 0.1,
+        unit: ParamUnit::Raw,
+        display_scale: ParamScale::Linear,
+      },
+    );
+    let shape = builder.const_zero();
+    let rate = builder.const_value(3.3);
+    let (phase, depth, sync, retrigger) = (
+      builder.const_zero(),
+      builder.const_one(),
+      builder.const_zero(),
+      builder.const_one(),
+    );
+    let lfo_output = builder.signal();
+    builder.block(Block::Lfo(lfo::Block {
+      inputs: lfo::Inputs {
+        shape,
+        rate,
+        phase,
+        depth,
+        sync,
+        retrigger,
+      },
+      output: lfo_output,
+    }));
+    let lfo_source = builder.source("lfo", lfo_output);
+    builder.modulation(&drive, lfo_source, 0.7);
+
+    let kind = builder.const_one();
+    let noise_output = builder.signal();
+    builder.block(Block::Noise(noise::Block {
+      inputs: noise::Inputs { kind },
+      output: noise_output,
+    }));
+    let voice = builder.voice().clone();
+    let expr = builder.expr(|expr| expr.mul_signals(noise_output, voice.velocity));
+    let expr_output = expr.output;
+    builder.block(Block::Expr(expr));
+    builder.name_signal(expr_output, "noise");
+
+    let output = builder.signal();
+    builder.block(Block::Shaper(shaper::Block {
+      shape: Shape::SoftClip,
+      oversampling: true,
+      inputs: shaper::Inputs {
+        input: expr_output,
+        drive: drive.out_signal_ref,
+      },
+      output,
+    }));
+    builder.out(output, output);
+    builder.build()
+  }
+
+  fn render(program: &mut Program<f32>) -> Vec<(f32, f32)> {
+    let globals = SynthGlobals::new();
+    let mut voice = Voice::new(44100.0, program);
+    voice.note_on(program, 60, 261.626, 0.8);
+    (0..256)
+      .map(|_| {
+        voice.process(program, &globals);
+        voice.output(program)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn json_round_trip() {
+    let mut program = program();
+    let json = serde_json::to_string(&program).unwrap();
+    // the program is moved by value through the serde frames, in debug builds
+    // it needs more stack than the test threads have, but not more than a main thread
+    let mut loaded: Program<f32> = std::thread::scope(|scope| {
+      std::thread::Builder::new()
+        .stack_size(8 * 1024 * 1024)
+        .spawn_scoped(scope, || serde_json::from_str(&json).unwrap())
+        .unwrap()
+        .join()
+        .unwrap()
+    });
+
+    assert_eq!(loaded.get_blocks(), program.get_blocks());
+    assert_eq!(loaded.get_params(), program.get_params());
+    assert_eq!(loaded.voice(), program.voice());
+    assert_eq!(
+      loaded.get_signal_by_name("noise"),
+      program.get_signal_by_name("noise")
+    );
+    assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+
+    let expected = render(&mut program);
+    assert!(expected.iter().any(|(left, _)| left.abs() > 1e-3));
+    assert_eq!(render(&mut loaded), expected);
   }
 }
@@ -1,4 +1,4 @@
-use std::ops::{Index, IndexMut};
+use core::ops::{Index, IndexMut};
 
 use generic_array::typenum::consts;
 use generic_array::typenum::marker_traits::Unsigned;
@@ -18,6 +18,7 @@ pub enum Error {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Modulation<F: Float> {
   pub source_ref: SourceRef,
   pub amount: F,
@@ -56,7 +57,7 @@ impl<F: Float> Default for Modulations<F> {
   }
 }
 
-impl<'a, F: Float> Modulations<F> {
+impl<F: Float> Modulations<F> {
   fn find(&mut self, param_ref: ParamRef, source_ref: SourceRef) -> Option<(usize, usize)> {
     let param_index: usize = param_ref.into();
     let mut head = self.heads[param_index];
@@ -113,27 +114,19 @@ impl<'a, F: Float> Modulations<F> {
   }
 
   pub fn delete(&mut self, param_ref: ParamRef, source_ref: SourceRef) -> Result<(), Error> {
-    match self.find(param_ref, source_ref) {
-      Some((prev, head)) => {
-        let next = self.pool.get(head).next;
-        match prev {
-          NIL => {
-            let param_index: usize = param_ref.into();
-            self.heads[param_index] = next;
-          }
-          _ => {
-            let prev_node = self.pool.get_mut(prev);
-            prev_node.next = next;
-          }
+    if let Some((prev, head)) = self.find(param_ref, source_ref) {
+      let next = self.pool.get(head).next;
+      match prev {
+        NIL => {
+          let param_index: usize = param_ref.into();
+          self.heads[param_index] = next;
+        }
+        _ => {
+          let prev_node = self.pool.get_mut(prev);
+          prev_node.next = next;
         }
-        self.pool.free(head);
-      }
-      None => {
-        println!(
-          "Can not delete the modulation {:?} -> {:?}",
-          source_ref, param_ref
-        );
       }
+      self.pool.free(head);
     }
     Ok(())
   }
@@ -149,7 +142,7 @@ impl<'a, F: Float> Modulations<F> {
   //   }
   // }
 
-  pub fn get_param_modulations(&self, param_ref: ParamRef) -> Iter<F> {
+  pub fn get_param_modulations(&self, param_ref: ParamRef) -> Iter<'_, F> {
     let param_index: usize = param_ref.into();
     let head = self.heads[param_index];
     Iter {
@@ -159,6 +152,41 @@ impl<'a, F: Float> Modulations<F> {
   }
 }
 
+/// Serialized as the list of modulations of every parameter
+#[cfg(feature = "serialize")]
+impl<F: Float + serde::Serialize> serde::Serialize for Modulations<F> {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeSeq;
+
+    let mut seq = serializer.serialize_seq(None)?;
+    for param_index in 0..MaxParams::to_usize() {
+      let param_ref = ParamRef::new(param_index);
+      // the list is built pushing to the head, so it is stored backwards to keep the order when loading
+      let modulations: alloc::vec::Vec<&Modulation<F>> =
+        self.get_param_modulations(param_ref).collect();
+      for modulation in modulations.into_iter().rev() {
+        seq.serialize_element(&(param_ref, modulation))?;
+      }
+    }
+    seq.end()
+  }
+}
+
+#[cfg(feature = "serialize")]
+impl<'de, F: Float + serde::Deserialize<'de>> serde::Deserialize<'de> for Modulations<F> {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let entries: alloc::vec::Vec<(ParamRef, Modulation<F>)> =
+      serde::Deserialize::deserialize(deserializer)?;
+    let mut modulations = Modulations::default();
+    for (param_ref, modulation) in entries {
+      modulations
+        .push(param_ref, modulation.source_ref, modulation.amount)
+        .map_err(|_| serde::de::Error::custom("too many modulations"))?;
+    }
+    Ok(modulations)
+  }
+}
+
 #[derive(Debug)]
 pub struct Iter<'a, F: Float> {
   pool: &'a ModulationsPool<F>,
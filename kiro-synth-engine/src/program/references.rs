@@ -1,15 +1,20 @@
+// the Hash32 derive implements the trait inside of a const
+#![allow(non_local_definitions)]
+
 use crate::program::ParamBlock;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash32, Copy, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct SignalRef(pub(crate) usize);
 
-impl Into<usize> for SignalRef {
-  fn into(self) -> usize {
-    self.0
+impl From<SignalRef> for usize {
+  fn from(reference: SignalRef) -> Self {
+    reference.0
   }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash32, Copy)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct SourceRef(pub(crate) usize);
 
 impl SourceRef {
@@ -18,13 +23,14 @@ impl SourceRef {
   }
 }
 
-impl Into<usize> for SourceRef {
-  fn into(self) -> usize {
-    self.0
+impl From<SourceRef> for usize {
+  fn from(reference: SourceRef) -> Self {
+    reference.0
   }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash32, Copy)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParamRef(pub(crate) usize);
 
 impl ParamRef {
@@ -45,13 +51,14 @@ impl From<&ParamBlock> for ParamRef {
   }
 }
 
-impl Into<usize> for ParamRef {
-  fn into(self) -> usize {
-    self.0
+impl From<ParamRef> for usize {
+  fn from(reference: ParamRef) -> Self {
+    reference.0
   }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockRef(pub(crate) usize);
 
 #[derive(Default)]
@@ -0,0 +1,115 @@
+use alloc::vec::Vec;
+
+use heapless::consts;
+
+use kiro_synth_dsp::funcs::interpolation::cubic_interpolation;
+
+use crate::float::Float;
+
+type MaxSamples = consts::U16;
+
+/// Recorded audio for the samplers to play back
+#[derive(Debug, Clone, Default)]
+pub struct Sample<F: Float> {
+  data: Vec<F>,
+  sample_rate: F,
+}
+
+impl<F: Float> Sample<F> {
+  pub fn new(data: &[F], sample_rate: F) -> Self {
+    Sample {
+      data: data.to_vec(),
+      sample_rate,
+    }
+  }
+
+  /// Number of frames
+  pub fn len(&self) -> usize {
+    self.data.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.data.is_empty()
+  }
+
+  /// Sample rate it was recorded with
+  pub fn sample_rate(&self) -> F {
+    self.sample_rate
+  }
+
+  /// Get the value at a fractional frame position, silent outside of the sample
+  pub fn interpolate(&self, position: F) -> F {
+    if position.is_nan() {
+      return F::zero();
+    }
+    // it is silent out of the data, so the clamping avoids overflows when taking the neighbours
+    let position = num_traits::clamp(position, F::val(-2.0), F::val(self.data.len() + 2));
+    let index = position.floor();
+    let fraction = position - index;
+    let index = index.to_isize().unwrap_or(0);
+    cubic_interpolation(
+      self.frame(index - 1),
+      self.frame(index),
+      self.frame(index + 1),
+      self.frame(index + 2),
+      fraction,
+    )
+  }
+
+  fn frame(&self, index: isize) -> F {
+    if index < 0 {
+      F::zero()
+    } else {
+      self
+        .data
+        .get(index as usize)
+        .cloned()
+        .unwrap_or_else(F::zero)
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Samples<F: Float>(heapless::Vec<(&'static str, Sample<F>), MaxSamples>);
+
+impl<F: Float> Samples<F> {
+  pub fn new() -> Self {
+    Samples(heapless::Vec::new())
+  }
+
+  /// Adds a sample returning its index, or none if there is no more space
+  pub fn add(&mut self, name: &'static str, sample: Sample<F>) -> Option<usize> {
+    self.0.push((name, sample)).ok()?;
+    Some(self.0.len() - 1)
+  }
+
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  pub fn name(&self, index: usize) -> &'static str {
+    self.0[index].0
+  }
+
+  pub fn sample(&self, index: usize) -> Option<&Sample<F>> {
+    self.0.get(index).map(|(_, sample)| sample)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::samples::Sample;
+
+  #[test]
+  fn silent_outside_of_the_sample() {
+    let sample = Sample::new(&[1.0f32, 1.0, 1.0, 1.0], 44100.0);
+    assert!((sample.interpolate(1.5) - 1.0).abs() < 1e-6);
+    for position in &[-10.0, 100.0, f32::INFINITY, f32::NEG_INFINITY, f32::NAN] {
+      assert!(sample.interpolate(*position).abs() < 1e-6);
+    }
+  }
+}
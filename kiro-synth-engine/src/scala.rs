@@ -0,0 +1,430 @@
+use heapless::consts;
+use heapless::Vec;
+use typenum::marker_traits::Unsigned;
+
+use crate::float::Float;
+use crate::tuning::Tuning;
+
+pub type MaxScaleNotes = consts::U128;
+pub type MaxMappingSize = consts::U128;
+
+type Keys = consts::U128;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Error {
+  /// The scale ends before the line with the description
+  MissingDescription,
+
+  /// The scale ends before the line with the number of notes
+  MissingNoteCount,
+
+  /// The line does not contain a valid number of notes
+  InvalidNoteCount(usize),
+
+  /// The scale has more notes than the supported
+  TooManyNotes(usize),
+
+  /// The line does not contain a valid pitch in cents or as a ratio
+  InvalidPitch(usize),
+
+  /// The scale ends before all the notes declared were found
+  MissingPitches { expected: usize, found: usize },
+
+  /// The keyboard mapping ends before all the required header values were found
+  MissingMappingValue,
+
+  /// The line of the keyboard mapping does not contain a valid value
+  InvalidMappingValue(usize),
+
+  /// The reference key of the keyboard mapping is not mapped to any degree
+  UnmappedReferenceKey(u8),
+}
+
+/// Scale from a Scala `.scl` file
+#[derive(Debug, Clone)]
+pub struct Scale<F: Float> {
+  /// Ratios for every degree after the first one, the last one is the period of the scale
+  ratios: Vec<F, MaxScaleNotes>,
+}
+
+impl<F: Float> Scale<F> {
+  pub fn parse(scl: &str) -> Result<Self, Error> {
+    let mut lines = scl
+      .lines()
+      .enumerate()
+      .map(|(index, line)| (index + 1, line.trim()))
+      .filter(|(_, line)| !line.starts_with('!'));
+
+    lines.next().ok_or(Error::MissingDescription)?;
+
+    let (count_line, count) = lines.next().ok_or(Error::MissingNoteCount)?;
+    let count = first_token(count)
+      .parse::<usize>()
+      .ok()
+      .filter(|count| *count > 0)
+      .ok_or(Error::InvalidNoteCount(count_line))?;
+    if count > MaxScaleNotes::to_usize() {
+      return Err(Error::TooManyNotes(count_line));
+    }
+
+    let mut ratios = Vec::new();
+    for (line_number, line) in lines.take(count) {
+      let ratio = Self::parse_pitch(first_token(line)).ok_or(Error::InvalidPitch(line_number))?;
+      ratios.push(ratio).unwrap();
+    }
+
+    if ratios.len() < count {
+      return Err(Error::MissingPitches {
+        expected: count,
+        found: ratios.len(),
+      });
+    }
+
+    Ok(Scale { ratios })
+  }
+
+  /// Parses cents when there is a dot, or a ratio otherwise
+  fn parse_pitch(pitch: &str) -> Option<F> {
+    if pitch.contains('.') {
+      let cents = pitch.parse::<f64>().ok()?;
+      Some(F::val(2.0f64.powf(cents / 1200.0)))
+    } else {
+      let mut parts = pitch.splitn(2, '/');
+      let numerator = parts.next()?.parse::<u64>().ok()?;
+      let denominator = match parts.next() {
+        Some(denominator) => denominator.parse::<u64>().ok()?,
+        None => 1,
+      };
+      if numerator == 0 || denominator == 0 {
+        None
+      } else {
+        Some(F::val(numerator) / F::val(denominator))
+      }
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.ratios.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.ratios.is_empty()
+  }
+
+  /// The ratio that repeats the scale, usually 2/1
+  pub fn period(&self) -> F {
+    self.ratios.last().cloned().unwrap_or_else(F::one)
+  }
+
+  /// The ratio for any degree relative to the first degree of the scale
+  pub fn degree_ratio(&self, degree: i32) -> F {
+    let len = self.ratios.len() as i32;
+    if len == 0 {
+      return F::one();
+    }
+    let step = degree.rem_euclid(len) as usize;
+    let periods = degree.div_euclid(len);
+    let ratio = if step == 0 {
+      F::one()
+    } else {
+      self.ratios[step - 1]
+    };
+    ratio * self.period().powi(periods)
+  }
+}
+
+/// Keyboard mapping from a Scala `.kbm` file
+#[derive(Debug, Clone)]
+pub struct KeyboardMapping<F: Float> {
+  pub first_key: u8,
+  pub last_key: u8,
+  /// Key where the first degree of the scale is mapped
+  pub middle_key: u8,
+  /// Key tuned to the reference frequency
+  pub reference_key: u8,
+  pub reference_frequency: F,
+  /// Degree of the scale used as the period of the mapping, zero to use the period of the scale
+  pub octave_degree: usize,
+  /// Degrees for every key in a period of the mapping, none for the unmapped ones.
+  /// When it is empty the keys are mapped linearly to consecutive degrees.
+  pub mapping: Vec<Option<usize>, MaxMappingSize>,
+}
+
+impl<F: Float> Default for KeyboardMapping<F> {
+  fn default() -> Self {
+    KeyboardMapping {
+      first_key: 0,
+      last_key: 127,
+      middle_key: 60,
+      reference_key: 69,
+      reference_frequency: F::val(440.0),
+      octave_degree: 0,
+      mapping: Vec::new(),
+    }
+  }
+}
+
+impl<F: Float> KeyboardMapping<F> {
+  pub fn parse(kbm: &str) -> Result<Self, Error> {
+    let mut lines = kbm
+      .lines()
+      .enumerate()
+      .map(|(index, line)| (index + 1, first_token(line.trim())))
+      .filter(|(_, line)| !line.starts_with('!') && !line.is_empty());
+
+    let size = next_value(&mut lines, |line| {
+      line
+        .parse::<usize>()
+        .ok()
+        .filter(|size| *size <= MaxMappingSize::to_usize())
+    })?;
+    let first_key = next_value(&mut lines, parse_key)?;
+    let last_key = next_value(&mut lines, parse_key)?;
+    let middle_key = next_value(&mut lines, parse_key)?;
+    let reference_key = next_value(&mut lines, parse_key)?;
+    let reference_frequency = next_value(&mut lines, |line| {
+      line
+        .parse::<f64>()
+        .ok()
+        .filter(|frequency| *frequency > 0.0)
+        .map(F::val)
+    })?;
+    let octave_degree = next_value(&mut lines, |line| line.parse::<usize>().ok())?;
+
+    let mut mapping = Vec::new();
+    for (line_number, line) in lines.take(size) {
+      let degree = match line {
+        "x" | "X" => None,
+        degree => Some(
+          degree
+            .parse::<usize>()
+            .map_err(|_| Error::InvalidMappingValue(line_number))?,
+        ),
+      };
+      mapping.push(degree).unwrap();
+    }
+    // Missing entries at the end are unmapped
+    mapping.resize(size, None).unwrap();
+
+    Ok(KeyboardMapping {
+      first_key,
+      last_key,
+      middle_key,
+      reference_key,
+      reference_frequency,
+      octave_degree,
+      mapping,
+    })
+  }
+
+  /// The ratio of the key relative to the first degree of the scale, if it is mapped
+  fn ratio(&self, scale: &Scale<F>, key: u8) -> Option<F> {
+    if key < self.first_key || key > self.last_key {
+      return None;
+    }
+
+    let offset = key as i32 - self.middle_key as i32;
+    if self.mapping.is_empty() {
+      Some(scale.degree_ratio(offset))
+    } else {
+      let size = self.mapping.len() as i32;
+      let degree = self.mapping[offset.rem_euclid(size) as usize]?;
+      let octave_ratio = match self.octave_degree {
+        0 => scale.period(),
+        octave_degree => scale.degree_ratio(octave_degree as i32),
+      };
+      Some(scale.degree_ratio(degree as i32) * octave_ratio.powi(offset.div_euclid(size)))
+    }
+  }
+}
+
+/// Tuning from a Scala scale and keyboard mapping.
+/// The keys not mapped by the keyboard mapping have a frequency of zero.
+#[derive(Debug, Clone)]
+pub struct ScalaTuning<F: Float> {
+  frequencies: Vec<F, Keys>,
+}
+
+impl<F: Float> ScalaTuning<F> {
+  pub fn new(scale: &Scale<F>, mapping: &KeyboardMapping<F>) -> Result<Self, Error> {
+    let reference_ratio = mapping
+      .ratio(scale, mapping.reference_key)
+      .ok_or(Error::UnmappedReferenceKey(mapping.reference_key))?;
+
+    let mut frequencies = Vec::new();
+    for key in 0..Keys::to_usize() {
+      let frequency = mapping
+        .ratio(scale, key as u8)
+        .map(|ratio| mapping.reference_frequency * ratio / reference_ratio)
+        .unwrap_or_else(F::zero);
+      frequencies.push(frequency).unwrap();
+    }
+
+    Ok(ScalaTuning { frequencies })
+  }
+
+  /// Parses the contents of a `.scl` file and an optional `.kbm` file
+  pub fn parse(scl: &str, kbm: Option<&str>) -> Result<Self, Error> {
+    let scale = Scale::parse(scl)?;
+    let mapping = match kbm {
+      Some(kbm) => KeyboardMapping::parse(kbm)?,
+      None => KeyboardMapping::default(),
+    };
+    Self::new(&scale, &mapping)
+  }
+}
+
+impl<F: Float> Tuning<F> for ScalaTuning<F> {
+  fn frequency(&self, key: u8) -> F {
+    self.frequencies[(key & 0x7f) as usize]
+  }
+}
+
+fn first_token(line: &str) -> &str {
+  line.split_whitespace().next().unwrap_or("")
+}
+
+/// Parses the next value of the keyboard mapping header
+fn next_value<'a, T, I, P>(lines: &mut I, parse: P) -> Result<T, Error>
+where
+  I: Iterator<Item = (usize, &'a str)>,
+  P: Fn(&str) -> Option<T>,
+{
+  let (line_number, line) = lines.next().ok_or(Error::MissingMappingValue)?;
+  parse(line).ok_or(Error::InvalidMappingValue(line_number))
+}
+
+fn parse_key(line: &str) -> Option<u8> {
+  line.parse::<u8>().ok().filter(|key| *key < 128)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::key_freqs::KEY_FREQ;
+  use crate::scala::{Error, KeyboardMapping, ScalaTuning, Scale};
+  use crate::tuning::Tuning;
+
+  const EQUAL_12: &str = "! equal.scl
+!
+12 tone equal temperament
+ 12
+!
+ 100.0
+ 200.
+ 300.0
+ 400.0
+ 500.0
+ 600.0
+ 700.0
+ 800.0
+ 900.0
+ 1000.0
+ 1100.0
+ 2/1
+";
+
+  const JUST_MAJOR: &str = "! just.scl
+Just major
+7
+9/8 major second
+5/4
+4/3
+3/2
+5/3
+15/8
+2
+";
+
+  #[test]
+  fn equal_temperament_matches_key_freqs() {
+    let tuning = ScalaTuning::<f64>::parse(EQUAL_12, None).unwrap();
+    for key in 0..128u8 {
+      let expected = KEY_FREQ[key as usize] as f64;
+      assert!((tuning.frequency(key) - expected).abs() / expected < 1e-4);
+    }
+  }
+
+  #[test]
+  fn ratios_with_keyboard_mapping() {
+    let kbm = "! C major on the white keys, C4 at 261.6256 Hz
+12
+0
+127
+60
+60
+261.6256
+7
+! Mapping
+0
+x
+1
+x
+2
+3
+x
+4
+x
+5
+x
+6
+";
+    let tuning = ScalaTuning::<f64>::parse(JUST_MAJOR, Some(kbm)).unwrap();
+    let c4 = 261.6256;
+    assert!((tuning.frequency(60) - c4).abs() < 1e-9);
+    assert!((tuning.frequency(67) - c4 * 1.5).abs() < 1e-9);
+    assert!((tuning.frequency(71) - c4 * 15.0 / 8.0).abs() < 1e-9);
+    assert!((tuning.frequency(72) - c4 * 2.0).abs() < 1e-9);
+    assert!((tuning.frequency(55) - c4 * 0.75).abs() < 1e-9);
+    assert!(tuning.frequency(61).abs() < 1e-9);
+  }
+
+  #[test]
+  fn reference_frequency_and_key() {
+    let mapping = KeyboardMapping {
+      reference_key: 60,
+      reference_frequency: 256.0f64,
+      ..KeyboardMapping::default()
+    };
+    let scale = Scale::parse(JUST_MAJOR).unwrap();
+    let tuning = ScalaTuning::new(&scale, &mapping).unwrap();
+    assert!((tuning.frequency(60) - 256.0).abs() < 1e-9);
+    assert!((tuning.frequency(64) - 384.0).abs() < 1e-9);
+    assert!((tuning.frequency(67) - 512.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn malformed_files_are_rejected() {
+    let parse = |scl: &str| Scale::<f64>::parse(scl).map(|_| ());
+    assert_eq!(parse("! only comments\n"), Err(Error::MissingDescription));
+    assert_eq!(parse("description\n"), Err(Error::MissingNoteCount));
+    assert_eq!(
+      parse("description\nmany\n"),
+      Err(Error::InvalidNoteCount(2))
+    );
+    assert_eq!(
+      parse("description\n2\n3/2\n"),
+      Err(Error::MissingPitches {
+        expected: 2,
+        found: 1
+      })
+    );
+    assert_eq!(
+      parse("description\n2\n3/0\n2/1\n"),
+      Err(Error::InvalidPitch(3))
+    );
+    assert_eq!(
+      parse("description\n2\n! comment\n3/2\nfifth\n"),
+      Err(Error::InvalidPitch(5))
+    );
+
+    let kbm = "0\n0\n127\n60\n200\n440.0\n0\n";
+    assert_eq!(
+      KeyboardMapping::<f64>::parse(kbm).map(|_| ()),
+      Err(Error::InvalidMappingValue(5))
+    );
+    assert_eq!(
+      KeyboardMapping::<f64>::parse("0\n0\n127\n").map(|_| ()),
+      Err(Error::MissingMappingValue)
+    );
+  }
+}
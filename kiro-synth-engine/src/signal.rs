@@ -1,16 +1,20 @@
 use core::ops::{Index, IndexMut};
 
+use kiro_synth_dsp::funcs::denormals::flush_denormal;
+
 use crate::float::Float;
 use crate::program::SignalRef;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub enum SignalState {
   Off,
   Updated,
   Consumed,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Signal<F: Float> {
   value: F,
   state: SignalState,
@@ -33,7 +37,9 @@ impl<F: Float> Signal<F> {
     }
   }
 
+  /// Values small enough to become denormals are flushed to zero
   pub fn set(&mut self, value: F) {
+    let value = flush_denormal(value);
     self.state = if self.value != value {
       SignalState::Updated
     } else {
@@ -71,7 +77,7 @@ impl<F: Float> Signal<F> {
 
   pub fn if_updated<G>(&mut self, f: G)
   where
-    G: FnOnce(F) -> (),
+    G: FnOnce(F),
   {
     match self.state {
       SignalState::Updated | SignalState::Consumed => f(self.consume()),
@@ -126,3 +132,30 @@ impl<'a, F: Float, S: Into<SignalRef>> IndexMut<S> for SignalBus<'a, F> {
     &mut self.signals[index.into().0]
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::float::Float;
+  use crate::signal::Signal;
+
+  fn decay<F: Float>() {
+    let mut signal = Signal::new(F::one());
+    let mut value = F::one();
+    for _ in 0..2000 {
+      value = value * F::val(0.9);
+      signal.set(value);
+      let output = signal.get();
+      assert!(output == F::zero() || output.is_normal());
+      if value < F::val(1e-30) {
+        assert!(output == F::zero());
+      }
+    }
+    assert!(signal.get() == F::zero());
+  }
+
+  #[test]
+  fn decaying_values_are_flushed_to_zero() {
+    decay::<f32>();
+    decay::<f64>();
+  }
+}
@@ -1,24 +1,25 @@
-use heapless::consts;
-use heapless::Vec;
 use ringbuf::Consumer;
-use typenum::marker_traits::Unsigned;
 
+use kiro_synth_dsp::effects::limiter::Limiter;
+
+use crate::allocator::{Normalization, VoiceAllocator};
 use crate::event::{Event, Message};
 use crate::float::Float;
 use crate::globals::SynthGlobals;
 use crate::program::Program;
+use crate::tuning::Tuning;
 use crate::voice::Voice;
 
-pub type MaxVoices = consts::U32;
+pub use crate::allocator::MaxVoices;
 
 pub struct Synth<'a, F: Float> {
   sample_rate: F,
   events: Consumer<Event<F>>,
   program: Program<'a, F>,
   globals: SynthGlobals<F>,
-  voices: Vec<Voice<F>, MaxVoices>,
-  active_voices: Vec<usize, MaxVoices>,
-  free_voices: Vec<usize, MaxVoices>,
+  allocator: VoiceAllocator<F>,
+  limiter: Option<Limiter<F>>,
+  sanitize: bool,
 }
 
 impl<'a, F: Float> Synth<'a, F> {
@@ -28,21 +29,16 @@ impl<'a, F: Float> Synth<'a, F> {
     program: Program<'a, F>,
     globals: SynthGlobals<F>,
   ) -> Self {
-    let mut voices: Vec<Voice<F>, MaxVoices> = Vec::new();
-    let mut free_voices: Vec<usize, MaxVoices> = Vec::new();
-    for index in 0..MaxVoices::to_usize() {
-      drop(voices.push(Voice::new(sample_rate, &program)));
-      free_voices.push(MaxVoices::to_usize() - index - 1).unwrap();
-    }
+    let allocator = VoiceAllocator::new(sample_rate, &program);
 
     Synth {
       sample_rate,
       events,
       program,
       globals,
-      voices,
-      active_voices: Vec::new(),
-      free_voices,
+      allocator,
+      limiter: None,
+      sanitize: true,
     }
   }
 
@@ -54,14 +50,25 @@ impl<'a, F: Float> Synth<'a, F> {
     &self.program
   }
 
+  pub fn get_voice_allocator(&self) -> &VoiceAllocator<F> {
+    &self.allocator
+  }
+
+  pub fn get_voice_allocator_mut(&mut self) -> &mut VoiceAllocator<F> {
+    &mut self.allocator
+  }
+
   pub fn get_last_voice(&self) -> Option<&Voice<F>> {
-    self.active_voices.last().map(|index| &self.voices[*index])
+    self.allocator.get_last_voice()
   }
 
   pub fn get_num_active_voices(&self) -> usize {
-    self.active_voices.len()
+    self.allocator.get_num_active_voices()
   }
 
+  /// Handle the pending events.
+  /// Like `process` and `process_block` it neither allocates nor frees memory,
+  /// so it can be called from the audio thread.
   pub fn prepare(&mut self) {
     while let Some(Event {
       timestamp: _,
@@ -73,15 +80,23 @@ impl<'a, F: Float> Synth<'a, F> {
         Message::NoteOff { key, velocity } => self.note_off(key, velocity),
         Message::ParamValue { param_ref, value } => {
           if let Some((_, param)) = self.program.get_param_mut(param_ref) {
-            println!("{} = {:?}", param.id, value);
-            param.value.set(value)
+            if !value.is_nan() {
+              let value = param.values.clamp(value);
+              param.value.set(value)
+            }
+          }
+        }
+        Message::ParamValueUnclamped { param_ref, value } => {
+          if let Some((_, param)) = self.program.get_param_mut(param_ref) {
+            if !value.is_nan() {
+              param.value.set(value)
+            }
           }
         }
         Message::ParamChange { param_ref, change } => {
           if let Some((_, param)) = self.program.get_param_mut(param_ref) {
             let value: F = param.value.get() + change;
-            let value = value.max(param.values.min).min(param.values.max);
-            println!("{} = {:?}", param.id, value);
+            let value = param.values.clamp(value);
             param.value.set(value);
           }
         }
@@ -90,11 +105,7 @@ impl<'a, F: Float> Synth<'a, F> {
           param_ref,
           amount,
         } => {
-          if let Some(source) = self.program.get_source(source_ref) {
-            let source_id = source.id;
-            if let Some((_, param)) = self.program.get_param(param_ref) {
-              println!("{} -> {} {:?}", source_id, param.id, amount);
-            }
+          if self.program.get_source(source_ref).is_some() {
             self
               .program
               .update_modulation(param_ref, source_ref, amount)
@@ -110,65 +121,447 @@ impl<'a, F: Float> Synth<'a, F> {
             .delete_modulation(param_ref, source_ref)
             .unwrap(); // TODO handle error
         }
+        Message::ControlChange { cc, value } => self.set_cc(cc, value),
+        Message::Sustain { on } => self.allocator.set_sustain(&self.program, on),
+        Message::Aftertouch { value } => self.allocator.set_aftertouch(&self.program, value),
+        Message::PolyPressure { key, value } => {
+          self.allocator.set_poly_pressure(&self.program, key, value)
+        }
+        Message::PitchBend { amount } => self.allocator.set_pitch_bend(&self.program, amount),
+        Message::Tempo { bpm } => self.set_tempo(bpm),
+        Message::SampleRate { sample_rate } => self.set_sample_rate(sample_rate),
+        Message::ResetBlock { block_ref } => self.allocator.reset_block(&self.program, block_ref),
       }
     }
   }
 
-  fn note_on(&mut self, key: u8, velocity: F) {
-    if let Some(index) = self.allocate_voice(key, velocity) {
-      self.active_voices.push(index).unwrap();
-      self.voices[index].note_on(&self.program, key, velocity);
-      println!("{:?}", self.active_voices);
-    }
+  pub fn set_tuning<T: Tuning<F> + Send + 'static>(&mut self, tuning: T) {
+    self.allocator.set_tuning(tuning);
   }
 
-  fn note_off(&mut self, key: u8, _velocity: F) {
-    for active_voice_index in 0..self.active_voices.len() {
-      let voice_index = self.active_voices[active_voice_index];
-      let voice = &mut self.voices[voice_index];
-      if voice.get_key(&self.program) == key {
-        voice.note_off(&self.program)
+  pub fn set_normalization(&mut self, normalization: Normalization) {
+    self.allocator.set_normalization(normalization);
+  }
+
+  pub fn set_cc(&mut self, cc: u8, value: F) {
+    self.globals.controllers.set(cc, value);
+  }
+
+  pub fn set_tempo(&mut self, bpm: F) {
+    self.globals.tempo = bpm;
+  }
+
+  /// Master seed for the random sequences, rendering the same notes with it gives the same output
+  pub fn set_seed(&mut self, seed: u32) {
+    self.globals.seed = seed;
+  }
+
+  /// Limit the mix of all the voices so it never goes over the threshold
+  pub fn set_limiter(&mut self, threshold: F, release_sec: F) {
+    match self.limiter.as_mut() {
+      Some(limiter) => {
+        limiter.set_threshold(threshold);
+        limiter.set_release_time_sec(release_sec);
       }
+      None => self.limiter = Some(Limiter::new(self.sample_rate, threshold, release_sec)),
     }
   }
 
-  fn allocate_voice(&mut self, _key: u8, _velocity: F) -> Option<usize> {
-    self.free_voices.pop()
+  pub fn remove_limiter(&mut self) {
+    self.limiter = None;
+  }
+
+  pub fn get_limiter(&self) -> Option<&Limiter<F>> {
+    self.limiter.as_ref()
+  }
+
+  /// Replace the NaN and infinite values of the output with silence and hard limit it to [-1, 1],
+  /// so a misbehaving program can not reach the audio device. It is enabled by default.
+  pub fn set_sanitize(&mut self, sanitize: bool) {
+    self.sanitize = sanitize;
+  }
+
+  pub fn get_sanitize(&self) -> bool {
+    self.sanitize
+  }
+
+  fn set_sample_rate(&mut self, sample_rate: F) {
+    self.sample_rate = sample_rate;
+    self.allocator.set_sample_rate(sample_rate);
+    if let Some(limiter) = self.limiter.as_mut() {
+      limiter.set_sample_rate(sample_rate);
+    }
+  }
+
+  fn note_on(&mut self, key: u8, velocity: F) {
+    self.allocator.note_on(&self.program, key, velocity);
+  }
+
+  fn note_off(&mut self, key: u8, _velocity: F) {
+    self.allocator.note_off(&self.program, key);
   }
 
   pub fn process(&mut self) -> (F, F) {
-    let (mut left, mut right) = (F::zero(), F::zero());
-
-    let mut freed_voices = false;
-    let mut active_voice_index = 0;
-    while active_voice_index < self.active_voices.len() {
-      let voice_index = self.active_voices[active_voice_index];
-      let voice = &mut self.voices[voice_index];
-
-      voice.process(&mut self.program, &self.globals);
-      let (voice_left, voice_right) = voice.output(&self.program);
-      left = left + voice_left;
-      right = right + voice_right;
-
-      if voice.is_off(&self.program) {
-        self.active_voices.swap_remove(active_voice_index);
-        self.free_voices.push(voice_index).unwrap();
-        freed_voices = true;
-      } else {
-        active_voice_index += 1;
+    let (left, right) = self.allocator.process(&mut self.program, &self.globals);
+
+    self.program.update_params();
+
+    let (left, right) = if self.sanitize {
+      (Self::finite_or_zero(left), Self::finite_or_zero(right))
+    } else {
+      (left, right)
+    };
+
+    let (left, right) = match self.limiter.as_mut() {
+      Some(limiter) => limiter.process(left, right),
+      None => (left, right),
+    };
+
+    if self.sanitize {
+      (Self::hard_limit(left), Self::hard_limit(right))
+    } else {
+      (left, right)
+    }
+  }
+
+  /// Process a block of frames writing the output into the slices
+  pub fn process_block(&mut self, left: &mut [F], right: &mut [F]) {
+    self
+      .allocator
+      .process_block(&mut self.program, &self.globals, left, right);
+
+    // the params are updated after every frame, as `process` does
+    for _ in 0..left.len().min(right.len()) {
+      self.program.update_params();
+    }
+
+    if self.sanitize {
+      for sample in left.iter_mut().chain(right.iter_mut()) {
+        *sample = Self::finite_or_zero(*sample);
       }
     }
 
-    if freed_voices {
-      println!("{:?}", self.active_voices);
+    if let Some(limiter) = self.limiter.as_mut() {
+      for (left, right) in left.iter_mut().zip(right.iter_mut()) {
+        let (limited_left, limited_right) = limiter.process(*left, *right);
+        *left = limited_left;
+        *right = limited_right;
+      }
     }
 
-    self.program.update_params();
+    if self.sanitize {
+      for sample in left.iter_mut().chain(right.iter_mut()) {
+        *sample = Self::hard_limit(*sample);
+      }
+    }
+  }
+
+  /// Done before the limiter so its envelope is not broken by a NaN
+  fn finite_or_zero(value: F) -> F {
+    if value.is_finite() {
+      value
+    } else {
+      F::zero()
+    }
+  }
 
-    (left, right)
+  fn hard_limit(value: F) -> F {
+    value.max(F::one().neg()).min(F::one())
   }
 }
 
 pub struct VoiceIter<'a, F: Float + 'a, I>(I)
 where
   I: Iterator<Item = &'a Voice<F>>;
+
+#[cfg(test)]
+mod tests {
+  use crate::event::{Error, Event, Message};
+  use crate::globals::SynthGlobals;
+  use crate::offline::OfflineRenderer;
+  use crate::program::blocks::{env_follower, lfo, osc};
+  use crate::program::{Block, ParamScale, ParamUnit, ParamValues, Program, ProgramBuilder};
+
+  fn program<'a>() -> Program<'a, f32> {
+    constant(0.8)
+  }
+
+  fn constant<'a>(value: f32) -> Program<'a, f32> {
+    let mut builder = ProgramBuilder::new();
+    let level = builder.const_value(value);
+    builder.out(level, level);
+    builder.build()
+  }
+
+  #[test]
+  fn limiter_keeps_the_voices_mix_under_the_threshold() {
+    let events: Vec<Event<f32>> = (60..64)
+      .map(|key| Event::new(0, Message::NoteOn { key, velocity: 1.0 }))
+      .collect();
+
+    let mut renderer = OfflineRenderer::new(44100.0, program(), SynthGlobals::new());
+    renderer.get_synth_mut().set_sanitize(false);
+    let unlimited = renderer.render(&events, 100);
+    assert!(unlimited.iter().any(|sample| *sample > 3.0));
+
+    let mut renderer = OfflineRenderer::new(44100.0, program(), SynthGlobals::new());
+    renderer.get_synth_mut().set_limiter(0.5, 0.1);
+    let limited = renderer.render(&events, 100);
+    assert!(limited.iter().all(|sample| sample.abs() <= 0.5 + 1e-6));
+    assert!(limited.iter().any(|sample| (sample - 0.5).abs() < 1e-6));
+
+    let (mut left, mut right) = (vec![0.0; 64], vec![0.0; 64]);
+    renderer
+      .get_synth_mut()
+      .process_block(&mut left, &mut right);
+    assert!(left
+      .iter()
+      .chain(right.iter())
+      .all(|sample| sample.abs() <= 0.5 + 1e-6));
+  }
+
+  #[test]
+  fn sanitize_silences_nan_and_hard_limits_the_output() {
+    let events = vec![Event::new(
+      0,
+      Message::NoteOn {
+        key: 60,
+        velocity: 1.0,
+      },
+    )];
+
+    let mut renderer = OfflineRenderer::new(44100.0, constant(f32::NAN), SynthGlobals::new());
+    let output = renderer.render(&events, 100);
+    assert!(output.iter().all(|sample| *sample == 0.0));
+
+    renderer.get_synth_mut().set_sanitize(false);
+    let output = renderer.render(&[], 100);
+    assert!(output.iter().all(|sample| sample.is_nan()));
+
+    let events: Vec<Event<f32>> = (60..64)
+      .map(|key| Event::new(0, Message::NoteOn { key, velocity: 1.0 }))
+      .collect();
+    let mut renderer = OfflineRenderer::new(44100.0, program(), SynthGlobals::new());
+    let output = renderer.render(&events, 100);
+    assert!(output.iter().all(|sample| sample.abs() <= 1.0));
+    assert!(output.iter().any(|sample| (sample - 1.0).abs() < 1e-6));
+  }
+
+  /// Level param modulated by an LFO going straight to the output
+  fn modulated_level<'a>() -> Program<'a, f32> {
+    let mut builder = ProgramBuilder::new();
+    let level = builder.param(
+      "level",
+      ParamValues {
+        initial_value: 0.5,
+        origin: 0.0,
+        min: 0.0,
+        max: 1.0,
+        resolution: 0.01,
+        unit: ParamUnit::Raw,
+        display_scale: ParamScale::Linear,
+      },
+    );
+    builder.block(Block::Param(level.clone()));
+    let zero = builder.const_zero();
+    let one = builder.const_one();
+    let rate = builder.const_value(100.0);
+    let lfo_output = builder.signal();
+    builder.block(Block::Lfo(lfo::Block {
+      inputs: lfo::Inputs {
+        shape: zero,
+        rate,
+        phase: zero,
+        depth: one,
+        sync: zero,
+        retrigger: one,
+      },
+      output: lfo_output,
+    }));
+    let lfo_source = builder.source("lfo", lfo_output);
+    builder.modulation(&level, lfo_source, 0.4);
+    builder.out(level.out_signal_ref, level.out_signal_ref);
+    builder.build()
+  }
+
+  #[test]
+  fn process_block_matches_process_with_modulated_params() {
+    let note_on = |key| Event::new(0, Message::NoteOn { key, velocity: 1.0 });
+    let events = [note_on(60), note_on(64)];
+    let mut frames = OfflineRenderer::new(44100.0, modulated_level(), SynthGlobals::new());
+    let mut blocks = OfflineRenderer::new(44100.0, modulated_level(), SynthGlobals::new());
+    frames.render(&events, 1);
+    blocks.render(&events, 1);
+
+    let expected: Vec<(f32, f32)> = (0..300).map(|_| frames.get_synth_mut().process()).collect();
+    let (mut left, mut right) = (vec![0.0; 300], vec![0.0; 300]);
+    blocks.get_synth_mut().process_block(&mut left, &mut right);
+
+    let min = expected
+      .iter()
+      .map(|(left, _)| *left)
+      .fold(f32::MAX, f32::min);
+    let max = expected
+      .iter()
+      .map(|(left, _)| *left)
+      .fold(f32::MIN, f32::max);
+    assert!(max - min > 0.5);
+    for (index, (left_value, right_value)) in expected.iter().enumerate() {
+      assert!((left[index] - left_value).abs() < 1e-6);
+      assert!((right[index] - right_value).abs() < 1e-6);
+    }
+  }
+
+  #[test]
+  fn reset_block_clears_only_the_state_of_that_block() {
+    let mut builder = ProgramBuilder::new();
+    let input = builder.const_one();
+    let attack = builder.const_value(0.01);
+    let release = builder.const_value(10.0);
+    let follower = |builder: &mut ProgramBuilder<f32>| {
+      let output = builder.signal();
+      let inputs = env_follower::Inputs {
+        input,
+        attack,
+        release,
+      };
+      let block_ref = builder.block(Block::EnvFollower(env_follower::Block { inputs, output }));
+      (block_ref, output)
+    };
+    let (left_block, left) = follower(&mut builder);
+    let (_, right) = follower(&mut builder);
+    builder.out(left, right);
+
+    let mut renderer = OfflineRenderer::new(44100.0, builder.build(), SynthGlobals::new());
+    let note_on = Event::new(
+      0,
+      Message::NoteOn {
+        key: 60,
+        velocity: 1.0,
+      },
+    );
+    let output = renderer.render(&[note_on], 2000);
+    assert!(output[3998] > 0.9 && output[3999] > 0.9);
+
+    let reset = Event::new(
+      2000,
+      Message::ResetBlock {
+        block_ref: left_block,
+      },
+    );
+    let output = renderer.render(&[reset], 1);
+    assert!(output[0] < 0.01);
+    assert!(output[1] > 0.9);
+  }
+
+  #[test]
+  fn param_values_are_clamped_unless_requested() {
+    let mut builder = ProgramBuilder::new();
+    let values = ParamValues {
+      initial_value: 0.5,
+      origin: 0.0,
+      min: 0.0,
+      max: 1.0,
+      resolution: 0.01,
+      unit: ParamUnit::Raw,
+      display_scale: ParamScale::Linear,
+    };
+    let param_ref = builder.param("level", values).reference;
+    let level = builder.const_zero();
+    builder.out(level, level);
+
+    let mut renderer = OfflineRenderer::new(44100.0, builder.build(), SynthGlobals::new());
+    let mut send = |message: Message<f32>| {
+      let frame = renderer.get_frame();
+      renderer.render(&[Event::new(frame, message)], 1);
+      let synth = renderer.get_synth_mut();
+      synth
+        .get_program()
+        .get_param(param_ref)
+        .unwrap()
+        .1
+        .value
+        .get()
+    };
+
+    let value = send(Message::param_value(param_ref, 5.0).unwrap());
+    assert!((value - 1.0).abs() < 1e-6);
+    let value = send(Message::param_value(param_ref, -5.0).unwrap());
+    assert!(value.abs() < 1e-6);
+    let value = send(Message::param_value_unclamped(param_ref, 5.0).unwrap());
+    assert!((value - 5.0).abs() < 1e-6);
+
+    // NaN values are rejected when creating the message, and ignored if they arrive anyway
+    assert_eq!(
+      Message::param_value(param_ref, f32::NAN).err(),
+      Some(Error::InvalidParamValue(param_ref))
+    );
+    assert_eq!(
+      Message::param_value_unclamped(param_ref, f32::NAN).err(),
+      Some(Error::InvalidParamValue(param_ref))
+    );
+    let value = send(Message::ParamValue {
+      param_ref,
+      value: f32::NAN,
+    });
+    assert!((value - 5.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn sample_rate_changes_reach_the_processors() {
+    let mut builder = ProgramBuilder::new();
+    let voice = builder.voice().clone();
+    let zero = builder.const_zero();
+    let one = builder.const_one();
+    let (output, sync_output) = (builder.signal(), builder.signal());
+    builder.block(Block::Osc(osc::Block {
+      inputs: osc::Inputs {
+        shape: zero,
+        amplitude: one,
+        amp_mod: zero,
+        octaves: zero,
+        semitones: zero,
+        cents: zero,
+        note_pitch: voice.note_pitch,
+        pitch_bend: zero,
+        freq_mod: zero,
+        pulse_width: zero,
+        sync: zero,
+      },
+      output,
+      sync_output,
+    }));
+    builder.out(output, output);
+
+    let rising_crossings = |output: &[f32]| {
+      let left: Vec<f32> = output.iter().step_by(2).cloned().collect();
+      left
+        .windows(2)
+        .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+        .count()
+    };
+
+    // a tenth of a second of the A4 at 44.1 KHz has 44 cycles
+    let mut renderer = OfflineRenderer::new(44100.0, builder.build(), SynthGlobals::new());
+    let note_on = Event::new(
+      0,
+      Message::NoteOn {
+        key: 69,
+        velocity: 1.0,
+      },
+    );
+    let output = renderer.render(&[note_on], 4410);
+    assert!((rising_crossings(&output) as isize - 44).abs() <= 1);
+
+    // the same frames at half the sample rate last twice as long
+    let frame = renderer.get_frame();
+    let sample_rate = Event::new(
+      frame,
+      Message::SampleRate {
+        sample_rate: 22050.0,
+      },
+    );
+    let output = renderer.render(&[sample_rate], 4410);
+    assert!((rising_crossings(&output) as isize - 88).abs() <= 1);
+  }
+}
@@ -0,0 +1,31 @@
+use crate::float::Float;
+use crate::key_freqs::KEY_FREQ;
+
+/// Maps MIDI keys into frequencies
+pub trait Tuning<F: Float> {
+  fn frequency(&self, key: u8) -> F;
+}
+
+/// Twelve-tone equal temperament with A4 at 440 Hz
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EqualTemperament;
+
+impl<F: Float> Tuning<F> for EqualTemperament {
+  fn frequency(&self, key: u8) -> F {
+    F::val(KEY_FREQ[(key & 0x7f) as usize])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::key_freqs::KEY_FREQ;
+  use crate::tuning::{EqualTemperament, Tuning};
+
+  #[test]
+  fn equal_temperament_matches_key_freqs() {
+    for key in 0..128u8 {
+      let frequency: f32 = EqualTemperament.frequency(key);
+      assert_eq!(frequency.to_bits(), KEY_FREQ[key as usize].to_bits());
+    }
+  }
+}
@@ -0,0 +1,80 @@
+use heapless::consts;
+use heapless::Vec;
+
+use crate::float::Float;
+
+pub type MaxVelocityTable = consts::U128;
+
+/// How the velocity of a note is mapped before reaching the voice
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum VelocityCurve<F: Float> {
+  #[default]
+  Linear,
+  /// Velocity raised to gamma, values greater than one make the response softer
+  Exponential { gamma: F },
+  /// Values equally spaced between velocity 0 and 1, linearly interpolated
+  Table(Vec<F, MaxVelocityTable>),
+}
+
+impl<F: Float> VelocityCurve<F> {
+  pub fn map(&self, velocity: F) -> F {
+    let velocity = velocity.max(F::zero()).min(F::one());
+    match self {
+      VelocityCurve::Linear => velocity,
+      VelocityCurve::Exponential { gamma } => velocity.powf(*gamma),
+      VelocityCurve::Table(table) => Self::interpolate(table.as_ref(), velocity),
+    }
+  }
+
+  fn interpolate(table: &[F], velocity: F) -> F {
+    match table.len() {
+      0 => velocity,
+      1 => table[0],
+      len => {
+        let position = velocity * F::val(len - 1);
+        let index = position.floor().to_usize().unwrap_or(0).min(len - 2);
+        let fraction = position - F::val(index);
+        table[index] + (table[index + 1] - table[index]) * fraction
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use heapless::Vec;
+
+  use crate::velocity::VelocityCurve;
+
+  fn curves() -> [VelocityCurve<f32>; 3] {
+    let table = Vec::from_slice(&[0.0, 0.1, 0.3, 1.0]).unwrap();
+    [
+      VelocityCurve::Linear,
+      VelocityCurve::Exponential { gamma: 3.0 },
+      VelocityCurve::Table(table),
+    ]
+  }
+
+  #[test]
+  fn exponential_maps_mid_velocities_lower() {
+    let linear = VelocityCurve::Linear;
+    let exponential = VelocityCurve::Exponential { gamma: 3.0 };
+    for velocity in &[0.25f32, 0.5, 0.75] {
+      assert!(exponential.map(*velocity) < linear.map(*velocity));
+    }
+  }
+
+  #[test]
+  fn table_interpolates() {
+    let curve = &curves()[2];
+    assert!((curve.map(0.5) - 0.2).abs() < 1e-6);
+  }
+
+  #[test]
+  fn limits_map_to_themselves() {
+    for curve in curves().iter() {
+      assert!(curve.map(0.0).abs() < 1e-6);
+      assert!((curve.map(1.0) - 1.0).abs() < 1e-6);
+    }
+  }
+}
@@ -3,14 +3,32 @@ use heapless::Vec;
 
 use crate::float::Float;
 use crate::globals::SynthGlobals;
-use crate::key_freqs::KEY_FREQ;
 use crate::processor::Processor;
-use crate::program::{Block, MaxBlocks, MaxSignals, Program};
+use crate::program::{Block, BlockRef, MaxBlocks, MaxSignals, Program, SignalRef};
 use crate::signal::{Signal, SignalBus};
 
+/// Linear ramp of the note pitch towards a target frequency
+#[derive(Debug, Clone, Default)]
+struct Glide<F: Float> {
+  target: F,
+  step: F,
+  remaining: usize,
+}
+
 pub struct Voice<F: Float> {
   signals: Vec<Signal<F>, MaxSignals>,
   processors: Vec<Processor<F>, MaxBlocks>,
+  glide: Glide<F>,
+  /// Pitch of the key before the pitch bend, it moves while gliding
+  key_pitch: F,
+  /// Multiplier for the key pitch from the pitch bend
+  bend_multiplier: F,
+  /// Multiplier for the key pitch from the detune
+  detune_multiplier: F,
+  /// Position in the allocator, the seed of the voice derives from it
+  index: usize,
+  /// Master seed the processors were seeded from
+  master_seed: Option<u32>,
 }
 
 impl<F: Float> Voice<F> {
@@ -34,6 +52,12 @@ impl<F: Float> Voice<F> {
     Voice {
       signals,
       processors,
+      glide: Glide::default(),
+      key_pitch: F::zero(),
+      bend_multiplier: F::one(),
+      detune_multiplier: F::one(),
+      index: 0,
+      master_seed: None,
     }
   }
 
@@ -41,18 +65,111 @@ impl<F: Float> Voice<F> {
     self.signals.as_ref()
   }
 
+  /// The current value of the signal with the given name in the program
+  pub fn signal_by_name(&self, program: &Program<F>, name: &str) -> Option<F> {
+    program
+      .get_signal_by_name(name)
+      .and_then(|signal| self.signals.get(signal.0))
+      .map(|signal| signal.get())
+  }
+
+  /// The names of the signals in the program with their current values
+  pub fn signal_names<'a, 'p: 'a>(
+    &'a self,
+    program: &'a Program<'p, F>,
+  ) -> impl Iterator<Item = (&'p str, F)> + 'a {
+    program
+      .signal_names()
+      .filter_map(move |(name, signal)| self.signals.get(signal.0).map(|s| (name, s.get())))
+  }
+
   pub(crate) fn get_key(&self, program: &Program<F>) -> u8 {
     self.signals[program.voice().key.0].get().to_u8().unwrap()
   }
-  //
-  //  pub fn get_velocity(&self, program: &Program<F>) -> F {
-  //    self.signals[program.voice().velocity.0].get()
-  //  }
+
+  pub fn get_note_pitch(&self, program: &Program<F>) -> F {
+    self.signals[program.voice().note_pitch.0].get()
+  }
+
+  /// The pitch of the key without the pitch bend applied
+  pub(crate) fn get_key_pitch(&self) -> F {
+    self.key_pitch
+  }
+
+  pub fn get_pitch_bend(&self, program: &Program<F>) -> F {
+    self.signals[program.voice().pitch_bend.0].get()
+  }
+
+  pub fn get_aftertouch(&self, program: &Program<F>) -> F {
+    self.signals[program.voice().aftertouch.0].get()
+  }
+
+  pub fn get_poly_pressure(&self, program: &Program<F>) -> F {
+    self.signals[program.voice().poly_pressure.0].get()
+  }
+
+  pub fn get_velocity(&self, program: &Program<F>) -> F {
+    self.signals[program.voice().velocity.0].get()
+  }
+
+  pub fn is_trigger_on(&self, program: &Program<F>) -> bool {
+    self.signals[program.voice().trigger.0].get() > F::zero()
+  }
+
+  pub fn is_gate_on(&self, program: &Program<F>) -> bool {
+    self.signals[program.voice().gate.0].get() > F::zero()
+  }
 
   pub(crate) fn is_off(&self, program: &Program<F>) -> bool {
     self.signals[program.voice().off.0].get() == F::one()
   }
 
+  pub(crate) fn set_sample_rate(&mut self, sample_rate: F) {
+    for proc in self.processors.iter_mut() {
+      proc.set_sample_rate(sample_rate);
+    }
+  }
+
+  pub(crate) fn set_index(&mut self, index: usize) {
+    self.index = index;
+    self.master_seed = None;
+  }
+
+  /// Seed the random sequences of the voice, they restart from it on every reset
+  fn set_seed(&mut self, seed: u32) {
+    let seed = seed.wrapping_mul(0x9e37_79b9);
+    for (index, proc) in self.processors.iter_mut().enumerate() {
+      proc.set_seed(seed.wrapping_add(index as u32));
+    }
+  }
+
+  /// Move the samples recorded by the probe on the signal into the buffer,
+  /// returning how many of them were written (none if there is no probe for it)
+  pub fn drain_probe(&mut self, signal: SignalRef, buffer: &mut [F]) -> usize {
+    self
+      .processors
+      .iter_mut()
+      .find_map(|proc| proc.drain_probe(signal, buffer))
+      .unwrap_or(0)
+  }
+
+  /// Reset the state of the processor of one block, keeping the signals and the rest of processors
+  pub(crate) fn reset_block(&mut self, program: &Program<F>, block_ref: BlockRef) {
+    let blocks = program.get_blocks();
+    let is_const = |block: &Block<F>| matches!(block, Block::Const { .. });
+    match blocks.get(block_ref.0) {
+      Some(block) if !is_const(block) => {
+        // there are no processors for the constants
+        let index = blocks[..block_ref.0]
+          .iter()
+          .filter(|block| !is_const(block))
+          .count();
+        self.processors[index].reset();
+      }
+      _ => {}
+    }
+  }
+
   pub(crate) fn reset(&mut self, program: &Program<F>) {
     let mut signals = SignalBus::new(self.signals.deref_mut());
     signals.reset();
@@ -67,27 +184,144 @@ impl<F: Float> Voice<F> {
     }
 
     signals[program.voice().off].set(F::zero());
+    for output in program.voice().output_channels.iter() {
+      signals[*output].set(F::zero());
+    }
 
     for proc in self.processors.iter_mut() {
       proc.reset();
     }
+
+    self.glide = Glide::default();
   }
 
-  pub(crate) fn note_on(&mut self, program: &Program<F>, key: u8, velocity: F) {
+  pub(crate) fn note_on(&mut self, program: &Program<F>, key: u8, pitch: F, velocity: F) {
     self.reset(program);
+    self.set_key(program, key, pitch);
     let voice = program.voice();
-    self.signals[voice.key.0].set(F::val(key));
     self.signals[voice.velocity.0].set(velocity);
-    self.signals[voice.note_pitch.0].set(F::val(KEY_FREQ[(key & 0x7f) as usize]));
+    self.signals[voice.poly_pressure.0].set(F::zero());
     self.signals[voice.gate.0].set(F::one());
     self.signals[voice.trigger.0].set(F::one());
   }
 
+  /// Change the key of a sounding voice without resetting it nor pulsing the trigger
+  pub(crate) fn legato_note_on(&mut self, program: &Program<F>, key: u8, pitch: F) {
+    self.set_key(program, key, pitch);
+    self.signals[program.voice().gate.0].set(F::one());
+  }
+
+  fn set_key(&mut self, program: &Program<F>, key: u8, pitch: F) {
+    self.signals[program.voice().key.0].set(F::val(key));
+    self.key_pitch = pitch;
+    self.glide = Glide::default();
+    self.update_note_pitch(program);
+  }
+
+  /// Glide the key pitch from the given frequency to the one set by the last note on
+  pub(crate) fn glide_from(&mut self, program: &Program<F>, from_pitch: F, samples: usize) {
+    let target = self.key_pitch;
+    if samples > 0 {
+      self.glide = Glide {
+        target,
+        step: (target - from_pitch) / F::val(samples),
+        remaining: samples,
+      };
+      self.key_pitch = from_pitch;
+      self.update_note_pitch(program);
+    }
+  }
+
+  /// Bend the note pitch by the given amount of semitones, it stays until changed again
+  pub fn set_pitch_bend(&mut self, program: &Program<F>, semitones: F) {
+    self.signals[program.voice().pitch_bend.0].set(semitones);
+    self.bend_multiplier = F::val(2.0).powf(semitones / F::val(12.0));
+    self.update_note_pitch(program);
+  }
+
+  /// Detune the note pitch by the given amount of cents
+  pub fn set_detune(&mut self, program: &Program<F>, cents: F) {
+    self.detune_multiplier = F::val(2.0).powf(cents / F::val(1200.0));
+    self.update_note_pitch(program);
+  }
+
+  pub fn set_aftertouch(&mut self, program: &Program<F>, aftertouch: F) {
+    self.signals[program.voice().aftertouch.0].set(aftertouch);
+  }
+
+  pub fn set_poly_pressure(&mut self, program: &Program<F>, pressure: F) {
+    self.signals[program.voice().poly_pressure.0].set(pressure);
+  }
+
+  fn update_note_pitch(&mut self, program: &Program<F>) {
+    let note_pitch = self.key_pitch * self.bend_multiplier * self.detune_multiplier;
+    self.signals[program.voice().note_pitch.0].set(note_pitch);
+  }
+
   pub(crate) fn note_off(&mut self, program: &Program<F>) {
     self.signals[program.voice().gate.0].set(F::zero());
   }
 
+  /// Reset to silence and mark the voice as off, as when its envelope ends
+  pub(crate) fn silence(&mut self, program: &Program<F>) {
+    self.reset(program);
+    self.signals[program.voice().off.0].set(F::one());
+  }
+
   pub(crate) fn process(&mut self, program: &mut Program<F>, synth_globals: &SynthGlobals<F>) {
+    self.update_seed(synth_globals);
+    self.process_frame(program, synth_globals);
+
+    // The trigger is an spike of 1 sample: note_on raises it, the processors see it high
+    // in the first processed sample, and it is cleared right after it whatever the block size
+    self.signals[program.voice().trigger.0].set(F::zero());
+  }
+
+  /// Process a number of frames writing the output directly into the slices.
+  /// The seed, the trigger and the lookup of the output signals are handled once
+  /// for the whole block, only the glide and the processors run for every frame.
+  pub(crate) fn process_block(
+    &mut self,
+    program: &mut Program<F>,
+    synth_globals: &SynthGlobals<F>,
+    frames: usize,
+    out_left: &mut [F],
+    out_right: &mut [F],
+  ) {
+    self.update_seed(synth_globals);
+    let voice = program.voice();
+    let (trigger, output_left, output_right) =
+      (voice.trigger.0, voice.output_left.0, voice.output_right.0);
+
+    let outputs = out_left.iter_mut().zip(out_right.iter_mut()).take(frames);
+    for (frame, (left, right)) in outputs.enumerate() {
+      self.process_frame(program, synth_globals);
+      if frame == 0 {
+        self.signals[trigger].set(F::zero());
+      }
+      *left = self.signals[output_left].get();
+      *right = self.signals[output_right].get();
+    }
+  }
+
+  fn update_seed(&mut self, synth_globals: &SynthGlobals<F>) {
+    if self.master_seed != Some(synth_globals.seed) {
+      self.master_seed = Some(synth_globals.seed);
+      self.set_seed(synth_globals.voice_seed(self.index));
+    }
+  }
+
+  fn process_frame(&mut self, program: &mut Program<F>, synth_globals: &SynthGlobals<F>) {
+    if self.glide.remaining > 0 {
+      self.glide.remaining -= 1;
+      self.key_pitch = if self.glide.remaining == 0 {
+        self.glide.target
+      } else {
+        self.key_pitch + self.glide.step
+      };
+      self.update_note_pitch(program);
+    }
+
     let mut signals = SignalBus::new(self.signals.deref_mut());
 
     for processor in self.processors.iter_mut() {
@@ -96,15 +330,17 @@ impl<F: Float> Voice<F> {
 
     signals.update();
 
-    // The trigger does an spike of 1 sample
-    let voice = program.voice();
-    if signals[voice.trigger].get() > F::zero() {
-      signals[voice.trigger].set(F::zero())
-    }
-
     // println!("{:?}", self.signals.iter_mut().skip(3)/*.take(2)*/.map(|s| (s.get(), s.state())).collect::<Vec<(F, SignalState), MaxSignals>>());
   }
 
+  /// Write the output of every channel into the slice, as many as fit in it
+  pub fn output_into(&self, program: &Program<F>, out: &mut [F]) {
+    for (value, output) in out.iter_mut().zip(program.voice().output_channels.iter()) {
+      *value = self.signals[output.0].get();
+    }
+  }
+
+  /// The output of the left and right channels
   pub(crate) fn output(&self, program: &Program<F>) -> (F, F) {
     let voice = program.voice();
     (
@@ -113,3 +349,199 @@ impl<F: Float> Voice<F> {
     )
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use kiro_synth_dsp::fixed::Fixed;
+
+  use crate::float::Float;
+  use crate::globals::SynthGlobals;
+  use crate::program::blocks::{envgen, osc};
+  use crate::program::{Block, Program, ProgramBuilder};
+  use crate::voice::Voice;
+
+  #[test]
+  fn signal_by_name() {
+    let mut builder = ProgramBuilder::new();
+    let level = builder.const_value(0.5);
+    builder.name_signal(level, "level");
+    builder.name_signal(builder.voice().velocity, "velocity");
+    builder.out(level, level);
+    let program = builder.build();
+
+    let mut voice = Voice::new(44100.0, &program);
+    voice.note_on(&program, 60, 261.626, 0.75);
+
+    assert_eq!(voice.signal_by_name(&program, "level"), Some(0.5));
+    assert_eq!(voice.signal_by_name(&program, "velocity"), Some(0.75));
+    assert_eq!(voice.signal_by_name(&program, "unknown"), None);
+    let names: Vec<&str> = voice.signal_names(&program).map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["level", "velocity"]);
+  }
+
+  #[test]
+  fn output_into_four_channels() {
+    let mut builder = ProgramBuilder::with_output_channels(4);
+    let channels = [
+      builder.const_value(0.1),
+      builder.const_value(0.2),
+      builder.const_value(0.3),
+      builder.const_value(0.4),
+    ];
+    builder.out_channels(&channels);
+    let mut program = builder.build();
+    let globals = SynthGlobals::new();
+
+    fn assert_output(voice: &Voice<f32>, program: &Program<f32>, expected: [f32; 4]) {
+      let mut out = [1.0; 4];
+      voice.output_into(program, &mut out);
+      for (value, expected) in out.iter().zip(expected.iter()) {
+        assert!((value - expected).abs() < 1e-6, "{:?}", out);
+      }
+    }
+
+    let mut voice = Voice::new(44100.0, &program);
+    voice.note_on(&program, 60, 261.626, 1.0);
+    assert_output(&voice, &program, [0.0; 4]);
+
+    voice.process(&mut program, &globals);
+    assert_output(&voice, &program, [0.1, 0.2, 0.3, 0.4]);
+    let (left, right) = voice.output(&program);
+    assert!((left - 0.1).abs() < 1e-6 && (right - 0.2).abs() < 1e-6);
+
+    voice.reset(&program);
+    assert_output(&voice, &program, [0.0; 4]);
+  }
+
+  fn trigger_program<'a>() -> Program<'a, f32> {
+    let mut builder = ProgramBuilder::new();
+    let voice = builder.voice().clone();
+    builder.out(voice.trigger, voice.gate);
+    builder.build()
+  }
+
+  /// Indices of the samples where the trigger is high
+  fn triggered_samples(
+    voice: &mut Voice<f32>,
+    program: &mut Program<f32>,
+    chunks: &[usize],
+  ) -> Vec<usize> {
+    let globals = SynthGlobals::new();
+    let mut triggered = Vec::new();
+    let mut offset = 0;
+    for chunk in chunks {
+      let mut left = vec![0.0; *chunk];
+      let mut right = vec![0.0; *chunk];
+      voice.process_block(program, &globals, *chunk, &mut left, &mut right);
+      for (index, value) in left.iter().enumerate() {
+        if *value > 0.0 {
+          triggered.push(offset + index);
+        }
+      }
+      offset += chunk;
+    }
+    triggered
+  }
+
+  #[test]
+  fn trigger_is_high_for_one_sample() {
+    let mut program = trigger_program();
+    let mut voice = Voice::new(44100.0, &program);
+    let globals = SynthGlobals::new();
+
+    voice.note_on(&program, 60, 261.626, 1.0);
+    assert!(voice.is_trigger_on(&program));
+    voice.process(&mut program, &globals);
+    assert!((voice.output(&program).0 - 1.0).abs() < 1e-6);
+    assert!(!voice.is_trigger_on(&program));
+    for _ in 0..10 {
+      voice.process(&mut program, &globals);
+      assert!(voice.output(&program).0.abs() < 1e-6);
+    }
+
+    for chunks in [vec![1, 1, 1], vec![3, 64], vec![64, 64], vec![1000]].iter() {
+      voice.note_on(&program, 62, 293.665, 1.0);
+      assert_eq!(triggered_samples(&mut voice, &mut program, chunks), vec![0]);
+    }
+
+    voice.note_on(&program, 60, 261.626, 1.0);
+    voice.note_on(&program, 64, 329.628, 1.0);
+    assert_eq!(triggered_samples(&mut voice, &mut program, &[16]), vec![0]);
+
+    voice.legato_note_on(&program, 67, 391.995);
+    assert!(triggered_samples(&mut voice, &mut program, &[16]).is_empty());
+  }
+
+  #[test]
+  fn signal_by_name_without_names() {
+    let program = ProgramBuilder::<f32>::new().build();
+    let voice = Voice::new(44100.0, &program);
+    assert_eq!(voice.signal_by_name(&program, "velocity"), None);
+    assert_eq!(voice.signal_names(&program).count(), 0);
+  }
+
+  fn render_osc_with_envelope<F: Float>() -> Vec<f32> {
+    let mut builder = ProgramBuilder::<F>::new();
+    let voice = builder.voice().clone();
+    let zero = builder.const_zero();
+    let decay = builder.const_value(F::val(0.05));
+    let sustain = builder.const_value(F::val(0.5));
+    let (envelope, envelope_biased) = (builder.signal(), builder.signal());
+    builder.block(Block::EG(envgen::Block {
+      inputs: envgen::Inputs {
+        attack: zero,
+        decay,
+        sustain,
+        release: zero,
+        mode: zero,
+        legato: zero,
+        reset_to_zero: zero,
+      },
+      outputs: envgen::Outputs {
+        normal: envelope,
+        biased: envelope_biased,
+        voice_off: voice.off,
+      },
+    }));
+    let (output, sync_output) = (builder.signal(), builder.signal());
+    builder.block(Block::Osc(osc::Block {
+      inputs: osc::Inputs {
+        shape: zero,
+        amplitude: envelope,
+        amp_mod: zero,
+        octaves: zero,
+        semitones: zero,
+        cents: zero,
+        note_pitch: voice.note_pitch,
+        pitch_bend: voice.pitch_bend,
+        freq_mod: zero,
+        pulse_width: zero,
+        sync: zero,
+      },
+      output,
+      sync_output,
+    }));
+    builder.out(output, output);
+    let mut program = builder.build();
+    let globals = SynthGlobals::new();
+
+    let mut voice = Voice::new(F::val(44100), &program);
+    voice.note_on(&program, 69, F::val(440), F::one());
+    (0..2205)
+      .map(|_| {
+        voice.process(&mut program, &globals);
+        voice.output(&program).0.to_f32().unwrap()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn fixed_point_voice_follows_f32() {
+    let fixed = render_osc_with_envelope::<Fixed>();
+    let float = render_osc_with_envelope::<f32>();
+    assert!(float.iter().any(|value| value.abs() > 0.5));
+    for (fixed, float) in fixed.iter().zip(float.iter()) {
+      assert!((fixed - float).abs() < 5e-3);
+    }
+  }
+}
@@ -1,10 +1,14 @@
 use heapless::consts;
 use heapless::Vec;
+use typenum::marker_traits::Unsigned;
 
 use kiro_synth_dsp::oscillators::osc_waveform::OscWaveform;
+use kiro_synth_dsp::waveforms::random_sample_hold::RandomSampleHold;
 use kiro_synth_dsp::waveforms::saw_blep::{self, SawBlep};
 use kiro_synth_dsp::waveforms::saw_trivial::SawTrivial;
 use kiro_synth_dsp::waveforms::sine_parabolic::SineParabolic;
+use kiro_synth_dsp::waveforms::square_blep::SquareBlep;
+use kiro_synth_dsp::waveforms::square_trivial::SquareTrivial;
 use kiro_synth_dsp::waveforms::triangle_dpw2x::TriangleDpw2x;
 use kiro_synth_dsp::waveforms::triangle_trivial::TriangleTrivial;
 
@@ -31,6 +35,7 @@ impl<F: Float> OscWaveforms<F> {
               .with_correction(saw_blep::Correction::EightPointBlepWithInterpolation),
           ),
         ),
+        ("sqr", OscWaveform::SquareBlep(SquareBlep::default())),
       ])
       .ok();
 
@@ -68,6 +73,11 @@ impl<F: Float> LfoWaveforms<F> {
           OscWaveform::TriangleTrivial(TriangleTrivial::default()),
         ),
         ("saw", OscWaveform::SawTrivial(SawTrivial::default())),
+        ("sqr", OscWaveform::SquareTrivial(SquareTrivial::default())),
+        (
+          "rnd",
+          OscWaveform::RandomSampleHold(RandomSampleHold::default()),
+        ),
       ])
       .ok();
     LfoWaveforms(waveforms)
@@ -89,3 +99,163 @@ impl<F: Float> LfoWaveforms<F> {
     &self.0[index].1
   }
 }
+
+/// Number of samples for the frames of the wave tables
+pub type WaveTableSize = consts::U256;
+/// Capacity for a frame and all its mip levels
+type WaveTableMipSize = consts::U512;
+pub type MaxWaveTableFrames = consts::U16;
+type MaxWaveTables = consts::U4;
+
+/// Single cycle of a wave table followed by its mip levels, each one half the length of the previous
+#[derive(Debug, Clone, Default)]
+struct WaveTableFrame<F: Float>(Vec<F, WaveTableMipSize>);
+
+impl<F: Float> WaveTableFrame<F> {
+  fn new(samples: &[F]) -> Self {
+    let size = WaveTableSize::to_usize();
+    let mut data: Vec<F, WaveTableMipSize> = Vec::new();
+
+    // resample the cycle into the size of the table
+    let step = F::val(samples.len()) / F::val(size);
+    for index in 0..size {
+      data
+        .push(Self::interpolate(samples, F::val(index) * step))
+        .unwrap();
+    }
+
+    // every mip level averages pairs of samples from the previous one
+    let mut start = 0;
+    let mut len = size;
+    while len > 1 {
+      for index in 0..len / 2 {
+        let sample = (data[start + 2 * index] + data[start + 2 * index + 1]) * F::val(0.5);
+        data.push(sample).unwrap();
+      }
+      start += len;
+      len /= 2;
+    }
+
+    WaveTableFrame(data)
+  }
+
+  fn interpolate(samples: &[F], position: F) -> F {
+    let len = samples.len();
+    let index = position.floor().to_usize().unwrap_or(0).min(len - 1);
+    let fraction = position - F::val(index);
+    let next = samples[(index + 1) % len];
+    samples[index] + (next - samples[index]) * fraction
+  }
+
+  fn sample(&self, level: usize, modulo: F) -> F {
+    let size = WaveTableSize::to_usize();
+    let start = 2 * size - 2 * (size >> level);
+    let len = size >> level;
+    Self::interpolate(&self.0[start..start + len], modulo * F::val(len))
+  }
+}
+
+/// Set of single cycle frames that can be morphed between
+#[derive(Debug, Clone, Default)]
+pub struct WaveTable<F: Float> {
+  frames: Vec<WaveTableFrame<F>, MaxWaveTableFrames>,
+}
+
+impl<F: Float> WaveTable<F> {
+  pub fn new() -> Self {
+    WaveTable { frames: Vec::new() }
+  }
+
+  /// Adds a frame from the samples of a single cycle of any length.
+  /// Returns the index of the frame or none if the table is full or there are no samples.
+  pub fn push_frame(&mut self, samples: &[F]) -> Option<usize> {
+    if samples.is_empty() {
+      return None;
+    }
+    self.frames.push(WaveTableFrame::new(samples)).ok()?;
+    Some(self.frames.len() - 1)
+  }
+
+  pub fn len(&self) -> usize {
+    self.frames.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.frames.is_empty()
+  }
+
+  /// Get a sample for the position between frames (from 0 to 1) and the modulo of the cycle.
+  /// The phase increment selects the mip level to avoid aliasing.
+  pub fn sample(&self, position: F, modulo: F, phase_inc: F) -> F {
+    if self.frames.is_empty() {
+      return F::zero();
+    }
+
+    let level = Self::mip_level(phase_inc);
+    let last = self.frames.len() - 1;
+    let position = position.max(F::zero()).min(F::one()) * F::val(last);
+    let index = position.floor().to_usize().unwrap_or(0).min(last);
+    let fraction = position - F::val(index);
+    let current = self.frames[index].sample(level, modulo);
+    let next = self.frames[(index + 1).min(last)].sample(level, modulo);
+    current + (next - current) * fraction
+  }
+
+  /// The highest harmonic of a level with N samples is N/2, so it has to satisfy N * phase_inc <= 1
+  fn mip_level(phase_inc: F) -> usize {
+    let mut level = 0;
+    let mut len = WaveTableSize::to_usize();
+    while len > 2 && F::val(len) * phase_inc.abs() > F::one() {
+      len /= 2;
+      level += 1;
+    }
+    level
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WaveTables<F: Float>(Vec<(&'static str, WaveTable<F>), MaxWaveTables>);
+
+impl<F: Float> WaveTables<F> {
+  pub fn new() -> Self {
+    WaveTables(Vec::new())
+  }
+
+  /// Adds a table returning its index, or none if there is no more space
+  pub fn add(&mut self, name: &'static str, table: WaveTable<F>) -> Option<usize> {
+    self.0.push((name, table)).ok()?;
+    Some(self.0.len() - 1)
+  }
+
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  pub fn name(&self, index: usize) -> &'static str {
+    self.0[index].0
+  }
+
+  pub fn table(&self, index: usize) -> Option<&WaveTable<F>> {
+    self.0.get(index).map(|(_, table)| table)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::waveforms::WaveTable;
+
+  #[test]
+  fn wave_table_morphs_between_frames() {
+    let mut table = WaveTable::<f32>::new();
+    assert_eq!(table.push_frame(&[0.0, 0.0]), Some(0));
+    assert_eq!(table.push_frame(&[1.0]), Some(1));
+
+    assert!(table.sample(0.0, 0.3, 0.01).abs() < 1e-6);
+    assert!((table.sample(0.25, 0.3, 0.01) - 0.25).abs() < 1e-6);
+    assert!((table.sample(1.0, 0.3, 0.4) - 1.0).abs() < 1e-6);
+  }
+}
@@ -0,0 +1,233 @@
+//! The audio thread must neither allocate nor free memory.
+//! This runs in its own binary because it replaces the global allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ringbuf::RingBuffer;
+
+use kiro_synth_engine::event::{Event, Message};
+use kiro_synth_engine::globals::SynthGlobals;
+use kiro_synth_engine::program::blocks::{delay, envgen, filter, lfo, noise, osc};
+use kiro_synth_engine::program::{Block, ParamScale, ParamUnit, ParamValues, ProgramBuilder};
+use kiro_synth_engine::synth::Synth;
+
+struct CountingAllocator;
+
+static AUDIO_THREAD_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+  static AUDIO_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+fn count_if_audio_thread() {
+  if AUDIO_THREAD.with(|audio_thread| audio_thread.get()) {
+    AUDIO_THREAD_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+  }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    count_if_audio_thread();
+    System.alloc(layout)
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    count_if_audio_thread();
+    System.dealloc(ptr, layout)
+  }
+
+  unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    count_if_audio_thread();
+    System.realloc(ptr, layout, new_size)
+  }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Run the closure as if it was the audio thread, returning the number of allocations
+fn audio_thread_allocations<R, A: FnOnce() -> R>(audio: A) -> usize {
+  AUDIO_THREAD_ALLOCATIONS.store(0, Ordering::SeqCst);
+  AUDIO_THREAD.with(|audio_thread| audio_thread.set(true));
+  audio();
+  AUDIO_THREAD.with(|audio_thread| audio_thread.set(false));
+  AUDIO_THREAD_ALLOCATIONS.load(Ordering::SeqCst)
+}
+
+#[test]
+fn prepare_and_process_do_not_allocate() {
+  let mut program = ProgramBuilder::new();
+  let voice = program.voice().clone();
+  let zero = program.const_zero();
+  let one = program.const_one();
+  let half = program.const_value(0.5);
+
+  let cutoff = program.param(
+    "cutoff",
+    ParamValues {
+      initial_value: 2000.0,
+      origin: 20.0,
+      min: 20.0,
+      max: 20000.0,
+      resolution: 1.0,
+      unit: ParamUnit::Hertz,
+      display_scale: ParamScale::Logarithmic,
+    },
+  );
+  program.block(Block::Param(cutoff.clone()));
+
+  let modulator = program.signal();
+  program.block(Block::Lfo(lfo::Block {
+    inputs: lfo::Inputs {
+      shape: zero,
+      rate: one,
+      phase: zero,
+      depth: one,
+      sync: zero,
+      retrigger: one,
+    },
+    output: modulator,
+  }));
+  let lfo_source = program.source("lfo", modulator);
+
+  let envelope = program.signal();
+  let envelope_biased = program.signal();
+  program.block(Block::EG(envgen::Block {
+    inputs: envgen::Inputs {
+      attack: zero,
+      decay: half,
+      sustain: half,
+      release: zero,
+      mode: zero,
+      legato: zero,
+      reset_to_zero: zero,
+    },
+    outputs: envgen::Outputs {
+      normal: envelope,
+      biased: envelope_biased,
+      voice_off: voice.off,
+    },
+  }));
+
+  let oscillator = program.signal();
+  let oscillator_sync = program.signal();
+  program.block(Block::Osc(osc::Block {
+    inputs: osc::Inputs {
+      shape: one,
+      amplitude: envelope,
+      amp_mod: zero,
+      octaves: zero,
+      semitones: zero,
+      cents: zero,
+      note_pitch: voice.note_pitch,
+      pitch_bend: voice.pitch_bend,
+      freq_mod: zero,
+      pulse_width: half,
+      sync: zero,
+    },
+    output: oscillator,
+    sync_output: oscillator_sync,
+  }));
+
+  let pink = program.signal();
+  program.block(Block::Noise(noise::Block {
+    inputs: noise::Inputs { kind: one },
+    output: pink,
+  }));
+
+  let filtered = program.signal();
+  program.block(Block::Filter(filter::Block {
+    input: oscillator,
+    params: filter::Params {
+      mode: zero,
+      freq: cutoff.out_signal_ref,
+      freq_mod: zero,
+      q: half,
+    },
+    output: filtered,
+  }));
+
+  let delayed = program.signal();
+  let delay = program.block(Block::Delay(delay::Block {
+    inputs: delay::Inputs {
+      input: filtered,
+      time: half,
+      feedback: half,
+    },
+    output: delayed,
+  }));
+  program.out(delayed, pink);
+
+  let (mut events, consumer) = RingBuffer::<Event<f32>>::new(64).split();
+  let mut synth = Synth::new(44100.0, consumer, program.build(), SynthGlobals::new());
+
+  let messages = vec![
+    Message::NoteOn {
+      key: 60,
+      velocity: 1.0,
+    },
+    Message::NoteOn {
+      key: 64,
+      velocity: 0.5,
+    },
+    Message::ParamValue {
+      param_ref: cutoff.reference,
+      value: 800.0,
+    },
+    Message::ParamValueUnclamped {
+      param_ref: cutoff.reference,
+      value: 900.0,
+    },
+    Message::ParamChange {
+      param_ref: cutoff.reference,
+      change: 100.0,
+    },
+    Message::ModulationUpdate {
+      source_ref: lfo_source,
+      param_ref: cutoff.reference,
+      amount: 200.0,
+    },
+    Message::ControlChange { cc: 1, value: 0.5 },
+    Message::Sustain { on: true },
+    Message::Aftertouch { value: 0.3 },
+    Message::PolyPressure {
+      key: 60,
+      value: 0.2,
+    },
+    Message::PitchBend { amount: 0.5 },
+    Message::Tempo { bpm: 90.0 },
+    Message::SampleRate {
+      sample_rate: 48000.0,
+    },
+    Message::ResetBlock { block_ref: delay },
+    Message::NoteOff {
+      key: 60,
+      velocity: 0.0,
+    },
+    Message::Sustain { on: false },
+    Message::ModulationDelete {
+      source_ref: lfo_source,
+      param_ref: cutoff.reference,
+    },
+    Message::NoteOff {
+      key: 64,
+      velocity: 0.0,
+    },
+  ];
+  let mut left = vec![0.0; 256];
+  let mut right = vec![0.0; 256];
+
+  for message in messages {
+    events.push(Event::now(message)).unwrap();
+    let allocations = audio_thread_allocations(|| {
+      synth.prepare();
+      for _ in 0..100 {
+        synth.process();
+      }
+      synth.process_block(&mut left, &mut right);
+    });
+    assert_eq!(allocations, 0);
+  }
+}
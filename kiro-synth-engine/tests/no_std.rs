@@ -0,0 +1,77 @@
+//! Plays a note through the voice allocator, the API available without the `std` feature.
+//! Run it with `cargo test --no-default-features` from this directory to check the no_std build.
+
+use kiro_synth_engine::allocator::VoiceAllocator;
+use kiro_synth_engine::globals::SynthGlobals;
+use kiro_synth_engine::program::blocks::{envgen, osc};
+use kiro_synth_engine::program::{Block, ProgramBuilder};
+
+#[test]
+fn note_on_and_process() {
+  let mut program = ProgramBuilder::new();
+  let voice = program.voice().clone();
+  let zero = program.const_zero();
+  let one = program.const_one();
+  let half = program.const_value(0.5);
+
+  let envelope = program.signal();
+  let envelope_biased = program.signal();
+  program.block(Block::EG(envgen::Block {
+    inputs: envgen::Inputs {
+      attack: zero,
+      decay: half,
+      sustain: half,
+      release: zero,
+      mode: zero,
+      legato: zero,
+      reset_to_zero: zero,
+    },
+    outputs: envgen::Outputs {
+      normal: envelope,
+      biased: envelope_biased,
+      voice_off: voice.off,
+    },
+  }));
+
+  let oscillator = program.signal();
+  let oscillator_sync = program.signal();
+  program.block(Block::Osc(osc::Block {
+    inputs: osc::Inputs {
+      shape: one,
+      amplitude: envelope,
+      amp_mod: zero,
+      octaves: zero,
+      semitones: zero,
+      cents: zero,
+      note_pitch: voice.note_pitch,
+      pitch_bend: voice.pitch_bend,
+      freq_mod: zero,
+      pulse_width: half,
+      sync: zero,
+    },
+    output: oscillator,
+    sync_output: oscillator_sync,
+  }));
+  program.out(oscillator, oscillator);
+
+  let mut program = program.build();
+  let globals = SynthGlobals::new();
+  let mut allocator = VoiceAllocator::new(44100.0f32, &program);
+
+  allocator.note_on(&program, 69, 1.0);
+  assert_eq!(allocator.get_num_active_voices(), 1);
+
+  let mut peak = 0.0f32;
+  for _ in 0..441 {
+    let (left, right) = allocator.process(&mut program, &globals);
+    assert!((left - right).abs() < 1e-6);
+    peak = peak.max(left.abs());
+  }
+  assert!(peak > 0.1);
+
+  allocator.note_off(&program, 69);
+  for _ in 0..4410 {
+    allocator.process(&mut program, &globals);
+  }
+  assert_eq!(allocator.get_num_active_voices(), 0);
+}
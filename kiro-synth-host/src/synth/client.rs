@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::fmt::Formatter;
 use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
@@ -14,6 +15,8 @@ use crate::synth::SynthFeedback;
 pub struct SynthClient<F: Float> {
   globals: SynthGlobals<F>,
   events: Producer<Event<F>>,
+  /// Events that didn't fit in the queue yet
+  pending: VecDeque<Event<F>>,
   feedback: Consumer<SynthFeedback>,
 }
 
@@ -26,6 +29,7 @@ impl<F: Float> SynthClient<F> {
     SynthClient {
       globals,
       events,
+      pending: VecDeque::new(),
       feedback,
     }
   }
@@ -38,8 +42,44 @@ impl<F: Float> SynthClient<F> {
     &self.globals.lfo_waveforms
   }
 
+  /// When the queue is full the event is kept to be sent in order by the next calls,
+  /// replacing any pending value for the same parameter as it is superseded by the new one.
   pub fn send_event(&mut self, event: Event<F>) {
-    drop(self.events.push(event));
+    if !self.flush_pending() {
+      self.keep_pending(event);
+    } else if let Err(event) = self.events.push(event) {
+      self.pending.push_back(event);
+    }
+  }
+
+  /// Try to send the events that didn't fit in the queue, returns whether all of them were sent
+  pub fn flush_pending(&mut self) -> bool {
+    while let Some(event) = self.pending.pop_front() {
+      if let Err(event) = self.events.push(event) {
+        self.pending.push_front(event);
+        return false;
+      }
+    }
+    true
+  }
+
+  fn keep_pending(&mut self, event: Event<F>) {
+    if let Message::ParamValue { param_ref, .. } = event.message {
+      let superseded = self
+        .pending
+        .iter()
+        .position(|pending| match pending.message {
+          Message::ParamValue {
+            param_ref: pending_ref,
+            ..
+          } => pending_ref == param_ref,
+          _ => false,
+        });
+      if let Some(index) = superseded {
+        self.pending.remove(index);
+      }
+    }
+    self.pending.push_back(event);
   }
 
   pub fn send_note_on(&mut self, key: u8, velocity: F) {
@@ -126,7 +166,10 @@ impl<F: Float> SynthClientMutex<F> {
   pub fn get_feedback(
     &mut self,
   ) -> Result<Option<SynthFeedback>, PoisonError<MutexGuard<'_, SynthClient<F>>>> {
-    self.0.lock().map(|mut client| client.feedback.pop())
+    self.0.lock().map(|mut client| {
+      client.flush_pending();
+      client.feedback.pop()
+    })
   }
 }
 
@@ -135,3 +178,49 @@ impl<F: Float> std::fmt::Debug for SynthClientMutex<F> {
     f.write_str("SynthClient")
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use ringbuf::RingBuffer;
+
+  use kiro_synth_engine::event::{Event, Message};
+  use kiro_synth_engine::globals::SynthGlobals;
+  use kiro_synth_engine::program::ParamRef;
+
+  use crate::synth::client::SynthClient;
+  use crate::synth::SynthFeedback;
+
+  fn param_value(message: &Message<f32>) -> Option<(ParamRef, f32)> {
+    match message {
+      Message::ParamValue { param_ref, value } => Some((*param_ref, *value)),
+      _ => None,
+    }
+  }
+
+  #[test]
+  fn pending_events_are_sent_when_the_queue_drains() {
+    let (events, mut consumer) = RingBuffer::<Event<f32>>::new(1).split();
+    let (_, feedback) = RingBuffer::<SynthFeedback>::new(1).split();
+    let mut client = SynthClient::new(SynthGlobals::new(), events, feedback);
+    let cutoff = ParamRef::new(0);
+    let resonance = ParamRef::new(1);
+
+    client.send_param_value(cutoff, 0.1);
+    client.send_param_value(cutoff, 0.2);
+    client.send_param_value(resonance, 0.5);
+    client.send_param_value(cutoff, 0.3);
+    assert!(!client.flush_pending());
+
+    let mut received = Vec::new();
+    while let Some(event) = consumer.pop() {
+      received.push(param_value(&event.message).unwrap());
+      client.flush_pending();
+    }
+
+    assert!(client.flush_pending());
+    assert_eq!(
+      received,
+      vec![(cutoff, 0.1), (resonance, 0.5), (cutoff, 0.3)]
+    );
+  }
+}
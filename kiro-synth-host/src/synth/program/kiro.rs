@@ -78,7 +78,8 @@ impl KiroModule {
     let voice = program.voice().clone();
 
     let zero = program.const_zero();
-    // let one = program.const_one();
+    let one = program.const_one();
+    let half = program.const_value(F::val(0.5));
 
     let num_filters = filter::Mode::count();
 
@@ -206,6 +207,8 @@ impl KiroModule {
         rate: params.lfo1.rate.out_signal_ref,
         phase: params.lfo1.phase.out_signal_ref,
         depth: params.lfo1.depth.out_signal_ref,
+        sync: zero,
+        retrigger: one,
       },
       output: signals.lfo1,
     };
@@ -216,6 +219,8 @@ impl KiroModule {
         rate: params.lfo2.rate.out_signal_ref,
         phase: params.lfo2.phase.out_signal_ref,
         depth: params.lfo2.depth.out_signal_ref,
+        sync: zero,
+        retrigger: one,
       },
       output: signals.lfo2,
     };
@@ -257,8 +262,11 @@ impl KiroModule {
         note_pitch: voice.note_pitch,
         pitch_bend: params.pitch_bend.out_signal_ref,
         freq_mod: zero,
+        pulse_width: half,
+        sync: zero,
       },
       output: signals.osc1,
+      sync_output: program.signal(),
     };
 
     let osc2 = osc::Block {
@@ -272,8 +280,11 @@ impl KiroModule {
         note_pitch: voice.note_pitch,
         pitch_bend: params.pitch_bend.out_signal_ref,
         freq_mod: zero,
+        pulse_width: half,
+        sync: zero,
       },
       output: signals.osc2,
+      sync_output: program.signal(),
     };
 
     let osc3 = osc::Block {
@@ -287,8 +298,11 @@ impl KiroModule {
         note_pitch: voice.note_pitch,
         pitch_bend: params.pitch_bend.out_signal_ref,
         freq_mod: zero,
+        pulse_width: half,
+        sync: zero,
       },
       output: signals.osc3,
+      sync_output: program.signal(),
     };
 
     let osc4 = osc::Block {
@@ -302,8 +316,11 @@ impl KiroModule {
         note_pitch: voice.note_pitch,
         pitch_bend: params.pitch_bend.out_signal_ref,
         freq_mod: zero,
+        pulse_width: half,
+        sync: zero,
       },
       output: signals.osc4,
+      sync_output: program.signal(),
     };
 
     let osc_mix = program.expr(|expr| {
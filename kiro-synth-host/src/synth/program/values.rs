@@ -1,6 +1,6 @@
 use kiro_synth_dsp::filters::freq_control::FreqControl;
 use kiro_synth_dsp::float::Float;
-use kiro_synth_engine::program::ParamValues;
+use kiro_synth_engine::program::{ParamScale, ParamUnit, ParamValues};
 use num_traits::ToPrimitive;
 
 pub fn pitch_bend<F: Float>() -> ParamValues<F> {
@@ -10,6 +10,8 @@ pub fn pitch_bend<F: Float>() -> ParamValues<F> {
     min: F::one().neg(),
     max: F::one(),
     resolution: F::epsilon(),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -20,6 +22,8 @@ pub fn adsr<F: Float, T: ToPrimitive>(value: T) -> ParamValues<F> {
     min: F::zero(),
     max: F::val(10.0),
     resolution: F::val(0.01),
+    unit: ParamUnit::Seconds,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -30,6 +34,8 @@ pub fn eg_mode<F: Float>() -> ParamValues<F> {
     min: F::zero(),
     max: F::one(),
     resolution: F::one(),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -40,6 +46,8 @@ pub fn boolean<F: Float>(initial: bool) -> ParamValues<F> {
     min: F::zero(),
     max: F::one(),
     resolution: F::one(),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -50,6 +58,8 @@ pub fn enumeration<F: Float>(count: usize) -> ParamValues<F> {
     min: F::zero(),
     max: F::val(count - 1),
     resolution: F::one(),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -60,6 +70,8 @@ pub fn amplitude<F: Float>() -> ParamValues<F> {
     min: F::zero(),
     max: F::one(),
     resolution: F::val(0.01),
+    unit: ParamUnit::Decibels,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -70,6 +82,8 @@ pub fn amplitude_db<F: Float>() -> ParamValues<F> {
     min: F::val(-96.0),
     max: F::val(24.0),
     resolution: F::val(0.1),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -80,6 +94,8 @@ pub fn octave<F: Float>() -> ParamValues<F> {
     min: F::val(-8.0),
     max: F::val(8.0),
     resolution: F::one(),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -90,6 +106,8 @@ pub fn semitones<F: Float>() -> ParamValues<F> {
     min: F::val(-12.0),
     max: F::val(12.0),
     resolution: F::one(),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -100,6 +118,8 @@ pub fn cents<F: Float>() -> ParamValues<F> {
     min: F::val(-100.0),
     max: F::val(100.0),
     resolution: F::one(),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -110,6 +130,8 @@ pub fn lfo_rate<F: Float>() -> ParamValues<F> {
     min: F::zero(),
     max: F::val(20.0),
     resolution: F::val(0.01),
+    unit: ParamUnit::Hertz,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -120,6 +142,8 @@ pub fn lfo_phase<F: Float>() -> ParamValues<F> {
     min: F::zero(),
     max: F::one(),
     resolution: F::val(1.0 / 8.0),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -130,6 +154,8 @@ pub fn filt_freq<F: Float>() -> ParamValues<F> {
     min: FreqControl::min_frequency(),
     max: FreqControl::max_frequency(),
     resolution: F::val(10.0),
+    unit: ParamUnit::Hertz,
+    display_scale: ParamScale::Logarithmic,
   }
 }
 
@@ -140,6 +166,8 @@ pub fn filt_q<F: Float>() -> ParamValues<F> {
     min: F::zero(),
     max: F::one(),
     resolution: F::val(0.01),
+    unit: ParamUnit::Percent,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -150,6 +178,8 @@ pub fn pan<F: Float>() -> ParamValues<F> {
     min: F::one().neg(),
     max: F::one(),
     resolution: F::val(0.01),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }
 
@@ -160,5 +190,7 @@ pub fn eg1_dca_amp_mod<F: Float>() -> ParamValues<F> {
     min: F::zero(),
     max: F::val(4.0),
     resolution: F::val(0.01),
+    unit: ParamUnit::Raw,
+    display_scale: ParamScale::Linear,
   }
 }